@@ -0,0 +1,342 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::expr::{self, Expr};
+use crate::figures::{
+    arc::Arc,
+    bezier::Bezier,
+    circle::Circle,
+    conic::{self, Conic},
+    function::FunctionGraph,
+    parametric::Parametric,
+    point::Point,
+    rectangle::Rectangle,
+    spline::Spline,
+    Figure,
+};
+use crate::tooltip::{format_number, format_point, format_sum};
+use crate::utils::round_to;
+
+const CONIC_TERMS: [&str; 6] = ["x²", "x·y", "y²", "x", "y", ""];
+// how far the equation may be off a polynomial of degree 2 and still count as one
+const FIT_TOLERANCE: f64 = 1e-9;
+
+// the figure written the way it can be typed in again to redefine it
+pub fn get_equation(figure: &Figure, precision: usize) -> String {
+    let n = |val: f64| format_number(val, precision);
+    match figure {
+        Figure::Point(p) => format_point(*p, precision),
+        Figure::Line(l) => {
+            if l.get_slope().is_infinite() {
+                format!("x = {}", n(l.get_point_a().get_x()))
+            } else {
+                format!(
+                    "y = {}",
+                    format_sum(&[l.get_slope(), l.get_y_intercept()], &["x", ""], precision)
+                )
+            }
+        }
+        Figure::Circle(c) => get_circle_equation(c.get_center(), c.get_rad(), precision),
+        // the angles are in degrees, counterclockwise from the start
+        Figure::Arc(a) => format!(
+            "{} [{}°, {}°]",
+            get_circle_equation(a.get_center(), a.get_rad(), precision),
+            n(a.get_start_angle().to_degrees()),
+            n((a.get_start_angle() + a.get_sweep()).to_degrees())
+        ),
+        Figure::Rectangle(r) => {
+            let (a, b) = (r.get_point_a(), r.get_point_b());
+            format_points(
+                &[
+                    a,
+                    Point::new(b.get_x(), a.get_y()),
+                    b,
+                    Point::new(a.get_x(), b.get_y()),
+                ],
+                precision,
+            )
+        }
+        Figure::Bezier(b) => format_points(b.get_controls(), precision),
+        Figure::Spline(s) => format_points(s.get_points(), precision),
+        Figure::Function(g) => format!("y = {}", g.get_source()),
+        Figure::Parametric(c) => {
+            let (t0, t1) = c.get_range();
+            format!("{} [{}, {}]", c.get_source(), n(t0), n(t1))
+        }
+        Figure::Conic(c) => {
+            // scaled so the largest coefficient is 1
+            let coefficients = c.get_coefficients();
+            let largest = coefficients
+                .iter()
+                .fold(0.0, |m: f64, c| if c.abs() > m.abs() { *c } else { m });
+            format!(
+                "{} = 0",
+                format_sum(&coefficients.map(|c| c / largest), &CONIC_TERMS, precision)
+            )
+        }
+    }
+}
+
+// a new figure of the same kind as old from an edited equation
+pub fn parse_equation(
+    input: &str,
+    old: &Figure,
+    params: &HashMap<String, f64>,
+) -> Result<Figure, String> {
+    let input = input.trim().replace('²', "^2").replace('·', "*");
+    let input = input.as_str();
+    match old {
+        Figure::Point(_) => match parse_points(input, params)?[..] {
+            [p] => Ok(Figure::Point(p)),
+            _ => Err(String::from("a point is written like (1, 2)")),
+        },
+        Figure::Line(_) => {
+            let [a, b, c, d, e, f] = fit_equation(input, params)?;
+            let scale = d.abs().max(e.abs()).max(f.abs());
+            if a.abs().max(b.abs()).max(c.abs()) > FIT_TOLERANCE * scale {
+                return Err(String::from(
+                    "a line needs a linear equation like y = 2x + 1",
+                ));
+            }
+            conic::to_line([d, e, f])
+                .map(Figure::Line)
+                .ok_or_else(|| String::from("the equation doesn't describe a line"))
+        }
+        Figure::Circle(_) => {
+            let (center, rad) = parse_circle(input, params)?;
+            Ok(Figure::Circle(Circle::new(
+                center,
+                Point::new(center.get_x() + rad, center.get_y()),
+            )))
+        }
+        Figure::Arc(a) => {
+            let (input, range) = expr::split_range(input);
+            let (start, end) = match range {
+                Some(range) => {
+                    let (start, end) = expr::parse_range(&range.replace('°', ""), params)?;
+                    (start.to_radians(), end.to_radians())
+                }
+                None => (a.get_start_angle(), a.get_start_angle() + a.get_sweep()),
+            };
+            let (center, rad) = parse_circle(input, params)?;
+            Ok(Figure::Arc(Arc::from_angles(
+                center,
+                rad,
+                start,
+                end - start,
+            )))
+        }
+        Figure::Rectangle(_) => match parse_points(input, params)?[..] {
+            [a, b] | [a, _, b, _] => Ok(Figure::Rectangle(Rectangle::new(a, b))),
+            _ => Err(String::from(
+                "a rectangle takes two opposite corners or all four",
+            )),
+        },
+        Figure::Bezier(b) => {
+            let points = parse_points(input, params)?;
+            if points.len() != b.get_controls().len() {
+                return Err(format!(
+                    "this curve takes {} points",
+                    b.get_controls().len()
+                ));
+            }
+            Ok(Figure::Bezier(Bezier::new(points)))
+        }
+        Figure::Spline(_) => {
+            let points = parse_points(input, params)?;
+            if points.len() < 2 {
+                return Err(String::from("a spline takes at least two points"));
+            }
+            Ok(Figure::Spline(Spline::new(points)))
+        }
+        Figure::Function(_) => {
+            let source = match input.split_once('=') {
+                Some((lhs, rhs)) if lhs.trim() == "y" || lhs.trim() == "f(x)" => rhs.trim(),
+                Some(_) => return Err(String::from("a function is written like y = x^2")),
+                None => input,
+            };
+            FunctionGraph::new(source, params.clone()).map(Figure::Function)
+        }
+        Figure::Parametric(c) => {
+            let (input, range) = expr::split_range(input);
+            let range = match range {
+                Some(range) => expr::parse_range(range, params)?,
+                None => c.get_range(),
+            };
+            if let Some(source) = input.strip_prefix("r").map(str::trim_start) {
+                if let Some(source) = source.strip_prefix('=') {
+                    return Parametric::polar(source.trim(), params.clone(), range)
+                        .map(Figure::Parametric);
+                }
+            }
+            let args = match input.strip_prefix('(').and_then(|i| i.strip_suffix(')')) {
+                Some(inner) => expr::split_arguments(inner),
+                None => vec![],
+            };
+            if args.len() != 2 {
+                return Err(String::from(
+                    "a curve is written like (cos(t), sin(t)) [0, 2pi]",
+                ));
+            }
+            Parametric::new(args[0], args[1], params.clone(), range).map(Figure::Parametric)
+        }
+        Figure::Conic(_) => {
+            let coefficients = fit_equation(input, params)?;
+            if coefficients.iter().all(|c| *c == 0.0) {
+                return Err(String::from("the equation holds everywhere"));
+            }
+            Ok(Figure::Conic(Conic::from_coefficients(coefficients)))
+        }
+    }
+}
+
+// (x - h)² + (y - k)² = r² with the signs worked out
+fn get_circle_equation(center: Point, rad: f64, precision: usize) -> String {
+    let square = |var: &str, shift: f64| {
+        let shift = round_to(shift, precision);
+        if shift == 0.0 {
+            format!("{}²", var)
+        } else if shift < 0.0 {
+            format!("({} + {})²", var, format_number(-shift, precision))
+        } else {
+            format!("({} - {})²", var, format_number(shift, precision))
+        }
+    };
+    format!(
+        "{} + {} = {}",
+        square("x", center.get_x()),
+        square("y", center.get_y()),
+        format_number(rad * rad, precision)
+    )
+}
+
+fn format_points(points: &[Point], precision: usize) -> String {
+    let points: Vec<String> = points.iter().map(|p| format_point(*p, precision)).collect();
+    points.join(", ")
+}
+
+// "(1, 2), (3, 4)" with any expressions for the coordinates
+fn parse_points(input: &str, params: &HashMap<String, f64>) -> Result<Vec<Point>, String> {
+    let lookup = |n: &str| params.get(n).copied();
+    expr::split_arguments(input)
+        .iter()
+        .map(|p| {
+            let inner = p
+                .strip_prefix('(')
+                .and_then(|p| p.strip_suffix(')'))
+                .ok_or_else(|| format!("'{}' is not a point like (1, 2)", p))?;
+            match expr::split_arguments(inner)[..] {
+                [x, y] => Ok(Point::new(
+                    expr::parse(x)?.eval(&lookup)?,
+                    expr::parse(y)?.eval(&lookup)?,
+                )),
+                _ => Err(format!("'{}' is not a point like (1, 2)", p)),
+            }
+        })
+        .collect()
+}
+
+fn parse_circle(input: &str, params: &HashMap<String, f64>) -> Result<(Point, f64), String> {
+    let [a, b, c, d, e, f] = fit_equation(input, params)?;
+    let scale = a.abs().max(c.abs());
+    if scale == 0.0 || (a - c).abs() > FIT_TOLERANCE * scale || b.abs() > FIT_TOLERANCE * scale {
+        return Err(String::from(
+            "a circle is written like (x - 1)² + (y - 2)² = 4",
+        ));
+    }
+    let center = Point::new(-d / (2.0 * a), -e / (2.0 * a));
+    let rad2 = center.get_x().powi(2) + center.get_y().powi(2) - f / a;
+    if rad2 <= 0.0 {
+        return Err(String::from("the circle has no points"));
+    }
+    Ok((center, rad2.sqrt()))
+}
+
+// the coefficients of ax² + bxy + cy² + dx + ey + f = 0 for an equation in x and y,
+// read off its values around the origin and checked at a few other points
+fn fit_equation(input: &str, params: &HashMap<String, f64>) -> Result<[f64; 6], String> {
+    let (lhs, rhs) = input
+        .split_once('=')
+        .ok_or_else(|| String::from("an equation needs a '='"))?;
+    let (lhs, rhs) = (expr::parse(lhs)?, expr::parse(rhs)?);
+    for var in lhs.get_variables().iter().chain(rhs.get_variables().iter()) {
+        if var != "x" && var != "y" && !params.contains_key(var) {
+            return Err(format!("unknown variable '{}'", var));
+        }
+    }
+    let g = |x: f64, y: f64| -> Result<f64, String> {
+        let lookup = |n: &str| match n {
+            "x" => Some(x),
+            "y" => Some(y),
+            _ => params.get(n).copied(),
+        };
+        Ok(eval(&lhs, &lookup)? - eval(&rhs, &lookup)?)
+    };
+    let f = g(0.0, 0.0)?;
+    let (gx1, gx2) = (g(1.0, 0.0)?, g(-1.0, 0.0)?);
+    let (gy1, gy2) = (g(0.0, 1.0)?, g(0.0, -1.0)?);
+    let a = (gx1 + gx2) / 2.0 - f;
+    let d = (gx1 - gx2) / 2.0;
+    let c = (gy1 + gy2) / 2.0 - f;
+    let e = (gy1 - gy2) / 2.0;
+    let b = (g(1.0, 1.0)? - g(1.0, -1.0)? - g(-1.0, 1.0)? + g(-1.0, -1.0)?) / 4.0;
+    let coefficients = [a, b, c, d, e, f];
+    let scale = coefficients.iter().fold(1.0, |m: f64, c| m.max(c.abs()));
+    for (x, y) in [(2.5, -1.7), (-3.1, 0.4), (0.7, 2.9), (4.2, 3.3)] {
+        let fitted = a * x * x + b * x * y + c * y * y + d * x + e * y + f;
+        if (g(x, y)? - fitted).abs() > FIT_TOLERANCE * scale * (x * x + y * y) {
+            return Err(String::from(
+                "only equations of lines, circles and conics can be used",
+            ));
+        }
+    }
+    Ok(coefficients)
+}
+
+fn eval(expr: &Expr, lookup: &dyn Fn(&str) -> Option<f64>) -> Result<f64, String> {
+    let value = expr.eval(lookup)?;
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(String::from("the equation isn't defined everywhere"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tooltip::FULL_PRECISION;
+
+    #[test]
+    fn equations_read_back_at_full_precision() {
+        let params = HashMap::new();
+        let tau = std::f64::consts::TAU;
+        let point = Figure::Point(Point::new(tau, -1.0 / 3.0));
+        assert_eq!(get_equation(&point, 2), "(6.28, -0.33)");
+        let text = get_equation(&point, FULL_PRECISION);
+        match parse_equation(&text, &point, &params) {
+            Ok(Figure::Point(p)) => {
+                assert_eq!((p.get_x(), p.get_y()), (tau, -1.0 / 3.0), "{}", text)
+            }
+            other => panic!("{} gave {:?}", text, other),
+        }
+
+        let circle = Figure::Circle(Circle::new(
+            Point::new(tau, 1.0 / 3.0),
+            Point::new(tau + 1.0, 1.0 / 3.0 + 1.0),
+        ));
+        let text = get_equation(&circle, FULL_PRECISION);
+        match parse_equation(&text, &circle, &params) {
+            Ok(Figure::Circle(c)) => {
+                assert!((c.get_center().get_x() - tau).abs() < 1e-9, "{}", text);
+                assert!(
+                    (c.get_center().get_y() - 1.0 / 3.0).abs() < 1e-9,
+                    "{}",
+                    text
+                );
+                assert!((c.get_rad() - 2f64.sqrt()).abs() < 1e-9, "{}", text);
+            }
+            other => panic!("{} gave {:?}", text, other),
+        }
+    }
+}
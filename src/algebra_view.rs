@@ -0,0 +1,138 @@
+#![allow(dead_code)]
+use fltk::dialog::alert_default;
+use fltk::enums::{CallbackTrigger, Color, FrameType};
+use fltk::prelude::{BrowserExt, GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{browser::HoldBrowser, group::Group, input::Input};
+
+use crate::canvas::Canvas;
+
+const ROW_HEIGHT: i32 = 26;
+
+// a side panel with every figure written as an equation, followed by where they intersect;
+// the equation of the selected figure can be edited to redefine it
+#[derive(Clone)]
+pub struct AlgebraView {
+    group: Group,
+    list: HoldBrowser,
+    input: Input,
+    canvas: Canvas,
+}
+
+impl AlgebraView {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let mut group = Group::new(x, y, w, h, None);
+        group.set_frame(FrameType::FlatBox);
+        group.set_color(Color::Background2);
+
+        let mut list = HoldBrowser::new(x + 5, y + 5, w - 10, h - ROW_HEIGHT - 15, None);
+        list.set_column_char('\t');
+        list.set_column_widths(&[(w - 10) / 6]);
+        let mut input = Input::new(x + 5, y + h - ROW_HEIGHT - 5, w - 10, ROW_HEIGHT, None);
+        input.set_trigger(CallbackTrigger::EnterKeyAlways);
+        group.end();
+        group.resizable(&list);
+        group.hide();
+
+        let mut view = AlgebraView {
+            group,
+            list,
+            input,
+            canvas,
+        };
+
+        view.list.set_callback({
+            let mut view = view.clone();
+            move |_| view.show_equation()
+        });
+        view.input.set_callback({
+            let mut view = view.clone();
+            move |input| view.apply(&input.value())
+        });
+        view.canvas.add_on_change({
+            let mut view = view.clone();
+            move || view.refresh()
+        });
+        view
+    }
+
+    // shows the panel if it is hidden and the other way around
+    pub fn toggle(&mut self) {
+        if self.group.visible() {
+            self.group.hide();
+        } else {
+            self.refresh();
+            self.group.show();
+        }
+        // the canvas beside the panel grows or shrinks with it
+        if let Some(mut window) = self.group.window() {
+            window.redraw();
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.group.visible()
+    }
+
+    // the list takes the new height, the input stays at the bottom
+    // for the layout to dock it
+    pub fn get_group(&self) -> &Group {
+        &self.group
+    }
+
+    // writes the equations again, keeping the selection
+    pub fn refresh(&mut self) {
+        let selected = self.list.value();
+        self.list.clear();
+        for (name, equation) in self.canvas.get_equations() {
+            self.list.add(&format!("{}\t{}", name, equation));
+        }
+        let intersections = self.canvas.get_intersection_list();
+        if !intersections.is_empty() {
+            self.list.add("@bIntersections");
+            for line in intersections {
+                self.list.add(&line);
+            }
+        }
+        if selected > 0 && selected <= self.list.size() {
+            self.list.select(selected);
+        }
+        self.show_equation();
+    }
+
+    // the index in the scene, None for the intersections below the figures
+    fn get_selected(&self) -> Option<usize> {
+        match self.list.value() {
+            0 => None,
+            line if line as usize <= self.canvas.get_equations().len() => Some(line as usize - 1),
+            _ => None,
+        }
+    }
+
+    fn show_equation(&mut self) {
+        // what is being typed stays until it is entered
+        if self.input.has_focus() {
+            return;
+        }
+        match self.get_selected().and_then(|i| self.canvas.get_source(i)) {
+            Some(equation) => {
+                self.input.set_value(&equation);
+                self.input.activate();
+            }
+            None => {
+                self.input.set_value("");
+                self.input.deactivate();
+            }
+        }
+        self.group.redraw();
+    }
+
+    fn apply(&mut self, text: &str) {
+        let i = match self.get_selected() {
+            Some(i) => i,
+            None => return,
+        };
+        if let Err(e) = self.canvas.redefine(i, text) {
+            alert_default(&e);
+        }
+    }
+}
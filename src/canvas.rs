@@ -1,39 +1,332 @@
+use std::collections::HashMap;
+use std::f64;
+use std::fs;
+use std::path::Path;
 use std::rc::Rc;
 use std::{borrow::Borrow, cell::RefCell};
 
-use fltk::draw::{draw_line, draw_rect, set_line_style, LineStyle};
+use fltk::draw::{draw_arc, draw_line, draw_rect, set_line_style, LineStyle};
 use fltk::prelude::SurfaceDevice;
 use fltk::surface::ImageSurface;
-use fltk::{app, button, draw, group};
+use fltk::{app, button, dialog, draw, group};
 use fltk::{
-    draw::{draw_circle, draw_circle_fill, draw_point, draw_rect_fill, set_draw_color},
-    enums::{Color, Event, FrameType},
+    draw::{draw_circle_fill, draw_point, draw_rect_fill, set_draw_color},
+    enums::{Color, ColorDepth, Event, Font, FrameType},
     frame::Frame,
+    image::RgbImage,
     prelude::{ImageExt, WidgetBase, WidgetExt},
 };
 
+use crate::algebra::{get_equation, parse_equation};
+use crate::command;
+use crate::construction::Construction;
+use crate::expr;
+use crate::figures::function::FunctionGraph;
 use crate::figures::line::Line;
+use crate::figures::parametric::Parametric;
 use crate::figures::point::Point;
-use crate::figures::{circle, rectangle};
+use crate::figures::{arc, bezier, circle, conic, rectangle, spline, Figure};
+use crate::measurement::{Label, Measurement};
+use crate::protocol::{self, Step};
+use crate::raster::Raster;
+use crate::relation::{get_points_relations, get_relations, Tolerance};
+use crate::render::{render_figure, Pen, Renderer};
+use crate::script::Script;
+use crate::style::{LinePattern, Marker, Style};
+use crate::svg;
+use crate::tooltip::{format_number, format_point, get_tooltip, FULL_PRECISION};
 use crate::utils::{
-    get_circles_intersection, get_line_circle_intersection, get_point_of_intersection,
+    get_angle, get_distance, get_intersections, get_line_distance, get_segment_distance,
 };
+use crate::view::View;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Line,
+    Rectangle,
+    Circle,
+    Arc,                   // center, start point, end angle
+    ThreePointArc,         // start, a point on the arc, end
+    QuadraticBezier,       // start, control, end
+    CubicBezier,           // start, two controls, end
+    Spline,                // any number of points, finished by a double click
+    Conic,                 // five points on it
+    TangentAtPoint,        // a point on a circle
+    TangentFromPoint,      // a circle, then a point outside of it
+    CommonTangents,        // two circles
+    Parallel,              // a line, then a point
+    Perpendicular,         // a line, then a point
+    PerpendicularBisector, // two points
+    AngleBisector,         // two lines
+    Midpoint,              // two points
+    Reflect,               // a figure, then the mirror line
+    PointReflect,          // a figure, then the center point
+    Rotate(f64),           // a figure, then the center point; the angle in radians
+    Dilate(f64),           // a figure, then the center point; the factor
+    Translate,             // a figure, then two points for the vector
+    Invert,                // a figure, then the circle
+    Relation,              // two figures, or any number of points finished by a double click
+    Distance,              // a point, then a point or a line
+    Angle,                 // two lines
+    ThreePointAngle,       // a point on each leg with the vertex in between
+    Area,                  // a closed figure
+}
+
+// what a click of a tool does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    Place,  // a new point which only defines the figure
+    Point,  // an existing point object or a new one the result will depend on
+    Line,   // an existing line
+    Circle, // an existing circle
+    Figure, // any existing figure
+}
+
+impl Tool {
+    // how many clicks it takes to build the figure, None if the user decides
+    pub fn points_needed(&self) -> Option<usize> {
+        match self {
+            Tool::Line | Tool::Rectangle | Tool::Circle => Some(2),
+            Tool::Arc | Tool::ThreePointArc | Tool::QuadraticBezier => Some(3),
+            Tool::CubicBezier => Some(4),
+            Tool::Conic => Some(5),
+            Tool::TangentAtPoint => Some(1),
+            Tool::TangentFromPoint | Tool::CommonTangents => Some(2),
+            Tool::Parallel
+            | Tool::Perpendicular
+            | Tool::PerpendicularBisector
+            | Tool::AngleBisector
+            | Tool::Midpoint => Some(2),
+            Tool::Reflect
+            | Tool::PointReflect
+            | Tool::Rotate(_)
+            | Tool::Dilate(_)
+            | Tool::Invert => Some(2),
+            Tool::Translate => Some(3),
+            Tool::Distance | Tool::Angle => Some(2),
+            Tool::ThreePointAngle => Some(3),
+            Tool::Area => Some(1),
+            Tool::Spline | Tool::Relation => None,
+        }
+    }
+
+    // what the click with this index does
+    pub fn get_pick(&self, i: usize) -> Pick {
+        match self {
+            Tool::TangentAtPoint | Tool::CommonTangents => Pick::Circle,
+            Tool::TangentFromPoint if i == 0 => Pick::Circle,
+            Tool::TangentFromPoint => Pick::Point,
+            Tool::Parallel | Tool::Perpendicular if i == 0 => Pick::Line,
+            Tool::Parallel | Tool::Perpendicular => Pick::Point,
+            Tool::PerpendicularBisector | Tool::Midpoint => Pick::Point,
+            Tool::AngleBisector => Pick::Line,
+            Tool::Reflect
+            | Tool::PointReflect
+            | Tool::Rotate(_)
+            | Tool::Dilate(_)
+            | Tool::Translate
+            | Tool::Invert
+                if i == 0 =>
+            {
+                Pick::Figure
+            }
+            Tool::Reflect => Pick::Line,
+            Tool::Invert => Pick::Circle,
+            Tool::PointReflect | Tool::Rotate(_) | Tool::Dilate(_) | Tool::Translate => Pick::Point,
+            Tool::Relation => Pick::Figure,
+            Tool::Distance if i == 0 => Pick::Point,
+            Tool::Distance | Tool::Area => Pick::Figure,
+            Tool::Angle => Pick::Line,
+            Tool::ThreePointAngle => Pick::Point,
+            _ => Pick::Place,
+        }
+    }
+
+    // what the click with this index should be on, for the status bar
+    pub fn get_hint(&self, i: usize) -> String {
+        let hint = match (self, i) {
+            (Tool::Line, 0) => "Click the line's first point",
+            (Tool::Line, _) => "Click the line's second point",
+            (Tool::Rectangle, 0) => "Click a corner of the rectangle",
+            (Tool::Rectangle, _) => "Click the opposite corner",
+            (Tool::Circle, 0) => "Click the circle's center",
+            (Tool::Circle, _) => "Click the circle's side point",
+            (Tool::Arc, 0) => "Click the arc's center",
+            (Tool::Arc, 1) => "Click where the arc starts",
+            (Tool::Arc, _) => "Click in the direction where the arc ends",
+            (Tool::ThreePointArc, 0) => "Click where the arc starts",
+            (Tool::ThreePointArc, 1) => "Click a point the arc passes through",
+            (Tool::ThreePointArc, _) => "Click where the arc ends",
+            (Tool::QuadraticBezier | Tool::CubicBezier, 0) => "Click where the curve starts",
+            (Tool::QuadraticBezier, 1) | (Tool::CubicBezier, 1 | 2) => "Click a control point",
+            (Tool::QuadraticBezier | Tool::CubicBezier, _) => "Click where the curve ends",
+            (Tool::Spline, 0) => "Click the spline's first point",
+            (Tool::Spline, _) => "Click the next point, double click the last one",
+            (Tool::Conic, _) => return format!("Click point {} of 5 on the conic", i + 1),
+            (Tool::TangentAtPoint, _) => "Click the circle where the tangent touches it",
+            (Tool::TangentFromPoint, 0) => "Click the circle",
+            (Tool::TangentFromPoint, _) => "Click a point outside the circle",
+            (Tool::CommonTangents, 0) => "Click the first circle",
+            (Tool::CommonTangents, _) => "Click the second circle",
+            (Tool::Parallel | Tool::Perpendicular, 0) => "Click the line",
+            (Tool::Parallel | Tool::Perpendicular, _) => "Click the point it goes through",
+            (Tool::PerpendicularBisector | Tool::Midpoint, 0) => "Click the first point",
+            (Tool::PerpendicularBisector | Tool::Midpoint, _) => "Click the second point",
+            (Tool::AngleBisector | Tool::Angle, 0) => "Click the first line",
+            (Tool::AngleBisector | Tool::Angle, _) => "Click the second line",
+            (
+                Tool::Reflect
+                | Tool::PointReflect
+                | Tool::Rotate(_)
+                | Tool::Dilate(_)
+                | Tool::Translate
+                | Tool::Invert,
+                0,
+            ) => "Click the figure to transform",
+            (Tool::Reflect, _) => "Click the mirror line",
+            (Tool::PointReflect | Tool::Rotate(_) | Tool::Dilate(_), _) => "Click the center",
+            (Tool::Translate, 1) => "Click where the vector starts",
+            (Tool::Translate, _) => "Click where the vector ends",
+            (Tool::Invert, _) => "Click the circle to invert in",
+            (Tool::Relation, 0) => "Click the first figure",
+            (Tool::Relation, _) => "Click the second figure, or more points and double click",
+            (Tool::Distance, 0) => "Click the point to measure from",
+            (Tool::Distance, _) => "Click a point or a line",
+            (Tool::ThreePointAngle, 0) => "Click a point on the first leg",
+            (Tool::ThreePointAngle, 1) => "Click the vertex",
+            (Tool::ThreePointAngle, _) => "Click a point on the second leg",
+            (Tool::Area, _) => "Click a closed figure",
+        };
+        hint.to_string()
+    }
+
+    // what the construction protocol calls the tool
+    pub fn get_name(&self) -> String {
+        let name = match self {
+            Tool::Line => "Line",
+            Tool::Rectangle => "Rectangle",
+            Tool::Circle => "Circle",
+            Tool::Arc => "Arc",
+            Tool::ThreePointArc => "Arc through three points",
+            Tool::QuadraticBezier => "Quadratic Bézier",
+            Tool::CubicBezier => "Cubic Bézier",
+            Tool::Spline => "Spline",
+            Tool::Conic => "Conic",
+            Tool::TangentAtPoint => "Tangent at a point",
+            Tool::TangentFromPoint => "Tangents from a point",
+            Tool::CommonTangents => "Common tangents",
+            Tool::Parallel => "Parallel",
+            Tool::Perpendicular => "Perpendicular",
+            Tool::PerpendicularBisector => "Perpendicular bisector",
+            Tool::AngleBisector => "Angle bisector",
+            Tool::Midpoint => "Midpoint",
+            Tool::Reflect => "Reflection in a line",
+            Tool::PointReflect => "Reflection in a point",
+            Tool::Rotate(angle) => {
+                return format!("Rotation by {}°", format_number(angle.to_degrees(), 2))
+            }
+            Tool::Dilate(factor) => return format!("Dilation by {}", format_number(*factor, 2)),
+            Tool::Translate => "Translation",
+            Tool::Invert => "Inversion",
+            Tool::Relation => "Relation",
+            Tool::Distance => "Distance",
+            Tool::Angle => "Angle",
+            Tool::ThreePointAngle => "Angle",
+            Tool::Area => "Area",
+        };
+        String::from(name)
+    }
+}
+
+// a figure together with the look it was drawn with, so the canvas can be redrawn
+#[derive(Debug, Clone)]
+struct Shape {
+    figure: Figure,
+    name: String,
+    style: Style,
+    visible: bool,
+    construction: Option<Construction>, // for figures which follow others
+    source: Option<String>,             // the equation as it was typed, while it still holds
+}
+
+// what the object list shows of a figure
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    name: String,
+    kind: &'static str,
+    color: (u8, u8, u8),
+    visible: bool,
+    dependent: bool,
+}
+
+impl ObjectInfo {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub fn get_color(&self) -> (u8, u8, u8) {
+        self.color
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn is_dependent(&self) -> bool {
+        self.dependent
+    }
+}
+
+type OnChange = Box<dyn FnMut()>;
+type OnMove = Box<dyn FnMut(Option<Point>)>;
+
+// the axes are drawn light, behind everything
+const AXIS_COLOR: (u8, u8, u8) = (217, 217, 217);
+// and the grid lighter still, its lines at least this many pixels apart
+const GRID_COLOR: (u8, u8, u8) = (238, 238, 238);
+const GRID_SPACING: f64 = 40.0;
+// how close a click has to be to grab a control point
+const GRAB_DISTANCE: f64 = 6.0;
+// how far apart in world units and in radians things may be and still count as equal
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+const DEFAULT_ANGLE_TOLERANCE: f64 = 1e-6;
+// decimals in the tooltips and the relations
+const DEFAULT_PRECISION: usize = 2;
+// of the lines of new objects, in pixels
+const DEFAULT_WIDTH: i32 = 3;
 
 #[derive(Clone)]
 pub struct Canvas {
     frame: Frame,
     surf: Rc<RefCell<ImageSurface>>,
-    r: Rc<RefCell<bool>>, // rectangle
-    l: Rc<RefCell<bool>>, // lines
-    c: Rc<RefCell<bool>>, // circle
+    tool: Rc<RefCell<Tool>>,
     points: Rc<RefCell<Vec<Point>>>,
-    buffer: Rc<RefCell<Vec<Point>>>,
+    buffer: Rc<RefCell<Vec<Point>>>, // the clicks of the figure being built
+    started: Rc<RefCell<(usize, usize)>>, // figures and clicked points from before them
     color: Rc<RefCell<Color>>,
     fill: Rc<RefCell<bool>>,
-    lines: Rc<RefCell<Vec<Line>>>,
-    circles: Rc<RefCell<Vec<circle::Circle>>>,
-    lines_buffer: Rc<RefCell<Vec<Line>>>,
-    circles_buffer: Rc<RefCell<Vec<circle::Circle>>>,
+    line_width: Rc<RefCell<i32>>,
+    grid: Rc<RefCell<bool>>,
+    snap: Rc<RefCell<bool>>, // new points go to the nearest grid point
+    figures: Rc<RefCell<Vec<Shape>>>,
+    measurements: Rc<RefCell<Vec<Measurement>>>, // labels which follow the figures they measure
+    dragging: Rc<RefCell<Option<(usize, usize)>>>, // figure and control point index
+    view: Rc<RefCell<View>>,
+    params: Rc<RefCell<HashMap<String, f64>>>, // user parameters for the function graphs
+    tolerance: Rc<RefCell<Tolerance>>,         // for the relation tool
+    precision: Rc<RefCell<usize>>,
+    hover: Rc<RefCell<Option<(i32, i32, String)>>>, // the tooltip and where the mouse is
+    on_change: Rc<RefCell<Vec<OnChange>>>,          // tells the panels the scene changed
+    on_move: Rc<RefCell<Vec<OnMove>>>,              // tells where the mouse is in the world
+    script: Rc<RefCell<Option<Script>>>,            // what is left of a script run step by step
+    protocol: Rc<RefCell<Vec<Step>>>,               // how the scene was built, step by step
+    replay: Rc<RefCell<Option<usize>>>,             // how many steps are shown, None for all
+    ratio: Rc<RefCell<f64>>,                        // device pixels for each pixel of the frame
+    message: Rc<RefCell<Option<String>>>,           // why the last click made nothing
 }
 
 macro_rules! rcrc {
@@ -42,6 +335,16 @@ macro_rules! rcrc {
     };
 }
 
+// an offscreen surface for a frame of w x h, on a HiDPI screen it has more pixels than
+// the frame, so it comes with the ratio between them
+fn new_surface(w: i32, h: i32) -> (ImageSurface, f64) {
+    let surf = ImageSurface::new(w, h, true);
+    let ratio = surf
+        .image()
+        .map_or(1.0, |image| image.data_w() as f64 / image.w().max(1) as f64);
+    (surf, ratio)
+}
+
 // need to do some bugfixes with placig the points so that
 // the circles and rectangles are drawn correctly
 
@@ -51,272 +354,400 @@ impl Canvas {
         frame.set_color(Color::White);
         frame.set_frame(FrameType::DownBox);
 
-        let surf = ImageSurface::new(frame.width(), frame.height(), false);
+        let view = View::new(frame.width(), frame.height());
+
+        let (surf, ratio) = new_surface(frame.width(), frame.height());
         ImageSurface::push_current(&surf);
-        draw_rect_fill(0, 0, w, h, Color::White);
+        draw_scene(&[], &[], &[], &view, ratio, false, DEFAULT_PRECISION);
         ImageSurface::pop_current();
 
         let surf = rcrc!(surf);
 
-        let c = false;
-        let r = false;
-        let l = true;
+        let tool = Tool::Line;
         let points: Vec<Point> = Vec::new();
         let buffer: Vec<Point> = Vec::new();
+        let started = (0, 0);
         let color = Color::Black;
         let fill = false;
-        let lines: Vec<Line> = Vec::new();
-        let circles: Vec<circle::Circle> = Vec::new();
-        let lines_buffer: Vec<Line> = Vec::new();
-        let circles_buffer: Vec<circle::Circle> = Vec::new();
-
+        let line_width = DEFAULT_WIDTH;
+        let grid = false;
+        let snap = false;
+        let figures: Vec<Shape> = Vec::new();
+        let measurements: Vec<Measurement> = Vec::new();
+        let dragging: Option<(usize, usize)> = None;
+        let params: HashMap<String, f64> = HashMap::new();
+        let tolerance = Tolerance::new(DEFAULT_TOLERANCE, DEFAULT_ANGLE_TOLERANCE);
+        let precision = DEFAULT_PRECISION;
+        let hover: Option<(i32, i32, String)> = None;
+        let on_change: Vec<OnChange> = Vec::new();
+        let on_move: Vec<OnMove> = Vec::new();
+        let script: Option<Script> = None;
+        let protocol: Vec<Step> = Vec::new();
+        let replay: Option<usize> = None;
+        let message: Option<String> = None;
 
-        let c = rcrc!(c);
-        let r = rcrc!(r);
-        let l = rcrc!(l);
+        let tool = rcrc!(tool);
         let points = rcrc!(points);
         let buffer = rcrc!(buffer);
+        let started = rcrc!(started);
         let color = rcrc!(color);
         let fill = rcrc!(fill);
-        let lines = rcrc!(lines);
-        let circles = rcrc!(circles);
-        let lines_buffer = rcrc!(lines_buffer);
-        let circles_buffer = rcrc!(circles_buffer);
+        let line_width = rcrc!(line_width);
+        let grid = rcrc!(grid);
+        let snap = rcrc!(snap);
+        let figures = rcrc!(figures);
+        let measurements = rcrc!(measurements);
+        let dragging = rcrc!(dragging);
+        let view = rcrc!(view);
+        let params = rcrc!(params);
+        let tolerance = rcrc!(tolerance);
+        let precision = rcrc!(precision);
+        let hover = rcrc!(hover);
+        let on_change = rcrc!(on_change);
+        let on_move = rcrc!(on_move);
+        let script = rcrc!(script);
+        let protocol = rcrc!(protocol);
+        let replay = rcrc!(replay);
+        let ratio = rcrc!(ratio);
+        let message = rcrc!(message);
 
         // handlers
         frame.draw({
             let surf = surf.clone();
+            let buffer = buffer.clone();
+            let view = view.clone();
+            let hover = hover.clone();
             move |f| {
                 let surf = surf.borrow_mut();
                 let mut img = surf.image().unwrap();
                 img.draw(f.x(), f.y(), f.w(), f.h());
+                draw_pending(&buffer.borrow_mut(), &view.borrow_mut(), f.x(), f.y());
+                // the tooltip is drawn over the image, so moving the mouse doesn't redraw the scene
+                if let Some((x, y, text)) = hover.borrow_mut().as_ref() {
+                    draw_tooltip(f.x() + x, f.y() + y, text, f.x() + f.w(), f.y() + f.h());
+                }
             }
         });
 
         frame.handle({
             let surf = surf.clone();
-            let l_clone = l.clone();
-            let c_clone = c.clone();
-            let r_clone = r.clone();
+            let tool = tool.clone();
             let points = points.clone(); // needed for later Tooltips
             let buffer = buffer.clone();
+            let started = started.clone();
             let color = color.clone();
             let fill = fill.clone();
-            let lines = lines.clone();
-            let circles = circles.clone();
-            let lines_buffer = lines_buffer.clone();
-            let circles_buffer = circles_buffer.clone();
-
+            let line_width = line_width.clone();
+            let grid = grid.clone();
+            let snap = snap.clone();
+            let figures = figures.clone();
+            let measurements = measurements.clone();
+            let dragging = dragging.clone();
+            let view = view.clone();
+            let tolerance = tolerance.clone();
+            let precision = precision.clone();
+            let hover = hover.clone();
+            let on_change = on_change.clone();
+            let on_move = on_move.clone();
+            let protocol = protocol.clone();
+            let replay = replay.clone();
+            let ratio = ratio.clone();
+            let message = message.clone();
 
             move |f, ev| {
                 // println!("{}", ev);
                 // println!("coords {:?}", app::event_coords());
                 // println!("get mouse {:?}", app::get_mouse());
                 let surf = surf.borrow_mut();
-                let l_bm = l_clone.borrow_mut();
-                let c_bm = c_clone.borrow_mut();
-                let r_bm = r_clone.borrow_mut();
+                let tool = tool.borrow_mut();
                 let mut points = points.borrow_mut();
                 let mut buffer = buffer.borrow_mut();
+                let mut started = started.borrow_mut();
                 let color = color.borrow_mut();
                 let fill = fill.borrow_mut();
-                let mut lines = lines.borrow_mut();
-                let mut circles = circles.borrow_mut();
-                let mut lines_buffer = lines_buffer.borrow_mut();
-                let mut circles_buffer = circles_buffer.borrow_mut();
-
+                let line_width = line_width.borrow_mut();
+                let grid = *grid.borrow_mut();
+                let snap = snap.borrow_mut();
+                let mut figures = figures.borrow_mut();
+                let mut measurements = measurements.borrow_mut();
+                let mut dragging = dragging.borrow_mut();
+                let view = view.borrow_mut();
+                let tolerance = tolerance.borrow_mut();
+                let precision = precision.borrow_mut();
+                let mut hover = hover.borrow_mut();
+                let mut protocol = protocol.borrow_mut();
+                let mut replay = replay.borrow_mut();
+                let ratio = *ratio.borrow_mut();
+                let mut message = message.borrow_mut();
 
                 match ev {
                     Event::Push => {
-                        ImageSurface::push_current(&surf);
-                        let coords = app::event_coords();
-                        set_line_style(LineStyle::Solid, 3);
-                        draw_circle(coords.0 as f64, coords.1 as f64, 1.0);
-
-                        &points.push(Point::new(coords.0 as f64, coords.1 as f64));
-                        &buffer.push(Point::new(coords.0 as f64, coords.1 as f64));
-                        let len = buffer.len();
-
-                        // line
-                        if len > 1 && l_bm.eq(&true) {
-                            let first = &buffer[len - 1];
-                            let second = &buffer[len - 2];
-                            let l = Line::new(*first, *second);
-                            lines_buffer.push(l);
-
-                            set_draw_color(*color);
-                            set_line_style(LineStyle::Solid, 3);
-
-                            // draw an infinte line that should go through the first and second point
-
-                            // y = mx + b
-                            // x = (y - b) / m
-                            // println!("\nFirst x: {:?}", first.get_x());
-                            // println!("Second x: {:?}", second.get_x());
-                            // println!("Slope: {:?}", l.get_slope());
-                            if first.get_x() == second.get_x() {
-                                // if the line is vertical
-                                draw_line(first.get_x() as i32, 0, first.get_x() as i32, 20000);
-                            } else if first.get_y() == second.get_y() {
-                                // if the line is horizontal
-                                draw_line(0, first.get_y() as i32, 20000, first.get_y() as i32);
-                            } else {
-                                let fx =
-                                    (first.get_y() - l.get_y_intercept()) / l.get_slope() + 10000.0;
-                                let fy = l.get_slope() * fx + l.get_y_intercept();
-
-                                let sx =
-                                    (second.get_y() - l.get_y_intercept()) / l.get_slope() - 10000.0;
-                                let sy = l.get_slope() * sx + l.get_y_intercept();
-
-                                draw_line(fx as i32, fy as i32, sx as i32, sy as i32);
+                        *hover = None;
+                        *message = None;
+                        // a right click gives up the figure being built
+                        if app::event_mouse_button() == app::MouseButton::Right {
+                            if drop_pending(&mut buffer, &mut figures, &mut points, *started) {
+                                ImageSurface::push_current(&surf);
+                                draw_scene(
+                                    &figures,
+                                    &measurements,
+                                    &points,
+                                    &view,
+                                    ratio,
+                                    grid,
+                                    *precision,
+                                );
+                                ImageSurface::pop_current();
+                                f.redraw();
+                                notify_change(&on_change);
                             }
-                            &buffer.clear();
+                            return true;
+                        }
+                        // building goes on from the whole construction, not a replayed part
+                        if replay.is_some() {
+                            *replay = None;
+                            ImageSurface::push_current(&surf);
+                            draw_scene(
+                                &figures,
+                                &measurements,
+                                &points,
+                                &view,
+                                ratio,
+                                grid,
+                                *precision,
+                            );
+                            ImageSurface::pop_current();
+                            f.redraw();
                         }
+                        let coords = app::event_coords();
+                        let click =
+                            view.to_world((coords.0 - f.x()) as f64, (coords.1 - f.y()) as f64);
+                        // where a new point goes, the click itself still picks what is there
+                        let place = if *snap {
+                            snap_to_grid(click, &view)
+                        } else {
+                            click
+                        };
 
-                        // circle
-                        if len > 1 && c_bm.eq(&true) {
-                            let sidepoint = &buffer[len - 1];
-                            let center = &buffer[len - 2];
-                            let circle = circle::Circle::new(*center, *sidepoint);
-                            circles_buffer.push(circle);
-
-                            set_draw_color(*color);
-                            set_line_style(LineStyle::Solid, 3);
-                            if *fill {
-                                set_line_style(LineStyle::Solid, circle.get_rad() as i32 * 2);
-                                draw_circle(
-                                    circle.get_center().get_x() as f64,
-                                    circle.get_center().get_y() as f64,
-                                    1.0,
-                                );
-                            } else {
-                                draw_circle(
-                                    circle.get_center().get_x() as f64,
-                                    circle.get_center().get_y() as f64,
-                                    circle.get_rad(),
-                                );
+                        // grab a control point of a curve instead of starting a new figure,
+                        // the tools that pick figures use the clicks for that instead
+                        if buffer.is_empty() && tool.get_pick(0) == Pick::Place {
+                            *dragging = find_control_point(&figures, click, &view);
+                            if dragging.is_some() {
+                                return true;
                             }
-                            &buffer.clear();
                         }
 
-                        // rectangle
-                        if len > 1 && r_bm.eq(&true) {
-                            let first = &buffer[len - 1];
-                            let second = &buffer[len - 2];
-
-                            let rect = rectangle::Rectangle::new(*first, *second);
-                            set_draw_color(*color);
-                            set_line_style(LineStyle::Solid, 3);
-                            if *fill {
-                                draw_rect_fill(
-                                    rect.get_point_b().get_x() as i32,
-                                    rect.get_point_b().get_y() as i32,
-                                    rect.get_width() as i32,
-                                    rect.get_height() as i32,
-                                    *color,
-                                );
-                            } else {
-                                draw_rect(
-                                    rect.get_point_b().get_x() as i32,
-                                    rect.get_point_b().get_y() as i32,
-                                    rect.get_width() as i32,
-                                    rect.get_height() as i32,
-                                );
-                            };
-
-                            &buffer.clear();
+                        // a double click finishes the tools that take any number of clicks
+                        let finished = tool.points_needed().is_none() && app::event_clicks();
+                        // a cancelled figure takes back what it made from here on
+                        if buffer.is_empty() {
+                            *started = (figures.len(), points.len());
                         }
-
-                        let lb_len = lines_buffer.len();
-                        let cb_len = circles_buffer.len();
-                        let l_len = lines.len();
-                        let c_len = circles.len();
-
-                        if lb_len == 1 {
-                            if l_len == 0 {
-                                let l1 = lines_buffer.pop().unwrap();
-                                for c in circles.iter() {
-                                    let c1 = *c;
-                                    let points = get_line_circle_intersection(l1, c1);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    for p in points.iter() {
-                                        draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
+                        match tool.get_pick(buffer.len()) {
+                            _ if finished => {}
+                            Pick::Place => {
+                                points.push(place);
+                                buffer.push(place);
+                            }
+                            Pick::Point => {
+                                // snap to a point object or make one to depend on
+                                let i = match find_point(&figures, click, &view) {
+                                    Some(i) => i,
+                                    None => {
+                                        push_shape(
+                                            &mut figures,
+                                            Figure::Point(place),
+                                            Style::new((0, 0, 0), false),
+                                            None,
+                                        );
+                                        figures.len() - 1
                                     }
+                                };
+                                buffer.push(figures[i].figure.get_control_points()[0]);
+                            }
+                            // a click that should pick a figure but misses them all is ignored
+                            Pick::Line if find_line(&figures, click, &view).is_none() => {
+                                return true
+                            }
+                            Pick::Circle if find_circle(&figures, click, &view).is_none() => {
+                                return true
+                            }
+                            Pick::Figure if find_figure(&figures, click, &view).is_none() => {
+                                return true
+                            }
+                            Pick::Line | Pick::Circle | Pick::Figure => buffer.push(click),
+                        }
+
+                        // two picks are enough unless they are points, which can be followed by more
+                        if *tool == Tool::Relation
+                            && (finished
+                                || buffer.len() == 2 && !all_points(&figures, &buffer, &view))
+                        {
+                            let report = get_relation_report(
+                                &figures, &buffer, &view, *tolerance, *precision,
+                            );
+                            buffer.clear();
+                            if let Some(report) = report {
+                                // the dialog runs its own loop, so it has to wait until the
+                                // canvas isn't borrowed anymore
+                                app::add_timeout3(0.0, move |_| dialog::message_default(&report));
+                            }
+                        } else if Some(buffer.len()) == tool.points_needed() || finished {
+                            let inputs =
+                                get_step_inputs(*tool, &figures, &buffer, &view, *precision);
+                            let figure = match *tool {
+                                Tool::Line => Some(Figure::Line(Line::new(buffer[1], buffer[0]))),
+                                Tool::Rectangle => Some(Figure::Rectangle(
+                                    rectangle::Rectangle::new(buffer[1], buffer[0]),
+                                )),
+                                Tool::Circle => {
+                                    Some(Figure::Circle(circle::Circle::new(buffer[0], buffer[1])))
                                 }
-                                lines.push(l1);
-                            } else {
-                                let l1 = lines_buffer.pop().unwrap();
-                                for l in lines.iter() {
-                                    let l2 = *l;
-                                    let p = get_point_of_intersection(l1, l2);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
+                                Tool::Arc => Some(Figure::Arc(arc::Arc::new(
+                                    buffer[0], buffer[1], buffer[2],
+                                ))),
+                                Tool::ThreePointArc => {
+                                    arc::Arc::from_three_points(buffer[0], buffer[1], buffer[2])
+                                        .map(Figure::Arc)
                                 }
-                                // calculate the intersection points between this line and circles
-                                for c in circles.iter() {
-                                    let c1 = *c;
-                                    let points = get_line_circle_intersection(l1, c1);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    for p in points.iter() {
-                                        draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
-                                    }
+                                Tool::QuadraticBezier => Some(Figure::Bezier(
+                                    bezier::Bezier::quadratic(buffer[0], buffer[1], buffer[2]),
+                                )),
+                                Tool::CubicBezier => Some(Figure::Bezier(bezier::Bezier::cubic(
+                                    buffer[0], buffer[1], buffer[2], buffer[3],
+                                ))),
+                                Tool::Spline if buffer.len() > 1 => {
+                                    Some(Figure::Spline(spline::Spline::new(buffer.clone())))
                                 }
-                                lines.push(l1);
+                                Tool::Spline => None,
+                                Tool::Conic => conic::Conic::from_five_points([
+                                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
+                                ])
+                                .map(Figure::Conic),
+                                _ => None,
+                            };
+                            let style = Style::new(color.to_rgb(), *fill).with_width(*line_width);
+
+                            if let Some(figure) = figure {
+                                push_shape(&mut figures, figure, style, None);
                             }
-                        }
-                        
-                        if cb_len == 1 {
-                            if c_len == 0 {
-                                let c1 = circles_buffer.pop().unwrap();
-                                for l in lines.iter() {
-                                    let l1 = *l;
-                                    let points = get_line_circle_intersection(l1, c1);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    for p in points.iter() {
-                                        draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
+                            for construction in get_constructions(*tool, &figures, &buffer, &view) {
+                                let parents = &figures;
+                                let parent = |i: usize| parents.get(i).map(|s| &s.figure);
+                                match construction.apply(parent, view.get_bounds()) {
+                                    Some(figure) => {
+                                        push_shape(&mut figures, figure, style, Some(construction))
                                     }
-                                }
-                                circles.push(c1);
-
-                            } else {
-                                let c1 = circles_buffer.pop().unwrap();
-                                for c in circles.iter() {
-                                    let c2 = *c;
-                                    let points = get_circles_intersection(c1, c2);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    for p in points.iter() {
-                                        draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
+                                    None => {
+                                        *message =
+                                            construction.get_refusal(parent).map(String::from)
                                     }
                                 }
-                                // calculate the intersection points between this line and circles
-                                for l in lines.iter() {
-                                    let l1 = *l;
-                                    let points = get_line_circle_intersection(l1, c1);
-                                    // draw the intersection point
-                                    set_line_style(LineStyle::Solid, 3);
-                                    set_draw_color(Color::Red);
-                                    for p in points.iter() {
-                                        draw_circle(p.get_x() as f64, p.get_y() as f64, 1.0);
-                                    }
+                            }
+                            match get_measurement(*tool, &figures, &buffer, &view) {
+                                Some(measurement) => measurements.push(measurement),
+                                None if *tool == Tool::Area => {
+                                    *message = Some(String::from(
+                                        "Only circles, rectangles, arcs and ellipses have an area",
+                                    ))
                                 }
-                                circles.push(c1);
+                                None => {}
                             }
+                            buffer.clear();
+                            record_step(
+                                &mut protocol,
+                                tool.get_name(),
+                                inputs,
+                                &figures,
+                                points.len(),
+                                measurements.len(),
+                            );
                         }
 
-
+                        ImageSurface::push_current(&surf);
+                        draw_scene(
+                            &figures,
+                            &measurements,
+                            &points,
+                            &view,
+                            ratio,
+                            grid,
+                            *precision,
+                        );
                         ImageSurface::pop_current();
                         f.redraw();
+                        notify_change(&on_change);
+                        true
+                    }
+                    Event::Drag => {
+                        if let Some((fi, ci)) = *dragging {
+                            let coords = app::event_coords();
+                            let p =
+                                view.to_world((coords.0 - f.x()) as f64, (coords.1 - f.y()) as f64);
+                            let p = if *snap { snap_to_grid(p, &view) } else { p };
+                            notify_move(&on_move, Some(p));
+
+                            // keep the clicked point in sync so it moves with the handle
+                            let old = figures[fi].figure.get_control_points()[ci];
+                            for q in points.iter_mut() {
+                                if q.get_x() == old.get_x() && q.get_y() == old.get_y() {
+                                    *q = p;
+                                }
+                            }
+                            figures[fi].figure.set_control_point(ci, p);
+                            figures[fi].source = None;
+                            update_constructions(&mut figures, view.get_bounds());
+
+                            // everything that crossed the curve has to be redrawn as well
+                            ImageSurface::push_current(&surf);
+                            draw_scene(
+                                &figures,
+                                &measurements,
+                                &points,
+                                &view,
+                                ratio,
+                                grid,
+                                *precision,
+                            );
+                            ImageSurface::pop_current();
+                            f.redraw();
+                            notify_change(&on_change);
+                        }
+                        true
+                    }
+                    Event::Released => {
+                        *dragging = None;
+                        true
+                    }
+                    Event::Move => {
+                        let coords = app::event_coords();
+                        let (x, y) = (coords.0 - f.x(), coords.1 - f.y());
+                        let p = view.to_world(x as f64, y as f64);
+                        notify_move(&on_move, Some(p));
+                        // only what the replay shows
+                        let (shapes_end, points_end, _) = protocol::get_ends(&protocol, *replay)
+                            .unwrap_or((figures.len(), points.len(), measurements.len()));
+                        let text = get_hover_text(
+                            &figures[..shapes_end],
+                            &points[..points_end],
+                            p,
+                            &view,
+                            *precision,
+                        );
+                        if text.is_some() || hover.is_some() {
+                            *hover = text.map(|text| (x, y, text));
+                            f.redraw();
+                        }
+                        true
+                    }
+                    // needed to get the move events
+                    Event::Enter => true,
+                    Event::Leave => {
+                        notify_move(&on_move, None);
+                        if hover.take().is_some() {
+                            f.redraw();
+                        }
                         true
                     }
                     _ => false,
@@ -324,81 +755,1542 @@ impl Canvas {
             }
         });
 
-        Self {
+        let mut canvas = Self {
             frame,
             surf,
-            r,
-            c,
-            l,
+            tool,
             points,
             buffer,
+            started,
             color,
             fill,
-            lines,
-            circles,
-            lines_buffer,
-            circles_buffer,
+            line_width,
+            grid,
+            snap,
+            figures,
+            measurements,
+            dragging,
+            view,
+            params,
+            tolerance,
+            precision,
+            hover,
+            on_change,
+            on_move,
+            script,
+            protocol,
+            replay,
+            ratio,
+            message,
+        };
+
+        // the window can be resized, the surface follows the frame
+        canvas.frame.resize_callback({
+            let mut canvas = canvas.clone();
+            move |_, _, _, w, h| canvas.resize(w, h)
+        });
+
+        canvas
+    }
+
+    // a new surface the size of the frame, the scene stays centered
+    fn resize(&mut self, w: i32, h: i32) {
+        let view = *self.view.borrow_mut();
+        if w <= 0 || h <= 0 || (w, h) == (view.get_width(), view.get_height()) {
+            return;
         }
+        let (surf, ratio) = new_surface(w, h);
+        *self.surf.borrow_mut() = surf;
+        *self.ratio.borrow_mut() = ratio;
+        *self.view.borrow_mut() = view.resize(w, h);
+        self.render();
     }
 
-    pub fn clear(&mut self) {
+    // draws everything again from the stored figures, or as much as the replay has got to
+    fn render(&mut self) {
         let surf = self.surf.borrow_mut();
-        let mut buf = self.buffer.borrow_mut();
-        let mut points = self.points.borrow_mut();
-        let mut lines = self.lines.borrow_mut();
-        let mut circles = self.circles.borrow_mut();
-        points.clear();
-        buf.clear();
-        lines.clear();
-        circles.clear();
+        let figures = self.figures.borrow_mut();
+        let measurements = self.measurements.borrow_mut();
+        let points = self.points.borrow_mut();
+        let (shapes_end, points_end, measurements_end) = protocol::get_ends(
+            &self.protocol.borrow_mut(),
+            *self.replay.borrow_mut(),
+        )
+        .unwrap_or((figures.len(), points.len(), measurements.len()));
         ImageSurface::push_current(&surf);
-        draw_rect_fill(0, 0, self.frame.w(), self.frame.h(), Color::White);
+        draw_scene(
+            &figures[..shapes_end],
+            &measurements[..measurements_end],
+            &points[..points_end],
+            &self.view.borrow_mut(),
+            *self.ratio.borrow_mut(),
+            *self.grid.borrow_mut(),
+            *self.precision.borrow_mut(),
+        );
         ImageSurface::pop_current();
         self.frame.redraw();
+        notify_change(&self.on_change);
     }
 
-    pub fn load(&mut self, points: Vec<Point>) {
-        let surf = self.surf.borrow_mut();
-        ImageSurface::push_current(&surf);
-        for p in points {
-            draw_circle(p.get_x(), p.get_y(), 5.0);
+    // writes what the canvas shows to an .svg or .png file, the PNG as sharp as the screen
+    pub fn export_image(&self, path: &Path) -> Result<(), String> {
+        let format = get_image_format(path)?;
+        let figures = self.figures.borrow_mut();
+        let points = self.points.borrow_mut();
+        let (shapes_end, points_end, _) = protocol::get_ends(
+            &self.protocol.borrow_mut(),
+            *self.replay.borrow_mut(),
+        )
+        .unwrap_or((figures.len(), points.len(), 0));
+        let image = export(
+            &figures[..shapes_end],
+            &points[..points_end],
+            &self.view.borrow_mut(),
+            format,
+            *self.ratio.borrow_mut(),
+        );
+        fs::write(path, image).map_err(|e| format!("can't write {}: {}", path.display(), e))
+    }
+
+    pub fn clear(&mut self) {
+        {
+            let mut buf = self.buffer.borrow_mut();
+            let mut points = self.points.borrow_mut();
+            let mut figures = self.figures.borrow_mut();
+            points.clear();
+            buf.clear();
+            figures.clear();
+            self.measurements.borrow_mut().clear();
+            self.params.borrow_mut().clear();
+            *self.dragging.borrow_mut() = None;
+            *self.script.borrow_mut() = None;
+            self.protocol.borrow_mut().clear();
+            *self.replay.borrow_mut() = None;
         }
-        ImageSurface::pop_current();
-        self.frame.redraw();
+        self.render();
     }
 
-    // a function that should change l, r, c to a difrent bool value
-    pub fn toggle(&mut self, buttons: (bool, bool, bool)) {
-        let mut l = self.l.borrow_mut();
-        let mut r = self.r.borrow_mut();
-        let mut c = self.c.borrow_mut();
+    // takes back the last step of the protocol with everything it made, or the clicks of
+    // a figure that isn't finished yet
+    pub fn undo(&mut self) {
+        if self.cancel() {
+            return;
+        }
+        *self.dragging.borrow_mut() = None;
+        *self.replay.borrow_mut() = None;
+        {
+            let mut protocol = self.protocol.borrow_mut();
+            if protocol.pop().is_some() {
+                let (shapes_end, points_end, measurements_end) =
+                    protocol.last().map_or((0, 0, 0), |step| step.get_ends());
+                self.figures.borrow_mut().truncate(shapes_end);
+                self.points.borrow_mut().truncate(points_end);
+                self.measurements.borrow_mut().truncate(measurements_end);
+            }
+        }
+        self.render();
+    }
 
-        match buttons {
-            (true, false, false) => {
-                *l = true;
-                *r = false;
-                *c = false;
+    // gives up the figure being built with the points its clicks made, false if there is none
+    pub fn cancel(&mut self) -> bool {
+        let dropped = drop_pending(
+            &mut self.buffer.borrow_mut(),
+            &mut self.figures.borrow_mut(),
+            &mut self.points.borrow_mut(),
+            *self.started.borrow_mut(),
+        );
+        if dropped {
+            self.render();
+        }
+        dropped
+    }
+
+    // deletes an object together with the ones built on it and what measures them
+    pub fn delete(&mut self, i: usize) {
+        {
+            let mut figures = self.figures.borrow_mut();
+            if i >= figures.len() {
+                return;
+            }
+            // the parents come first, so one pass finds everything that depends on i
+            let mut deleted = vec![false; figures.len()];
+            deleted[i] = true;
+            for j in i + 1..figures.len() {
+                if let Some(construction) = figures[j].construction {
+                    deleted[j] = construction.get_parents().iter().any(|p| deleted[*p]);
+                }
             }
-            (false, true, false) => {
-                *l = false;
-                *r = true;
-                *c = false;
+            // how many of the first n figures are left, which is also the new index of figure n
+            let kept_shapes = get_kept(&deleted);
+            let names: Vec<String> = figures
+                .iter()
+                .zip(&deleted)
+                .filter(|(_, d)| **d)
+                .map(|(shape, _)| shape.name.clone())
+                .collect();
+            let shapes = std::mem::take(&mut *figures);
+            *figures = shapes
+                .into_iter()
+                .zip(&deleted)
+                .filter(|(_, d)| !**d)
+                .map(|(mut shape, _)| {
+                    shape.construction = shape.construction.map(|c| c.remap(|p| kept_shapes[p]));
+                    shape
+                })
+                .collect();
+
+            let mut measurements = self.measurements.borrow_mut();
+            let measured: Vec<bool> = measurements
+                .iter()
+                .map(|m| m.get_parents().iter().any(|p| deleted[*p]))
+                .collect();
+            let kept_measurements = get_kept(&measured);
+            *measurements = measurements
+                .iter()
+                .zip(&measured)
+                .filter(|(_, d)| !**d)
+                .map(|(m, _)| m.remap(|p| kept_shapes[p]))
+                .collect();
+
+            let mut protocol = self.protocol.borrow_mut();
+            *protocol =
+                protocol::remove_objects(&protocol, &kept_shapes, &kept_measurements, &names);
+            self.buffer.borrow_mut().clear();
+            *self.dragging.borrow_mut() = None;
+            *self.replay.borrow_mut() = None;
+        }
+        self.render();
+    }
+
+    // magnifies the scene around the middle of the canvas
+    pub fn zoom(&mut self, factor: f64) {
+        let view = *self.view.borrow_mut();
+        *self.view.borrow_mut() = view.zoom(factor);
+        self.render();
+    }
+
+    // back to the scale and origin the canvas started with
+    pub fn reset_zoom(&mut self) {
+        let view = *self.view.borrow_mut();
+        *self.view.borrow_mut() = View::new(view.get_width(), view.get_height());
+        self.render();
+    }
+
+    // the points of a file are pixels from the top left of the canvas, like they were before
+    // the canvas had world coordinates, so they land where the view shows those pixels
+    pub fn load(&mut self, points: Vec<Point>) {
+        let view = *self.view.borrow_mut();
+        self.points.borrow_mut().extend(
+            points
+                .into_iter()
+                .map(|p| view.to_world(p.get_x(), p.get_y())),
+        );
+        self.record(String::from("Points from a file"), vec![]);
+        self.render();
+    }
+
+    // takes "y = a*sin(x)", "f(x) = x^2" or just "x^2" to plot a function,
+    // "(cos(t), sin(t))" or "r = 1 + cos(θ)" with an optional range like "[0, pi]"
+    // for a parametric or polar curve, or "a = 2" to set a parameter the curves can use
+    pub fn plot(&mut self, input: &str) -> Result<(), String> {
+        plot(
+            &mut self.figures.borrow_mut(),
+            &mut self.params.borrow_mut(),
+            input,
+            self.color.borrow_mut().to_rgb(),
+        )?;
+        self.record(String::from("Plot"), vec![String::from(input.trim())]);
+        self.render();
+        Ok(())
+    }
+
+    // a statement of the construction language like "c = Circle(A, 5)", anything else
+    // is plotted
+    pub fn execute(&mut self, input: &str) -> Result<(), String> {
+        execute(
+            &mut self.figures.borrow_mut(),
+            &mut self.params.borrow_mut(),
+            input,
+            self.get_new_style(),
+            self.view.borrow_mut().get_bounds(),
+        )?;
+        self.record_statement(input);
+        self.render();
+        Ok(())
+    }
+
+    // clears the scene for a script, which then runs at once or a statement at a time
+    pub fn load_script(&mut self, script: Script, all: bool) -> Result<(), String> {
+        self.clear();
+        *self.script.borrow_mut() = Some(script);
+        if all {
+            self.step_script(usize::MAX)
+        } else {
+            self.render();
+            Ok(())
+        }
+    }
+
+    // runs up to n statements of the loaded script, an error ends the script there
+    pub fn step_script(&mut self, n: usize) -> Result<(), String> {
+        let mut result = Ok(());
+        // one at a time, so each statement gets its own step in the protocol
+        for _ in 0..n {
+            let statement = match self.get_next_step() {
+                Some((_, statement)) => statement,
+                None => break,
+            };
+            result = match self.script.borrow_mut().as_mut() {
+                Some(script) => run_script(
+                    &mut self.figures.borrow_mut(),
+                    &mut self.params.borrow_mut(),
+                    script,
+                    1,
+                    self.get_new_style(),
+                    self.view.borrow_mut().get_bounds(),
+                ),
+                None => Ok(()),
+            };
+            if result.is_err() {
+                break;
             }
-            _ => {
-                *l = false;
-                *r = false;
-                *c = true;
+            self.record_statement(&statement);
+        }
+        {
+            let mut script = self.script.borrow_mut();
+            if result.is_err() || script.as_ref().is_some_and(|s| s.is_finished()) {
+                *script = None;
             }
         }
+        self.render();
+        result
+    }
+
+    // the line number and the statement a step would run
+    pub fn get_next_step(&self) -> Option<(usize, String)> {
+        let script = self.script.borrow_mut();
+        let (line, statement) = script.as_ref()?.peek()?;
+        Some((line, String::from(statement)))
+    }
+
+    // the tool, or command, of a statement and what it was given
+    fn record_statement(&mut self, input: &str) {
+        let (tool, inputs) = match command::parse(input) {
+            Ok(Some(statement)) => match statement.get_command() {
+                Some(command) => (String::from(command), statement.get_arguments().to_vec()),
+                None => (
+                    String::from("Point"),
+                    vec![format!("({})", statement.get_arguments().join(", "))],
+                ),
+            },
+            _ => (String::from("Plot"), vec![String::from(input.trim())]),
+        };
+        self.record(tool, inputs);
+    }
+
+    // a new step ends any replay, since it comes after all the others, and the figure being
+    // built, whose points are kept with the step
+    fn record(&mut self, tool: String, inputs: Vec<String>) {
+        self.buffer.borrow_mut().clear();
+        record_step(
+            &mut self.protocol.borrow_mut(),
+            tool,
+            inputs,
+            &self.figures.borrow_mut(),
+            self.points.borrow_mut().len(),
+            self.measurements.borrow_mut().len(),
+        );
+        *self.replay.borrow_mut() = None;
+    }
+
+    pub fn get_protocol(&self) -> Vec<Step> {
+        self.protocol.borrow_mut().clone()
+    }
+
+    // how many steps of the protocol are shown, None for all of them
+    pub fn get_replay(&self) -> Option<usize> {
+        *self.replay.borrow_mut()
+    }
+
+    pub fn set_replay(&mut self, steps: Option<usize>) {
+        let len = self.protocol.borrow_mut().len();
+        *self.replay.borrow_mut() = steps.filter(|n| *n < len);
+        self.render();
+    }
+
+    // the clicks for the last tool don't go into a figure of the new one
+    pub fn toggle(&mut self, tool: Tool) {
+        *self.tool.borrow_mut() = tool;
+        *self.message.borrow_mut() = None;
+        if !self.cancel() {
+            notify_change(&self.on_change);
+        }
+    }
+
+    pub fn get_tool(&self) -> Tool {
+        *self.tool.borrow_mut()
     }
 
     pub fn set_color(&mut self, color: (u8, u8, u8)) {
-        let mut c = self.color.borrow_mut();
-        *c = Color::from_rgb(color.0, color.1, color.2);
+        *self.color.borrow_mut() = Color::from_rgb(color.0, color.1, color.2);
+        notify_change(&self.on_change);
+    }
+
+    pub fn get_color(&self) -> (u8, u8, u8) {
+        self.color.borrow_mut().to_rgb()
+    }
+
+    // what objects made now look like
+    fn get_new_style(&self) -> Style {
+        Style::new(self.color.borrow_mut().to_rgb(), *self.fill.borrow_mut())
+            .with_width(*self.line_width.borrow_mut())
     }
 
     pub fn set_fill(&mut self, fill: bool) {
-        let mut f = self.fill.borrow_mut();
-        *f = fill;
+        *self.fill.borrow_mut() = fill;
+        notify_change(&self.on_change);
+    }
+
+    pub fn get_fill(&self) -> bool {
+        *self.fill.borrow_mut()
+    }
+
+    pub fn set_line_width(&mut self, width: i32) {
+        *self.line_width.borrow_mut() = width;
+    }
+
+    pub fn get_line_width(&self) -> i32 {
+        *self.line_width.borrow_mut()
+    }
+
+    pub fn set_grid(&mut self, grid: bool) {
+        *self.grid.borrow_mut() = grid;
+        self.render();
+    }
+
+    pub fn get_grid(&self) -> bool {
+        *self.grid.borrow_mut()
+    }
+
+    pub fn set_snap(&mut self, snap: bool) {
+        *self.snap.borrow_mut() = snap;
+    }
+
+    pub fn get_snap(&self) -> bool {
+        *self.snap.borrow_mut()
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: Tolerance) {
+        let mut t = self.tolerance.borrow_mut();
+        *t = tolerance;
+    }
+
+    pub fn get_tolerance(&self) -> Tolerance {
+        *self.tolerance.borrow_mut()
+    }
+
+    pub fn set_precision(&mut self, precision: usize) {
+        *self.precision.borrow_mut() = precision;
+        // the measurements are labelled with as many decimals
+        self.render();
+    }
+
+    pub fn get_precision(&self) -> usize {
+        *self.precision.borrow_mut()
+    }
+
+    // called after anything in the scene changed, once the canvas is free to be asked about it
+    pub fn add_on_change(&mut self, on_change: impl FnMut() + 'static) {
+        self.on_change.borrow_mut().push(Box::new(on_change));
+    }
+
+    // called with where the mouse is over the canvas, None once it leaves
+    pub fn add_on_move(&mut self, on_move: impl FnMut(Option<Point>) + 'static) {
+        self.on_move.borrow_mut().push(Box::new(on_move));
+    }
+
+    // shown in the status bar instead of the hint until the next click
+    pub fn set_message(&mut self, message: &str) {
+        *self.message.borrow_mut() = Some(String::from(message));
+        notify_change(&self.on_change);
+    }
+
+    // what the next click of the tool is for, and how to give up a figure that is started;
+    // why the last click made nothing until the next one
+    pub fn get_hint(&self) -> String {
+        if let Some(message) = self.message.borrow_mut().as_ref() {
+            return message.clone();
+        }
+        let clicks = self.buffer.borrow_mut().len();
+        let hint = self.tool.borrow_mut().get_hint(clicks);
+        if clicks == 0 {
+            hint
+        } else {
+            format!("{} (a right click cancels)", hint)
+        }
+    }
+
+    pub fn get_objects(&self) -> Vec<ObjectInfo> {
+        self.figures
+            .borrow_mut()
+            .iter()
+            .map(|shape| ObjectInfo {
+                name: shape.name.clone(),
+                kind: shape.figure.get_kind(),
+                color: shape.style.get_color(),
+                visible: shape.visible,
+                dependent: shape.construction.is_some(),
+            })
+            .collect()
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        self.figures
+            .borrow_mut()
+            .iter()
+            .map(|shape| shape.name.clone())
+            .collect()
+    }
+
+    pub fn get_properties(&self, i: usize) -> Vec<(String, f64)> {
+        match self.figures.borrow_mut().get(i) {
+            Some(shape) => shape.figure.get_properties(),
+            None => vec![],
+        }
+    }
+
+    // changes one of the numbers of get_properties, everything depending on the figure follows
+    pub fn set_property(&mut self, i: usize, j: usize, value: f64) -> Result<(), String> {
+        {
+            let mut figures = self.figures.borrow_mut();
+            let shape = figures.get_mut(i).ok_or("there is no such object")?;
+            if shape.construction.is_some() {
+                return Err(format!("{} depends on other objects", shape.name));
+            }
+            let old = shape.figure.get_control_points();
+            let mut values: Vec<f64> = shape.figure.get_properties().iter().map(|p| p.1).collect();
+            if j >= values.len() {
+                return Err(String::from("there is no such property"));
+            }
+            values[j] = value;
+            if !shape.figure.set_properties(&values) {
+                return Err(format!("{} can't take that value", shape.name));
+            }
+            shape.source = None;
+            let new = shape.figure.get_control_points();
+            move_clicked_points(&mut self.points.borrow_mut(), &old, &new);
+            update_constructions(&mut figures, self.view.borrow_mut().get_bounds());
+        }
+        self.render();
+        Ok(())
+    }
+
+    // name and equation of every figure, in the order they were drawn
+    pub fn get_equations(&self) -> Vec<(String, String)> {
+        let precision = *self.precision.borrow_mut();
+        self.figures
+            .borrow_mut()
+            .iter()
+            .map(|shape| (shape.name.clone(), get_equation(&shape.figure, precision)))
+            .collect()
+    }
+
+    // the equation of figure i to be edited, as it was typed or with every decimal, so nothing
+    // the user doesn't touch gets rounded
+    pub fn get_source(&self, i: usize) -> Option<String> {
+        self.figures
+            .borrow_mut()
+            .get(i)
+            .map(|shape| match &shape.source {
+                Some(source) => source.clone(),
+                None => get_equation(&shape.figure, FULL_PRECISION),
+            })
+    }
+
+    // "a ∩ b: (1.00, 2.00)" for every pair of visible figures which cross where the canvas
+    // shows them
+    pub fn get_intersection_list(&self) -> Vec<String> {
+        let precision = *self.precision.borrow_mut();
+        let figures = self.figures.borrow_mut();
+        let (x_min, y_min, x_max, y_max) = self.view.borrow_mut().get_bounds();
+        let visible = |p: &&Point| {
+            (x_min..=x_max).contains(&p.get_x()) && (y_min..=y_max).contains(&p.get_y())
+        };
+        let mut list = vec![];
+        for (i, shape) in figures.iter().enumerate().filter(|(_, s)| s.visible) {
+            for other in figures[..i].iter().filter(|s| s.visible) {
+                let bounds = (x_min, y_min, x_max, y_max);
+                let points = get_intersections(&other.figure, &shape.figure, bounds);
+                let points: Vec<String> = points
+                    .iter()
+                    .filter(visible)
+                    .map(|p| format_point(*p, precision))
+                    .collect();
+                if points.is_empty() {
+                    continue;
+                }
+                list.push(format!(
+                    "{} ∩ {}: {}",
+                    other.name,
+                    shape.name,
+                    points.join(", ")
+                ));
+            }
+        }
+        list
+    }
+
+    // replaces a figure with the one an edited equation describes
+    pub fn redefine(&mut self, i: usize, equation: &str) -> Result<(), String> {
+        {
+            let mut figures = self.figures.borrow_mut();
+            let shape = figures.get_mut(i).ok_or("there is no such object")?;
+            if shape.construction.is_some() {
+                return Err(format!("{} depends on other objects", shape.name));
+            }
+            let figure = parse_equation(equation, &shape.figure, &self.params.borrow_mut())?;
+            let old = shape.figure.get_control_points();
+            shape.figure = figure;
+            shape.source = Some(equation.trim().to_string());
+            let new = shape.figure.get_control_points();
+            move_clicked_points(&mut self.points.borrow_mut(), &old, &new);
+            update_constructions(&mut figures, self.view.borrow_mut().get_bounds());
+        }
+        self.render();
+        Ok(())
+    }
+
+    pub fn get_style(&self, i: usize) -> Option<Style> {
+        self.figures.borrow_mut().get(i).map(|shape| shape.style)
+    }
+
+    pub fn set_style(&mut self, i: usize, style: Style) {
+        if let Some(shape) = self.figures.borrow_mut().get_mut(i) {
+            shape.style = style;
+        }
+        self.render();
+    }
+
+    pub fn set_visible(&mut self, i: usize, visible: bool) {
+        if let Some(shape) = self.figures.borrow_mut().get_mut(i) {
+            shape.visible = visible;
+        }
+        self.render();
+    }
+}
+
+// adds what was built since the step before to the protocol, if anything was
+fn record_step(
+    protocol: &mut Vec<Step>,
+    tool: String,
+    inputs: Vec<String>,
+    shapes: &[Shape],
+    points: usize,
+    measurements: usize,
+) {
+    let start = protocol.last().map_or((0, 0, 0), |step| step.get_ends());
+    let end = (shapes.len(), points, measurements);
+    if end == start {
+        return;
+    }
+    let mut outputs: Vec<String> = shapes[start.0..].iter().map(|s| s.name.clone()).collect();
+    if points > start.1 && outputs.is_empty() {
+        outputs.push(format!("{} points", points - start.1));
+    }
+    if measurements > start.2 {
+        outputs.push(String::from("measurement"));
+    }
+    protocol.push(Step::new(tool, inputs, outputs, end));
+}
+
+// for each n how many of the first n items are not deleted, one longer than deleted
+fn get_kept(deleted: &[bool]) -> Vec<usize> {
+    let mut kept = vec![0];
+    for d in deleted {
+        kept.push(kept[kept.len() - 1] + !*d as usize);
+    }
+    kept
+}
+
+// the objects the clicks of a tool picked by name, and where the others were
+fn get_step_inputs(
+    tool: Tool,
+    shapes: &[Shape],
+    clicks: &[Point],
+    view: &View,
+    precision: usize,
+) -> Vec<String> {
+    clicks
+        .iter()
+        .enumerate()
+        .map(|(i, click)| {
+            let picked = match tool.get_pick(i) {
+                Pick::Place => None,
+                Pick::Point => find_point(shapes, *click, view),
+                Pick::Line => find_line(shapes, *click, view),
+                Pick::Circle => find_circle(shapes, *click, view),
+                Pick::Figure => find_figure(shapes, *click, view),
+            };
+            match picked {
+                Some(i) => shapes[i].name.clone(),
+                None => format_point(*click, precision),
+            }
+        })
+        .collect()
+}
+
+// runs up to n statements of a script, stopping at the first one which fails
+fn run_script(
+    shapes: &mut Vec<Shape>,
+    params: &mut HashMap<String, f64>,
+    script: &mut Script,
+    n: usize,
+    style: Style,
+    bounds: (f64, f64, f64, f64),
+) -> Result<(), String> {
+    for _ in 0..n {
+        let (line, statement) = match script.peek() {
+            Some(step) => step,
+            None => break,
+        };
+        execute(shapes, params, statement, style, bounds)
+            .map_err(|e| format!("line {}: {}", line, e))?;
+        script.advance();
+    }
+    Ok(())
+}
+
+// the formats the scene can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+// the format the extension of path asks for
+pub fn get_image_format(path: &Path) -> Result<ImageFormat, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match extension.to_lowercase().as_str() {
+        "svg" => Ok(ImageFormat::Svg),
+        "png" => Ok(ImageFormat::Png),
+        _ => Err(format!(
+            "{}: only .svg and .png images can be exported",
+            path.display()
+        )),
+    }
+}
+
+// runs a script without a window and draws what it builds as an image
+pub fn export_script(
+    script: &mut Script,
+    width: i32,
+    height: i32,
+    format: ImageFormat,
+) -> Result<Vec<u8>, String> {
+    let view = View::new(width, height);
+    let mut shapes = vec![];
+    let mut params = HashMap::new();
+    run_script(
+        &mut shapes,
+        &mut params,
+        script,
+        usize::MAX,
+        Style::new((0, 0, 0), false),
+        view.get_bounds(),
+    )?;
+    Ok(export(&shapes, &[], &view, format, 1.0))
+}
+
+// an SVG has the figures, a PNG is what the canvas shows without the handles and labels
+fn export(
+    shapes: &[Shape],
+    points: &[Point],
+    view: &View,
+    format: ImageFormat,
+    ratio: f64,
+) -> Vec<u8> {
+    match format {
+        ImageFormat::Svg => {
+            let figures: Vec<(&Figure, &Style)> = shapes
+                .iter()
+                .filter(|s| s.visible)
+                .map(|s| (&s.figure, &s.style))
+                .collect();
+            svg::to_svg(&figures, view).into_bytes()
+        }
+        ImageFormat::Png => {
+            let mut raster = Raster::new(view.get_width(), view.get_height(), ratio);
+            render_scene(&mut raster, shapes, points, view, false);
+            raster.to_png()
+        }
+    }
+}
+
+// a statement of the construction language, or a curve or parameter for plot; intersections
+// are looked for within bounds
+fn execute(
+    shapes: &mut Vec<Shape>,
+    params: &mut HashMap<String, f64>,
+    input: &str,
+    style: Style,
+    bounds: (f64, f64, f64, f64),
+) -> Result<(), String> {
+    let statement = match command::parse(input)? {
+        Some(statement) => statement,
+        None => return plot(shapes, params, input, style.get_color()),
+    };
+    if statement.get_command() == Some("Style") {
+        return apply_style(shapes, &statement);
+    }
+    if let Some(name) = statement.get_name() {
+        if shapes.iter().any(|s| s.name == name) {
+            return Err(format!("'{}' is already used", name));
+        }
+    }
+    let (points, outputs) = {
+        let lookup = |name: &str| {
+            let i = shapes.iter().position(|s| s.name == name)?;
+            Some((i, &shapes[i].figure))
+        };
+        statement.build(&lookup, shapes.len(), params, bounds)?
+    };
+
+    // the points that were written out come first, like the ones a click makes
+    let count = shapes.len();
+    for p in points {
+        push_shape(shapes, Figure::Point(p), Style::new((0, 0, 0), false), None);
+    }
+    let several = outputs.len() > 1;
+    for (k, output) in outputs.into_iter().enumerate() {
+        let (figure, construction) = match output {
+            command::Output::Free(figure) => (Some(figure), None),
+            command::Output::Dependent(construction) => {
+                let parents = &shapes;
+                let figure = construction.apply(|i| parents.get(i).map(|s| &s.figure), bounds);
+                (figure, Some(construction))
+            }
+        };
+        let figure = match figure {
+            Some(figure) => figure,
+            None => {
+                let parents = &shapes;
+                let refusal =
+                    construction.and_then(|c| c.get_refusal(|i| parents.get(i).map(|s| &s.figure)));
+                shapes.truncate(count);
+                return Err(String::from(refusal.unwrap_or("the result isn't defined")));
+            }
+        };
+        // several results are numbered, A1, A2, ...
+        let name = statement.get_name().map(|name| {
+            if several {
+                format!("{}{}", name, k + 1)
+            } else {
+                String::from(name)
+            }
+        });
+        if let Some(name) = &name {
+            if shapes.iter().any(|s| &s.name == name) {
+                shapes.truncate(count);
+                return Err(format!("'{}' is already used", name));
+            }
+        }
+        push_shape(shapes, figure, style, construction);
+        if let (Some(name), Some(shape)) = (name, shapes.last_mut()) {
+            shape.name = name;
+        }
+    }
+    Ok(())
+}
+
+// "Style(c, width=2, dash=dotted)" changes how an object looks
+fn apply_style(shapes: &mut [Shape], statement: &command::Statement) -> Result<(), String> {
+    if statement.get_name().is_some() {
+        return Err(String::from("Style doesn't make an object to name"));
+    }
+    let (name, settings) = statement
+        .get_arguments()
+        .split_first()
+        .ok_or("Style needs an object")?;
+    let shape = shapes
+        .iter_mut()
+        .find(|s| &s.name == name)
+        .ok_or_else(|| format!("there is no object called '{}'", name))?;
+    shape.style = shape.style.parse(&settings.join(" "))?;
+    Ok(())
+}
+
+// see Canvas::plot
+fn plot(
+    shapes: &mut Vec<Shape>,
+    params: &mut HashMap<String, f64>,
+    input: &str,
+    color: (u8, u8, u8),
+) -> Result<(), String> {
+    let style = Style::new(color, false);
+    let (input, range) = expr::split_range(input.trim());
+    let range = match range {
+        Some(r) => Some(expr::parse_range(r, params)?),
+        None => None,
+    };
+
+    // "(x(t), y(t))" but not "(x + 1)^2"
+    let coordinates = match input.strip_prefix('(').and_then(|i| i.strip_suffix(')')) {
+        Some(inner) => Some(expr::split_arguments(inner)).filter(|a| a.len() == 2),
+        None => None,
+    };
+    if let Some(args) = coordinates {
+        let range = range.unwrap_or((0.0, 2.0 * f64::consts::PI));
+        let curve = Parametric::new(args[0], args[1], params.clone(), range)?;
+        push_shape(shapes, Figure::Parametric(curve), style, None);
+        return Ok(());
+    }
+    let (name, source) = match input.split_once('=') {
+        Some((lhs, rhs)) => (Some(lhs.trim()), rhs.trim()),
+        None => (None, input),
+    };
+
+    // "r = ...", "a = ..." or "y = ..." and a plain expression
+    match name {
+        Some("r") => {
+            let range = range.unwrap_or((0.0, 2.0 * f64::consts::PI));
+            let curve = Parametric::polar(source, params.clone(), range)?;
+            push_shape(shapes, Figure::Parametric(curve), style, None);
+        }
+        Some(name) if name != "y" && !name.contains('(') => {
+            if range.is_some() {
+                return Err(String::from("a parameter doesn't take a range"));
+            }
+            if !is_parameter_name(name) {
+                return Err(format!("'{}' can't be used as a parameter name", name));
+            }
+            let value = expr::parse(source)?.eval(&|n| params.get(n).copied())?;
+            params.insert(String::from(name), value);
+            for shape in shapes.iter_mut() {
+                shape.figure.set_params(params);
+            }
+        }
+        _ => {
+            if range.is_some() {
+                return Err(String::from(
+                    "a function is plotted over the visible x range",
+                ));
+            }
+            let graph = FunctionGraph::new(source, params.clone())?;
+            push_shape(shapes, Figure::Function(graph), style, None);
+        }
+    }
+    Ok(())
+}
+
+// adds a figure under the next free name, points get capital letters like in a textbook
+fn push_shape(
+    shapes: &mut Vec<Shape>,
+    figure: Figure,
+    style: Style,
+    construction: Option<Construction>,
+) {
+    let name = get_next_name(shapes, &figure);
+    shapes.push(Shape {
+        figure,
+        name,
+        style,
+        visible: true,
+        construction,
+        source: None,
+    });
+}
+
+// A, B, ..., Z, A1, B1, ... and the same in lowercase, leaving out the letters used for
+// variables and constants in expressions
+fn get_next_name(shapes: &[Shape], figure: &Figure) -> String {
+    let letters: Vec<char> = match figure {
+        Figure::Point(_) => ('A'..='Z').collect(),
+        _ => ('a'..='z').filter(|c| !"ertxy".contains(*c)).collect(),
+    };
+    let mut n = 0;
+    loop {
+        for c in letters.iter() {
+            let name = if n == 0 {
+                c.to_string()
+            } else {
+                format!("{}{}", c, n)
+            };
+            if !shapes.iter().any(|s| s.name == name) {
+                return name;
+            }
+        }
+        n += 1;
+    }
+}
+
+// lets the panels catch up once the canvas isn't borrowed anymore
+fn notify_change(on_change: &Rc<RefCell<Vec<OnChange>>>) {
+    let on_change = on_change.clone();
+    app::add_timeout3(0.0, move |_| {
+        for on_change in on_change.borrow_mut().iter_mut() {
+            on_change();
+        }
+    });
+}
+
+fn notify_move(on_move: &Rc<RefCell<Vec<OnMove>>>, p: Option<Point>) {
+    let on_move = on_move.clone();
+    app::add_timeout3(0.0, move |_| {
+        for on_move in on_move.borrow_mut().iter_mut() {
+            on_move(p);
+        }
+    });
+}
+
+// forgets the clicks of the figure being built and the points they made, false if there
+// were none
+fn drop_pending(
+    buffer: &mut Vec<Point>,
+    figures: &mut Vec<Shape>,
+    points: &mut Vec<Point>,
+    (shapes_end, points_end): (usize, usize),
+) -> bool {
+    if buffer.is_empty() {
+        return false;
+    }
+    buffer.clear();
+    figures.truncate(shapes_end);
+    points.truncate(points_end);
+    true
+}
+
+// the clicked points move along with the control points they were placed as, like when dragging
+fn move_clicked_points(points: &mut [Point], old: &[Point], new: &[Point]) {
+    for q in points.iter_mut() {
+        if let Some(k) = old
+            .iter()
+            .position(|o| o.get_x() == q.get_x() && o.get_y() == q.get_y())
+        {
+            if let Some(p) = new.get(k) {
+                *q = *p;
+            }
+        }
+    }
+}
+
+// redraws the whole surface from the stored figures; the scene is rasterized with
+// anti-aliasing at the resolution of the screen, fltk adds the handles and the text
+fn draw_scene(
+    shapes: &[Shape],
+    measurements: &[Measurement],
+    points: &[Point],
+    view: &View,
+    ratio: f64,
+    grid: bool,
+    precision: usize,
+) {
+    let mut raster = Raster::new(view.get_width(), view.get_height(), ratio);
+    render_scene(&mut raster, shapes, points, view, grid);
+    let image = RgbImage::new(
+        raster.get_pixels(),
+        raster.get_width() as i32,
+        raster.get_height() as i32,
+        ColorDepth::Rgb8,
+    );
+    if let Ok(mut image) = image {
+        image.scale(view.get_width(), view.get_height(), false, true);
+        image.draw(0, 0, view.get_width(), view.get_height());
+    }
+    for shape in shapes.iter().filter(|s| s.visible) {
+        draw_controls(&shape.figure, view);
+    }
+    // on top of everything, so the values stay readable
+    for measurement in measurements.iter() {
+        if let Some(label) = measurement.measure(|i| shapes.get(i).map(|s| &s.figure), precision) {
+            draw_label(&label, view);
+        }
+    }
+}
+
+// the grid, the axes, the clicked points, the figures and where they cross
+fn render_scene(
+    renderer: &mut dyn Renderer,
+    shapes: &[Shape],
+    points: &[Point],
+    view: &View,
+    grid: bool,
+) {
+    let (x, y) = view.to_screen(Point::new(0.0, 0.0));
+    let (w, h) = (view.get_width() as f64, view.get_height() as f64);
+    if grid {
+        let step = get_grid_step(view);
+        let ((x0, x1), (y0, y1)) = (view.get_x_range(), view.get_y_range());
+        renderer.set_pen(Pen::new(GRID_COLOR, 1.0, LinePattern::Solid, 1.0));
+        for i in (x0 / step).ceil() as i64..=(x1 / step).floor() as i64 {
+            let (gx, _) = view.to_screen(Point::new(i as f64 * step, 0.0));
+            renderer.draw_polyline(&[(gx, 0.0), (gx, h)]);
+        }
+        for i in (y0 / step).ceil() as i64..=(y1 / step).floor() as i64 {
+            let (_, gy) = view.to_screen(Point::new(0.0, i as f64 * step));
+            renderer.draw_polyline(&[(0.0, gy), (w, gy)]);
+        }
+    }
+    renderer.set_pen(Pen::new(AXIS_COLOR, 1.0, LinePattern::Solid, 1.0));
+    renderer.draw_polyline(&[(0.0, y), (w, y)]);
+    renderer.draw_polyline(&[(x, 0.0), (x, h)]);
+
+    renderer.set_pen(Pen::new((0, 0, 0), 1.0, LinePattern::Solid, 1.0));
+    for p in points.iter() {
+        renderer.draw_marker(view.to_screen(*p), Marker::Dot, 2.0);
+    }
+    for (i, shape) in shapes.iter().enumerate() {
+        if !shape.visible {
+            continue;
+        }
+        render_figure(renderer, &shape.figure, &shape.style, view);
+        // the intersection points with everything drawn before
+        renderer.set_pen(Pen::new((255, 0, 0), 1.0, LinePattern::Solid, 1.0));
+        for other in shapes[..i].iter().filter(|s| s.visible) {
+            for p in get_intersections(&shape.figure, &other.figure, view.get_bounds()) {
+                renderer.draw_marker(view.to_screen(p), Marker::Dot, 2.0);
+            }
+        }
+    }
+}
+
+// 1, 2 or 5 times a power of ten in world units, so the grid lines are not too close
+fn get_grid_step(view: &View) -> f64 {
+    let min = GRID_SPACING / view.to_pixels(1.0);
+    let power = 10f64.powf(min.log10().floor());
+    [1.0, 2.0, 5.0]
+        .iter()
+        .map(|m| m * power)
+        .find(|step| *step >= min)
+        .unwrap_or(10.0 * power)
+}
+
+// the grid point nearest to p, whether the grid is shown or not
+fn snap_to_grid(p: Point, view: &View) -> Point {
+    let step = get_grid_step(view);
+    Point::new(
+        (p.get_x() / step).round() * step,
+        (p.get_y() / step).round() * step,
+    )
+}
+
+// the last drawn figure wins if control points lie on top of each other,
+// figures which follow others can't be dragged themselves
+fn find_control_point(shapes: &[Shape], p: Point, view: &View) -> Option<(usize, usize)> {
+    for (fi, shape) in shapes.iter().enumerate().rev() {
+        if shape.construction.is_some() || !shape.visible {
+            continue;
+        }
+        for (ci, c) in shape.figure.get_control_points().iter().enumerate() {
+            if view.to_pixels(get_distance(*c, p)) <= GRAB_DISTANCE {
+                return Some((fi, ci));
+            }
+        }
+    }
+    None
+}
+
+// the figures which depend on others are built again from their parents, in order
+fn update_constructions(shapes: &mut [Shape], bounds: (f64, f64, f64, f64)) {
+    for i in 0..shapes.len() {
+        let (parents, rest) = shapes.split_at_mut(i);
+        if let Some(construction) = rest[0].construction {
+            // an undefined result keeps the last figure
+            if let Some(figure) = construction.apply(|j| parents.get(j).map(|s| &s.figure), bounds)
+            {
+                rest[0].figure = figure;
+            }
+        }
+    }
+}
+
+// the point object under p, the last one drawn wins
+fn find_point(shapes: &[Shape], p: Point, view: &View) -> Option<usize> {
+    shapes.iter().rposition(|shape| match shape.figure {
+        _ if !shape.visible => false,
+        Figure::Point(q) => view.to_pixels(get_distance(p, q)) <= GRAB_DISTANCE,
+        _ => false,
+    })
+}
+
+// the line under p, lines are drawn without ends
+fn find_line(shapes: &[Shape], p: Point, view: &View) -> Option<usize> {
+    shapes.iter().rposition(|shape| match shape.figure {
+        _ if !shape.visible => false,
+        Figure::Line(l) => view.to_pixels(get_line_distance(p, l)) <= GRAB_DISTANCE,
+        _ => false,
+    })
+}
+
+// the circle whose outline is under p
+fn find_circle(shapes: &[Shape], p: Point, view: &View) -> Option<usize> {
+    shapes.iter().rposition(|shape| match shape.figure {
+        _ if !shape.visible => false,
+        Figure::Circle(c) => {
+            view.to_pixels((get_distance(c.get_center(), p) - c.get_rad()).abs()) <= GRAB_DISTANCE
+        }
+        _ => false,
+    })
+}
+
+// any figure under p, the last one drawn wins
+fn find_figure(shapes: &[Shape], p: Point, view: &View) -> Option<usize> {
+    let tolerance = GRAB_DISTANCE / view.get_scale();
+    shapes
+        .iter()
+        .rposition(|shape| shape.visible && is_near(&shape.figure, p, tolerance, view.get_bounds()))
+}
+
+fn is_near(figure: &Figure, p: Point, tolerance: f64, bounds: (f64, f64, f64, f64)) -> bool {
+    let on_polyline = |points: &[Point]| {
+        points
+            .windows(2)
+            .any(|s| get_segment_distance(p, s[0], s[1]) <= tolerance)
+    };
+    match figure {
+        Figure::Point(q) => get_distance(p, *q) <= tolerance,
+        Figure::Line(l) => get_line_distance(p, *l) <= tolerance,
+        Figure::Circle(c) => (get_distance(c.get_center(), p) - c.get_rad()).abs() <= tolerance,
+        Figure::Arc(a) => {
+            (get_distance(a.get_center(), p) - a.get_rad()).abs() <= tolerance
+                && a.contains_point(p)
+        }
+        Figure::Rectangle(r) => {
+            let (a, b) = (r.get_point_a(), r.get_point_b());
+            on_polyline(&[
+                a,
+                Point::new(a.get_x(), b.get_y()),
+                b,
+                Point::new(b.get_x(), a.get_y()),
+                a,
+            ])
+        }
+        _ => figure
+            .get_polylines(tolerance / 4.0, bounds)
+            .is_some_and(|pieces| pieces.iter().any(|piece| on_polyline(piece))),
+    }
+}
+
+// the dependent figures a tool builds from the picked figures
+fn get_constructions(
+    tool: Tool,
+    shapes: &[Shape],
+    clicks: &[Point],
+    view: &View,
+) -> Vec<Construction> {
+    let point = |i: usize| find_point(shapes, clicks[i], view);
+    let line = |i: usize| find_line(shapes, clicks[i], view);
+    let circle = |i: usize| find_circle(shapes, clicks[i], view);
+    // the tangents which exist now, two at most from a point and four of two circles
+    let existing = |candidates: Vec<Construction>| -> Vec<Construction> {
+        let parent = |i: usize| shapes.get(i).map(|s| &s.figure);
+        candidates
+            .into_iter()
+            .filter(|c| c.apply(parent, view.get_bounds()).is_some())
+            .collect()
+    };
+    let figure = |i: usize| find_figure(shapes, clicks[i], view);
+    let found = match tool {
+        Tool::TangentAtPoint => circle(0).and_then(|circle| match shapes[circle].figure {
+            Figure::Circle(c) => {
+                let angle = get_angle(c.get_center(), clicks[0]);
+                Some(vec![Construction::Tangent { circle, angle }])
+            }
+            _ => None,
+        }),
+        Tool::TangentFromPoint => circle(0).zip(point(1)).map(|(circle, point)| {
+            existing(
+                (0..2)
+                    .map(|index| Construction::PointTangent {
+                        circle,
+                        point,
+                        index,
+                    })
+                    .collect(),
+            )
+        }),
+        Tool::CommonTangents => {
+            circle(0)
+                .zip(circle(1))
+                .filter(|(a, b)| a != b)
+                .map(|(first, second)| {
+                    existing(
+                        (0..4)
+                            .map(|index| Construction::CommonTangent {
+                                first,
+                                second,
+                                index,
+                            })
+                            .collect(),
+                    )
+                })
+        }
+        Tool::Parallel => line(0)
+            .zip(point(1))
+            .map(|(line, point)| vec![Construction::Parallel { line, point }]),
+        Tool::Perpendicular => line(0)
+            .zip(point(1))
+            .map(|(line, point)| vec![Construction::Perpendicular { line, point }]),
+        Tool::PerpendicularBisector => point(0)
+            .zip(point(1))
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| vec![Construction::PerpendicularBisector(a, b)]),
+        Tool::Midpoint => point(0)
+            .zip(point(1))
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| vec![Construction::Midpoint(a, b)]),
+        Tool::AngleBisector => {
+            line(0)
+                .zip(line(1))
+                .filter(|(a, b)| a != b)
+                .map(|(first, second)| {
+                    [false, true]
+                        .iter()
+                        .map(|&outer| Construction::AngleBisector {
+                            first,
+                            second,
+                            outer,
+                        })
+                        .collect()
+                })
+        }
+        Tool::Reflect => figure(0)
+            .zip(line(1))
+            .map(|(figure, line)| vec![Construction::Reflection { figure, line }]),
+        Tool::PointReflect => figure(0)
+            .zip(point(1))
+            .map(|(figure, center)| vec![Construction::PointReflection { figure, center }]),
+        Tool::Rotate(angle) => figure(0).zip(point(1)).map(|(figure, center)| {
+            vec![Construction::Rotation {
+                figure,
+                center,
+                angle,
+            }]
+        }),
+        Tool::Dilate(factor) => figure(0).zip(point(1)).map(|(figure, center)| {
+            vec![Construction::Dilation {
+                figure,
+                center,
+                factor,
+            }]
+        }),
+        Tool::Translate => figure(0)
+            .zip(point(1).zip(point(2)))
+            .map(|(figure, (from, to))| vec![Construction::Translation { figure, from, to }]),
+        Tool::Invert => figure(0)
+            .zip(circle(1))
+            .filter(|(figure, circle)| figure != circle)
+            .map(|(figure, circle)| vec![Construction::Inversion { figure, circle }]),
+        _ => None,
+    };
+    found.unwrap_or_default()
+}
+
+fn all_points(shapes: &[Shape], clicks: &[Point], view: &View) -> bool {
+    clicks.iter().all(|c| {
+        find_figure(shapes, *c, view).is_some_and(|i| matches!(shapes[i].figure, Figure::Point(_)))
+    })
+}
+
+// what the relation tool finds out about the picked figures, None for less than two
+fn get_relation_report(
+    shapes: &[Shape],
+    clicks: &[Point],
+    view: &View,
+    tolerance: Tolerance,
+    precision: usize,
+) -> Option<String> {
+    let picked: Vec<&Figure> = clicks
+        .iter()
+        .filter_map(|c| find_figure(shapes, *c, view))
+        .map(|i| &shapes[i].figure)
+        .collect();
+    let relations = match picked[..] {
+        [] | [_] => return None,
+        [f1, f2] => get_relations(f1, f2, tolerance, precision, view.get_bounds()),
+        _ => {
+            let points: Vec<Point> = picked
+                .iter()
+                .filter_map(|f| match f {
+                    Figure::Point(p) => Some(*p),
+                    _ => None,
+                })
+                .collect();
+            get_points_relations(&points, tolerance.get_distance())
+        }
+    };
+    Some(relations.join("\n"))
+}
+
+// the properties of what is under p, point objects and clicked points win over the figures
+fn get_hover_text(
+    shapes: &[Shape],
+    points: &[Point],
+    p: Point,
+    view: &View,
+    precision: usize,
+) -> Option<String> {
+    if let Some(i) = find_point(shapes, p, view) {
+        return Some(get_tooltip(&shapes[i].figure, precision));
+    }
+    if let Some(q) = points
+        .iter()
+        .rev()
+        .find(|q| view.to_pixels(get_distance(p, **q)) <= GRAB_DISTANCE)
+    {
+        return Some(format_point(*q, precision));
+    }
+    find_figure(shapes, p, view).map(|i| get_tooltip(&shapes[i].figure, precision))
+}
+
+// the measurement a tool takes of the picked points and figures
+fn get_measurement(
+    tool: Tool,
+    shapes: &[Shape],
+    clicks: &[Point],
+    view: &View,
+) -> Option<Measurement> {
+    let point = |i: usize| find_point(shapes, clicks[i], view);
+    let line = |i: usize| find_line(shapes, clicks[i], view);
+    match tool {
+        Tool::Distance => {
+            let a = point(0)?;
+            match point(1) {
+                Some(b) if a != b => Some(Measurement::Distance(a, b)),
+                Some(_) => None,
+                None => line(1).map(|line| Measurement::LineDistance { point: a, line }),
+            }
+        }
+        Tool::Angle => line(0)
+            .zip(line(1))
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| Measurement::Angle(a, b)),
+        Tool::ThreePointAngle => Some(Measurement::ThreePointAngle(
+            point(0)?,
+            point(1)?,
+            point(2)?,
+        )),
+        // only what encloses something, or the label would have nothing to show
+        Tool::Area => find_figure(shapes, clicks[0], view)
+            .map(Measurement::Area)
+            .filter(|m| m.measure(|i| shapes.get(i).map(|s| &s.figure), 0).is_some()),
+        _ => None,
+    }
+}
+
+fn is_parameter_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_')
+        && name != "x"
+        && name != "t"
+        && name != "r"
+        && name != "theta"
+        && expr::get_constant(name).is_none()
+}
+
+// what can be dragged, over the figure
+fn draw_controls(figure: &Figure, view: &View) {
+    match figure {
+        Figure::Bezier(b) => {
+            // the control polygon shows how the handles pull on the curve
+            set_draw_color(Color::Dark3);
+            set_line_style(LineStyle::Dash, 1);
+            draw_polyline(b.get_controls(), view);
+            draw_handles(b.get_controls(), view);
+        }
+        Figure::Spline(s) => {
+            set_draw_color(Color::Dark3);
+            draw_handles(s.get_points(), view);
+        }
+        Figure::Conic(c) => {
+            set_draw_color(Color::Dark3);
+            draw_handles(c.get_points(), view);
+        }
+        _ => {}
+    }
+}
+
+fn draw_polyline(points: &[Point], view: &View) {
+    draw::begin_line();
+    for p in points.iter() {
+        let (x, y) = view.to_screen(*p);
+        draw::vertex(x, y);
+    }
+    draw::end_line();
+}
+
+fn draw_handles(points: &[Point], view: &View) {
+    set_line_style(LineStyle::Solid, 1);
+    for p in points.iter() {
+        let (x, y) = view.to_screen(*p);
+        draw_rect(x as i32 - 3, y as i32 - 3, 7, 7);
+    }
+}
+
+// the value next to what it measures, angles get an arc around the vertex
+fn draw_label(label: &Label, view: &View) {
+    const ANGLE_RADIUS: f64 = 25.0;
+    set_draw_color(Color::DarkGreen);
+    set_line_style(LineStyle::Dash, 1);
+    if let Some((a, b)) = label.get_segment() {
+        let (x1, y1) = view.to_screen(a);
+        let (x2, y2) = view.to_screen(b);
+        draw_line(x1 as i32, y1 as i32, x2 as i32, y2 as i32);
+    }
+    set_line_style(LineStyle::Solid, 1);
+    let (mut x, mut y) = view.to_screen(label.get_position());
+    if let Some((vertex, start, sweep)) = label.get_angle() {
+        let (cx, cy) = view.to_screen(vertex);
+        let d = (ANGLE_RADIUS * 2.0) as i32;
+        let (x0, y0) = ((cx - ANGLE_RADIUS) as i32, (cy - ANGLE_RADIUS) as i32);
+        draw_arc(
+            x0,
+            y0,
+            d,
+            d,
+            start.to_degrees(),
+            (start + sweep).to_degrees(),
+        );
+        // the text goes past the arc, halfway between the legs; the screen y points down
+        let (sin, cos) = (start + sweep / 2.0).sin_cos();
+        x = cx + (ANGLE_RADIUS + 12.0) * cos;
+        y = cy - (ANGLE_RADIUS + 12.0) * sin;
+    }
+    draw::set_font(Font::Helvetica, 12);
+    let text = label.get_text();
+    let w = draw::width(text);
+    draw::draw_text(text, (x - w / 2.0) as i32, y as i32 - 4);
+}
+
+// rings around the clicks of the figure being built, so it is clear the tool waits for more
+fn draw_pending(clicks: &[Point], view: &View, x: i32, y: i32) {
+    set_draw_color(Color::from_rgb(255, 128, 0));
+    set_line_style(LineStyle::Solid, 2);
+    for p in clicks.iter() {
+        let (px, py) = view.to_screen(*p);
+        draw::draw_circle(x as f64 + px, y as f64 + py, 6.0);
+    }
+}
+
+// a box with the lines of text below and to the right of the mouse, kept inside the frame
+fn draw_tooltip(x: i32, y: i32, text: &str, max_x: i32, max_y: i32) {
+    draw::set_font(Font::Helvetica, 12);
+    let lines: Vec<&str> = text.lines().collect();
+    let line_height = draw::height();
+    let w = lines.iter().map(|l| draw::width(l)).fold(0.0, f64::max) as i32 + 8;
+    let h = line_height * lines.len() as i32 + 6;
+    let x = (x + 14).min(max_x - w).max(0);
+    let y = (y + 18).min(max_y - h).max(0);
+    draw_rect_fill(x, y, w, h, Color::from_rgb(255, 255, 225));
+    set_draw_color(Color::Dark3);
+    set_line_style(LineStyle::Solid, 1);
+    draw_rect(x, y, w, h);
+    set_draw_color(Color::Black);
+    for (i, line) in lines.iter().enumerate() {
+        let baseline = y + 3 + line_height * (i as i32 + 1) - draw::descent();
+        draw::draw_text(line, x + 4, baseline);
     }
 }
@@ -0,0 +1,817 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::construction::Construction;
+use crate::expr;
+use crate::figures::{
+    arc::Arc, bezier::Bezier, circle::Circle, conic::Conic, line::Line, point::Point,
+    rectangle::Rectangle, spline::Spline, Figure,
+};
+use crate::utils::{get_distance, get_intersections, get_tangents_from_point};
+
+// every command with what it takes, for the hints and the completion
+pub const COMMANDS: [(&str, &str); 20] = [
+    ("AngleBisector", "AngleBisector(line, line)"),
+    ("Arc", "Arc(start, point, end)"),
+    ("Bezier", "Bezier(start, control, ..., end)"),
+    ("Circle", "Circle(center, radius) or Circle(center, point)"),
+    ("Conic", "Conic(point, point, point, point, point)"),
+    ("Dilate", "Dilate(figure, center, factor)"),
+    (
+        "Intersect",
+        "Intersect(figure, figure) or Intersect(figure, figure, n)",
+    ),
+    ("Invert", "Invert(figure, circle)"),
+    ("Line", "Line(point, point)"),
+    ("Midpoint", "Midpoint(point, point)"),
+    ("Parallel", "Parallel(line, point)"),
+    ("Perpendicular", "Perpendicular(line, point)"),
+    (
+        "PerpendicularBisector",
+        "PerpendicularBisector(point, point)",
+    ),
+    ("Rect", "Rect(corner, corner)"),
+    ("Reflect", "Reflect(figure, line) or Reflect(figure, point)"),
+    ("Rotate", "Rotate(figure, center, degrees)"),
+    ("Spline", "Spline(point, point, ...)"),
+    (
+        "Style",
+        "Style(object, color=#rrggbb, fill=#rrggbb|none, width=3, dash=solid|dashed|dotted|dashdot, marker=dot|circle|cross|square|diamond, size=3, opacity=1)",
+    ),
+    ("Tangent", "Tangent(circle, point)"),
+    ("Translate", "Translate(figure, from, to)"),
+];
+
+// one line of the construction language like "c = Circle(A, 5)" or "(1, 2)"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    name: Option<String>,
+    command: Option<String>, // None for a point like (1, 2)
+    arguments: Vec<String>,
+}
+
+// what a statement adds to the scene
+#[derive(Debug, Clone)]
+pub enum Output {
+    Free(Figure),
+    Dependent(Construction),
+}
+
+// an argument once the names are looked up
+#[derive(Debug, Clone, Copy)]
+enum Operand<'a> {
+    Object(usize, &'a Figure),
+    Point(Point), // written out, not an object yet
+    Number(f64),
+}
+
+impl Statement {
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    pub fn get_arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
+    // the new points some commands need as objects, which come first, and what the statement
+    // adds; lookup finds an object by its name, count is how many objects there are already,
+    // bounds is where intersections with curves without an end are looked for
+    pub fn build<'a>(
+        &self,
+        lookup: &dyn Fn(&str) -> Option<(usize, &'a Figure)>,
+        count: usize,
+        params: &HashMap<String, f64>,
+        bounds: (f64, f64, f64, f64),
+    ) -> Result<(Vec<Point>, Vec<Output>), String> {
+        let operands = self
+            .arguments
+            .iter()
+            .map(|a| get_operand(a, lookup, params))
+            .collect::<Result<Vec<_>, _>>()?;
+        let command = match &self.command {
+            Some(command) => command.as_str(),
+            None => match operands[..] {
+                [Operand::Number(x), Operand::Number(y)] => {
+                    return Ok((vec![], vec![Output::Free(Figure::Point(Point::new(x, y)))]))
+                }
+                _ => return Err(String::from("a point is written like (1, 2)")),
+            },
+        };
+        let mut args = Arguments {
+            command,
+            operands,
+            count,
+            points: vec![],
+            bounds,
+        };
+        let outputs = args.build()?;
+        Ok((args.points, outputs))
+    }
+}
+
+// "name = Command(arguments)", "Command(arguments)" or the same with a point like (1, 2);
+// None for everything else, which is left to the function plotter
+pub fn parse(input: &str) -> Result<Option<Statement>, String> {
+    // the "=" in "Style(c, width=2)" is inside the parentheses
+    let (name, value) = match input.split_once('=') {
+        Some((lhs, rhs)) if !lhs.contains('(') => (Some(lhs.trim()), rhs.trim()),
+        _ => (None, input.trim()),
+    };
+    let (command, inner) = match value.find('(') {
+        Some(open) if value.ends_with(')') => {
+            (value[..open].trim(), &value[open + 1..value.len() - 1])
+        }
+        _ => return Ok(None),
+    };
+    // functions like sin are written in lowercase, commands start with a capital
+    let command = if command.is_empty() {
+        // (cos(t), sin(t)) is a curve
+        let arguments = expr::split_arguments(inner);
+        let is_curve =
+            |a: &str| expr::parse(a).is_ok_and(|e| e.get_variables().iter().any(|v| v == "t"));
+        if arguments.len() != 2 || arguments.iter().any(|a| a.is_empty() || is_curve(a)) {
+            return Ok(None);
+        }
+        None
+    } else if command.starts_with(|c: char| c.is_uppercase()) && is_name(command) {
+        match COMMANDS
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(command))
+        {
+            Some((c, _)) => Some(String::from(*c)),
+            None => {
+                return Err(match get_closest_command(command) {
+                    Some(guess) => {
+                        format!("unknown command '{}', did you mean {}?", command, guess)
+                    }
+                    None => format!("unknown command '{}'", command),
+                })
+            }
+        }
+    } else {
+        return Ok(None);
+    };
+    if let Some(name) = name {
+        if !is_name(name) {
+            return Err(format!("'{}' can't be used as a name", name));
+        }
+    }
+    let arguments: Vec<String> = expr::split_arguments(inner)
+        .iter()
+        .map(|a| String::from(*a))
+        .collect();
+    if arguments.iter().any(|a| a.is_empty()) {
+        return Err(String::from("an argument is missing"));
+    }
+    Ok(Some(Statement {
+        name: name.map(String::from),
+        command,
+        arguments,
+    }))
+}
+
+// letters, digits and underscores, starting with a letter
+pub fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// the command a typo was most likely meant to be, a few letters off at most
+fn get_closest_command(typed: &str) -> Option<&'static str> {
+    let typed: Vec<char> = typed.to_lowercase().chars().collect();
+    COMMANDS
+        .iter()
+        .map(|(c, _)| {
+            (
+                get_edit_distance(&typed, &c.to_lowercase().chars().collect::<Vec<_>>()),
+                *c,
+            )
+        })
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+// how many letters have to be inserted, removed or replaced to get from a to b
+fn get_edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + (ca != cb) as usize)
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+// the commands, then the names, which start with what has been typed
+pub fn get_completions(prefix: &str, names: &[String]) -> Vec<String> {
+    if prefix.is_empty() {
+        return vec![];
+    }
+    let starts = |s: &str| s.to_lowercase().starts_with(&prefix.to_lowercase());
+    COMMANDS
+        .iter()
+        .filter(|(c, _)| starts(c))
+        .map(|(c, _)| format!("{}(", c))
+        .chain(names.iter().filter(|n| n.starts_with(prefix)).cloned())
+        .collect()
+}
+
+// the input with the word before the cursor completed as far as the candidates agree,
+// the new cursor and the candidates
+pub fn complete(input: &str, cursor: usize, names: &[String]) -> (String, usize, Vec<String>) {
+    let cursor = cursor.min(input.len());
+    let start = input[..cursor]
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| {
+            i + input[i..].chars().next().map_or(1, char::len_utf8)
+        });
+    let candidates = get_completions(&input[start..cursor], names);
+    let mut common = match candidates.first() {
+        Some(first) => first.clone(),
+        None => return (String::from(input), cursor, candidates),
+    };
+    for candidate in candidates.iter().skip(1) {
+        let shared = common
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| !a.eq_ignore_ascii_case(b))
+            .map_or(common.len().min(candidate.len()), |((i, _), _)| i);
+        common.truncate(shared);
+    }
+    // what has been typed stays when the candidates only agree on less
+    if common.len() < cursor - start {
+        return (String::from(input), cursor, candidates);
+    }
+    let completed = format!("{}{}{}", &input[..start], common, &input[cursor..]);
+    (completed, start + common.len(), candidates)
+}
+
+// what the innermost command the cursor is in takes
+pub fn get_hint(input: &str, cursor: usize) -> Option<&'static str> {
+    let cursor = cursor.min(input.len());
+    let mut depth = 0;
+    for (i, c) in input[..cursor].char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth > 0 => depth -= 1,
+            '(' => {
+                let before = input[..i].trim_end();
+                let start = before
+                    .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .map_or(0, |i| i + 1);
+                let command = &before[start..];
+                // the parentheses of a point or a function are looked past
+                if let Some((_, hint)) = COMMANDS
+                    .iter()
+                    .find(|(c, _)| c.eq_ignore_ascii_case(command))
+                {
+                    return Some(hint);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn get_operand<'a>(
+    argument: &str,
+    lookup: &dyn Fn(&str) -> Option<(usize, &'a Figure)>,
+    params: &HashMap<String, f64>,
+) -> Result<Operand<'a>, String> {
+    if let Some((i, figure)) = lookup(argument) {
+        return Ok(Operand::Object(i, figure));
+    }
+    let number = |source: &str| -> Result<f64, String> {
+        let value = expr::parse(source)?.eval(&|n| params.get(n).copied())?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(format!("'{}' isn't a number", source))
+        }
+    };
+    if let Some(inner) = argument.strip_prefix('(').and_then(|a| a.strip_suffix(')')) {
+        if let [x, y] = expr::split_arguments(inner)[..] {
+            return Ok(Operand::Point(Point::new(number(x)?, number(y)?)));
+        }
+    }
+    if argument.contains('(') && argument.starts_with(|c: char| c.is_uppercase()) {
+        return Err(format!("give {} a name first and use that", argument));
+    }
+    if is_name(argument) && !params.contains_key(argument) && expr::get_constant(argument).is_none()
+    {
+        return Err(format!("there is no object called '{}'", argument));
+    }
+    number(argument).map(Operand::Number)
+}
+
+// the arguments of one command, with the points that had to become objects
+struct Arguments<'a> {
+    command: &'a str,
+    operands: Vec<Operand<'a>>,
+    count: usize,
+    points: Vec<Point>,
+    bounds: (f64, f64, f64, f64),
+}
+
+impl Arguments<'_> {
+    fn build(&mut self) -> Result<Vec<Output>, String> {
+        let free = |figure: Figure| Ok(vec![Output::Free(figure)]);
+        match self.command {
+            "Line" => {
+                self.expect(2)?;
+                let (a, b) = self.distinct_points()?;
+                // in the order the line tool gets its clicks
+                free(Figure::Line(Line::new(b, a)))
+            }
+            "Rect" => {
+                self.expect(2)?;
+                let (a, b) = self.distinct_points()?;
+                free(Figure::Rectangle(Rectangle::new(b, a)))
+            }
+            "Circle" => {
+                self.expect(2)?;
+                let center = self.point(0)?;
+                let side = match self.operands[1] {
+                    Operand::Number(r) if r > 0.0 => Point::new(center.get_x() + r, center.get_y()),
+                    Operand::Number(_) => {
+                        return Err(String::from("the radius has to be positive"))
+                    }
+                    _ => self.point(1)?,
+                };
+                if get_distance(side, center) == 0.0 {
+                    return Err(String::from("the circle has no radius"));
+                }
+                free(Figure::Circle(Circle::new(center, side)))
+            }
+            "Arc" => {
+                self.expect(3)?;
+                let (a, b, c) = (self.point(0)?, self.point(1)?, self.point(2)?);
+                match Arc::from_three_points(a, b, c) {
+                    Some(arc) => free(Figure::Arc(arc)),
+                    None => Err(String::from("the points are on a line")),
+                }
+            }
+            "Bezier" => {
+                let points = self.points()?;
+                if points.len() < 3 {
+                    return Err(String::from("Bezier takes at least 3 points"));
+                }
+                free(Figure::Bezier(Bezier::new(points)))
+            }
+            "Spline" => {
+                let points = self.points()?;
+                if points.len() < 2 {
+                    return Err(String::from("Spline takes at least 2 points"));
+                }
+                free(Figure::Spline(Spline::new(points)))
+            }
+            "Conic" => {
+                self.expect(5)?;
+                let points = self.points()?;
+                match Conic::from_five_points([
+                    points[0], points[1], points[2], points[3], points[4],
+                ]) {
+                    Some(conic) => free(Figure::Conic(conic)),
+                    None => Err(String::from("no conic goes through these points")),
+                }
+            }
+            "Intersect" => self.intersect(),
+            _ => self.construct(),
+        }
+    }
+
+    fn construct(&mut self) -> Result<Vec<Output>, String> {
+        let dependent = |construction: Construction| Ok(vec![Output::Dependent(construction)]);
+        match self.command {
+            "Midpoint" | "PerpendicularBisector" => {
+                self.expect(2)?;
+                let (a, b) = (self.point_object(0)?, self.point_object(1)?);
+                if a == b {
+                    return Err(String::from("the points have to differ"));
+                }
+                if self.command == "Midpoint" {
+                    dependent(Construction::Midpoint(a, b))
+                } else {
+                    dependent(Construction::PerpendicularBisector(a, b))
+                }
+            }
+            "Parallel" | "Perpendicular" => {
+                self.expect(2)?;
+                let line = self.line(0)?;
+                let point = self.point_object(1)?;
+                if self.command == "Parallel" {
+                    dependent(Construction::Parallel { line, point })
+                } else {
+                    dependent(Construction::Perpendicular { line, point })
+                }
+            }
+            "Tangent" => {
+                self.expect(2)?;
+                let (circle, c) = match self.operands[0] {
+                    Operand::Object(j, Figure::Circle(c)) => (j, *c),
+                    _ => return Err(self.wrong(0, "a circle")),
+                };
+                let found = get_tangents_from_point(c, self.point(1)?).len();
+                if found == 0 {
+                    return Err(String::from("the point is inside the circle"));
+                }
+                let point = self.point_object(1)?;
+                Ok((0..found)
+                    .map(|index| {
+                        Output::Dependent(Construction::PointTangent {
+                            circle,
+                            point,
+                            index,
+                        })
+                    })
+                    .collect())
+            }
+            "AngleBisector" => {
+                self.expect(2)?;
+                let (first, second) = (self.line(0)?, self.line(1)?);
+                if first == second {
+                    return Err(String::from("the lines have to differ"));
+                }
+                Ok([false, true]
+                    .iter()
+                    .map(|&outer| {
+                        Output::Dependent(Construction::AngleBisector {
+                            first,
+                            second,
+                            outer,
+                        })
+                    })
+                    .collect())
+            }
+            "Reflect" => {
+                self.expect(2)?;
+                let figure = self.figure(0)?;
+                match self.operands[1] {
+                    Operand::Object(line, Figure::Line(_)) => {
+                        dependent(Construction::Reflection { figure, line })
+                    }
+                    _ => {
+                        let center = self.point_object(1)?;
+                        dependent(Construction::PointReflection { figure, center })
+                    }
+                }
+            }
+            "Rotate" => {
+                self.expect(3)?;
+                let (figure, center) = (self.figure(0)?, self.point_object(1)?);
+                let angle = self.number(2)?.to_radians();
+                dependent(Construction::Rotation {
+                    figure,
+                    center,
+                    angle,
+                })
+            }
+            "Dilate" => {
+                self.expect(3)?;
+                let (figure, center) = (self.figure(0)?, self.point_object(1)?);
+                let factor = self.number(2)?;
+                dependent(Construction::Dilation {
+                    figure,
+                    center,
+                    factor,
+                })
+            }
+            "Translate" => {
+                self.expect(3)?;
+                let figure = self.figure(0)?;
+                let (from, to) = (self.point_object(1)?, self.point_object(2)?);
+                dependent(Construction::Translation { figure, from, to })
+            }
+            "Invert" => {
+                self.expect(2)?;
+                let (figure, circle) = match (self.figure(0)?, self.operands[1]) {
+                    (figure, Operand::Object(circle, Figure::Circle(_))) => (figure, circle),
+                    _ => return Err(self.wrong(1, "a circle")),
+                };
+                if figure == circle {
+                    return Err(String::from("a circle can't be inverted in itself"));
+                }
+                dependent(Construction::Inversion { figure, circle })
+            }
+            command => Err(format!("unknown command '{}'", command)),
+        }
+    }
+
+    // the n-th intersection counting from 1, or all of them as they are now
+    fn intersect(&mut self) -> Result<Vec<Output>, String> {
+        if self.operands.len() != 2 && self.operands.len() != 3 {
+            return Err(String::from("Intersect takes 2 or 3 arguments"));
+        }
+        let (first, second) = match (self.operands[0], self.operands[1]) {
+            (Operand::Object(i, f), Operand::Object(j, g)) if i != j => ((i, f), (j, g)),
+            _ => return Err(String::from("Intersect takes two different objects")),
+        };
+        let found = get_intersections(first.1, second.1, self.bounds).len();
+        let indices = if self.operands.len() == 3 {
+            let n = self.number(2)?;
+            if n < 1.0 || n.fract() != 0.0 {
+                return Err(String::from("the intersections are counted from 1"));
+            }
+            vec![n as usize - 1]
+        } else {
+            (0..found).collect()
+        };
+        match indices.iter().max() {
+            None => Err(String::from("the objects don't intersect")),
+            Some(&index) if index >= found => {
+                Err(format!("the objects intersect in {} points", found))
+            }
+            Some(_) => Ok(indices
+                .into_iter()
+                .map(|index| {
+                    Output::Dependent(Construction::Intersection {
+                        first: first.0,
+                        second: second.0,
+                        index,
+                    })
+                })
+                .collect()),
+        }
+    }
+
+    fn expect(&self, n: usize) -> Result<(), String> {
+        if self.operands.len() == n {
+            Ok(())
+        } else {
+            Err(format!("{} takes {} arguments", self.command, n))
+        }
+    }
+
+    fn wrong(&self, i: usize, expected: &str) -> String {
+        format!(
+            "argument {} of {} has to be {}",
+            i + 1,
+            self.command,
+            expected
+        )
+    }
+
+    fn point(&self, i: usize) -> Result<Point, String> {
+        match self.operands[i] {
+            Operand::Object(_, Figure::Point(p)) => Ok(*p),
+            Operand::Point(p) => Ok(p),
+            _ => Err(self.wrong(i, "a point")),
+        }
+    }
+
+    fn points(&self) -> Result<Vec<Point>, String> {
+        (0..self.operands.len()).map(|i| self.point(i)).collect()
+    }
+
+    fn distinct_points(&self) -> Result<(Point, Point), String> {
+        let (a, b) = (self.point(0)?, self.point(1)?);
+        if get_distance(a, b) == 0.0 {
+            return Err(String::from("the points have to differ"));
+        }
+        Ok((a, b))
+    }
+
+    // a point the result depends on, one written out becomes an object like a click does
+    fn point_object(&mut self, i: usize) -> Result<usize, String> {
+        match self.operands[i] {
+            Operand::Object(j, Figure::Point(_)) => Ok(j),
+            Operand::Point(p) => {
+                self.points.push(p);
+                Ok(self.count + self.points.len() - 1)
+            }
+            _ => Err(self.wrong(i, "a point")),
+        }
+    }
+
+    fn line(&self, i: usize) -> Result<usize, String> {
+        match self.operands[i] {
+            Operand::Object(j, Figure::Line(_)) => Ok(j),
+            _ => Err(self.wrong(i, "a line")),
+        }
+    }
+
+    fn figure(&self, i: usize) -> Result<usize, String> {
+        match self.operands[i] {
+            Operand::Object(j, _) => Ok(j),
+            _ => Err(self.wrong(i, "an object")),
+        }
+    }
+
+    fn number(&self, i: usize) -> Result<f64, String> {
+        match self.operands[i] {
+            Operand::Number(n) => Ok(n),
+            _ => Err(self.wrong(i, "a number")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: (f64, f64, f64, f64) = (-10.0, -10.0, 10.0, 10.0);
+
+    fn statement(name: Option<&str>, command: Option<&str>, arguments: &[&str]) -> Statement {
+        Statement {
+            name: name.map(String::from),
+            command: command.map(String::from),
+            arguments: arguments.iter().map(|a| String::from(*a)).collect(),
+        }
+    }
+
+    #[test]
+    fn parse_statements() {
+        assert_eq!(
+            parse("c = Circle(A, 5)"),
+            Ok(Some(statement(Some("c"), Some("Circle"), &["A", "5"])))
+        );
+        assert_eq!(
+            parse("Style(c, width=2)"),
+            Ok(Some(statement(None, Some("Style"), &["c", "width=2"])))
+        );
+        assert_eq!(
+            parse("P = (1, 2 + 3)"),
+            Ok(Some(statement(Some("P"), None, &["1", "2 + 3"])))
+        );
+        // the case of a command doesn't matter past the capital
+        assert_eq!(
+            parse("MIDPOINT(A, B)"),
+            Ok(Some(statement(None, Some("Midpoint"), &["A", "B"])))
+        );
+    }
+
+    #[test]
+    fn parse_leaves_functions_and_curves() {
+        assert_eq!(parse("y = sin(x)"), Ok(None));
+        assert_eq!(parse("(cos(t), sin(t))"), Ok(None));
+        assert_eq!(parse("x^2 + 1"), Ok(None));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("Cirle(A, 5)"),
+            Err(String::from(
+                "unknown command 'Cirle', did you mean Circle?"
+            ))
+        );
+        assert_eq!(
+            parse("Frobnicate(A)"),
+            Err(String::from("unknown command 'Frobnicate'"))
+        );
+        assert_eq!(
+            parse("Line(A, )"),
+            Err(String::from("an argument is missing"))
+        );
+        assert_eq!(
+            parse("2a = Line(A, B)"),
+            Err(String::from("'2a' can't be used as a name"))
+        );
+    }
+
+    #[test]
+    fn edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(get_edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(get_edit_distance(&chars(""), &chars("line")), 4);
+        assert_eq!(get_edit_distance(&chars("line"), &chars("line")), 0);
+        assert_eq!(get_closest_command("Rotat"), Some("Rotate"));
+        assert_eq!(get_closest_command("Paralel"), Some("Parallel"));
+        assert_eq!(get_closest_command("Polygon"), None);
+    }
+
+    #[test]
+    fn completion() {
+        let names = vec![String::from("Cx"), String::from("c1")];
+        assert_eq!(
+            complete("c = Cir", 7, &names),
+            (
+                String::from("c = Circle("),
+                11,
+                vec![String::from("Circle(")]
+            )
+        );
+        // Perpendicular( and PerpendicularBisector( agree on less than the first
+        let (completed, cursor, candidates) = complete("Perp", 4, &names);
+        assert_eq!((completed.as_str(), cursor), ("Perpendicular", 13));
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(get_completions("C", &names)[..2], ["Circle(", "Conic("]);
+        assert!(get_completions("C", &names).contains(&String::from("Cx")));
+        assert_eq!(get_hint("Circle(A, ", 10), Some(COMMANDS[3].1));
+        assert_eq!(get_hint("Line((1, 2), ", 13), Some(COMMANDS[8].1));
+        assert_eq!(get_hint("sin(", 4), None);
+    }
+
+    #[test]
+    fn build_free_figures() {
+        let lookup = |_: &str| None;
+        let params = HashMap::new();
+        let (points, outputs) = parse("Circle((1, 1), 2)")
+            .unwrap()
+            .unwrap()
+            .build(&lookup, 0, &params, BOUNDS)
+            .unwrap();
+        assert!(points.is_empty());
+        match &outputs[..] {
+            [Output::Free(Figure::Circle(c))] => assert_eq!(c.get_rad(), 2.0),
+            _ => panic!("expected a circle, got {:?}", outputs),
+        }
+        let build = |input: &str| {
+            parse(input)
+                .unwrap()
+                .unwrap()
+                .build(&lookup, 0, &params, BOUNDS)
+                .map(|_| ())
+        };
+        assert_eq!(
+            build("Circle((1, 1), -2)"),
+            Err(String::from("the radius has to be positive"))
+        );
+        assert_eq!(
+            build("Line((1, 1))"),
+            Err(String::from("Line takes 2 arguments"))
+        );
+        assert_eq!(
+            build("Line((1, 1), (1, 1))"),
+            Err(String::from("the points have to differ"))
+        );
+        assert_eq!(
+            build("Line(A, (1, 1))"),
+            Err(String::from("there is no object called 'A'"))
+        );
+    }
+
+    #[test]
+    fn build_dependent_figures() {
+        let figures = [
+            Figure::Point(Point::new(0.0, 0.0)),
+            Figure::Line(Line::new(Point::new(-1.0, 1.0), Point::new(1.0, 1.0))),
+            Figure::Line(Line::new(Point::new(1.0, -1.0), Point::new(1.0, 1.0))),
+            Figure::Circle(Circle::new(Point::new(3.0, 3.0), Point::new(3.0, 4.0))),
+        ];
+        let lookup = |name: &str| match name {
+            "A" => Some((0, &figures[0])),
+            "l" => Some((1, &figures[1])),
+            "m" => Some((2, &figures[2])),
+            "c" => Some((3, &figures[3])),
+            _ => None,
+        };
+        let params = HashMap::new();
+        let build = |input: &str| {
+            parse(input)
+                .unwrap()
+                .unwrap()
+                .build(&lookup, figures.len(), &params, BOUNDS)
+        };
+        // a point written out becomes an object after the ones there are
+        let (points, outputs) = build("Midpoint(A, (2, 4))").unwrap();
+        assert_eq!(points.len(), 1);
+        assert!(matches!(
+            outputs[..],
+            [Output::Dependent(Construction::Midpoint(0, 4))]
+        ));
+        let (_, outputs) = build("Intersect(l, m)").unwrap();
+        assert!(matches!(
+            outputs[..],
+            [Output::Dependent(Construction::Intersection {
+                first: 1,
+                second: 2,
+                index: 0
+            })]
+        ));
+        assert!(build("Intersect(l, m, 2)").is_err());
+        let (_, outputs) = build("Tangent(c, A)").unwrap();
+        assert!(matches!(
+            outputs[..],
+            [
+                Output::Dependent(Construction::PointTangent {
+                    circle: 3,
+                    point: 0,
+                    index: 0
+                }),
+                Output::Dependent(Construction::PointTangent { index: 1, .. })
+            ]
+        ));
+        assert_eq!(
+            build("Tangent(c, (3, 3.5))").map(|_| ()),
+            Err(String::from("the point is inside the circle"))
+        );
+        assert_eq!(
+            build("Parallel(A, A)").map(|_| ()),
+            Err(String::from("argument 1 of Parallel has to be a line"))
+        );
+    }
+}
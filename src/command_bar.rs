@@ -0,0 +1,174 @@
+#![allow(dead_code)]
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use fltk::app;
+use fltk::dialog::alert_default;
+use fltk::enums::{Align, CallbackTrigger, Event, Key};
+use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button::Button, frame::Frame, group::Group, input::Input};
+
+use crate::canvas::Canvas;
+use crate::command;
+
+const LABEL_WIDTH: i32 = 60;
+const STEP_WIDTH: i32 = 60;
+
+// a text input for the construction language, with what the command being typed takes
+// next to it, completion on tab and the earlier statements on up and down; Step runs
+// the next statement of a script loaded to go through step by step
+#[derive(Clone)]
+pub struct CommandBar {
+    input: Input,
+    hint: Frame,
+    step: Button,
+    canvas: Canvas,
+    history: Rc<RefCell<Vec<String>>>,
+    recalled: Rc<RefCell<Option<usize>>>, // where up and down are in the history
+}
+
+impl CommandBar {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let group = Group::new(x, y, w, h, None);
+        let mut input = Input::new(x + LABEL_WIDTH, y, w / 2 - LABEL_WIDTH, h, "Input");
+        input.set_trigger(CallbackTrigger::EnterKeyAlways);
+        input.set_tooltip("A = (1, 2), c = Circle(A, 5), P = Intersect(l, c, 1), y = x^2, ...");
+        let mut hint = Frame::new(x + w / 2 + 5, y, w / 2 - STEP_WIDTH - 5, h, None);
+        hint.set_align(Align::Left | Align::Inside);
+        let mut step = Button::new(x + w - STEP_WIDTH, y, STEP_WIDTH, h, "Step");
+        step.deactivate();
+        group.end();
+
+        let mut bar = CommandBar {
+            input,
+            hint,
+            step,
+            canvas,
+            history: Rc::new(RefCell::new(vec![])),
+            recalled: Rc::new(RefCell::new(None)),
+        };
+
+        bar.input.set_callback({
+            let mut bar = bar.clone();
+            move |input| bar.run(&input.value())
+        });
+        bar.step.set_callback({
+            let mut bar = bar.clone();
+            move |_| {
+                if let Err(e) = bar.canvas.step_script(1) {
+                    alert_default(&e);
+                }
+            }
+        });
+        bar.canvas.add_on_change({
+            let mut bar = bar.clone();
+            move || bar.show_step()
+        });
+        bar.input.handle({
+            let mut bar = bar.clone();
+            move |_, ev| match ev {
+                Event::KeyDown => match app::event_key() {
+                    Key::Tab => {
+                        bar.complete();
+                        true
+                    }
+                    Key::Up => {
+                        bar.recall(true);
+                        true
+                    }
+                    Key::Down => {
+                        bar.recall(false);
+                        true
+                    }
+                    _ => false,
+                },
+                // the hint follows the cursor, which has moved once the key is handled
+                Event::KeyUp if app::event_key() == Key::Tab => true,
+                Event::KeyUp | Event::Released => {
+                    bar.show_hint();
+                    false
+                }
+                _ => false,
+            }
+        });
+        bar
+    }
+
+    fn run(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        match self.canvas.execute(text) {
+            Ok(()) => {
+                {
+                    let mut history = self.history.borrow_mut();
+                    if history.last().map(String::as_str) != Some(text) {
+                        history.push(String::from(text));
+                    }
+                }
+                *self.recalled.borrow_mut() = None;
+                self.input.set_value("");
+                self.show_step();
+            }
+            Err(e) => alert_default(&e),
+        }
+    }
+
+    fn complete(&mut self) {
+        let value = self.input.value();
+        let cursor = self.input.position().max(0) as usize;
+        let (completed, cursor, candidates) =
+            command::complete(&value, cursor, &self.canvas.get_names());
+        self.input.set_value(&completed);
+        self.input.set_position(cursor as i32).ok();
+        if candidates.len() > 1 {
+            self.hint.set_label(&candidates.join("  "));
+        } else {
+            self.show_hint();
+        }
+    }
+
+    // older statements with up, newer ones with down and an empty input after the newest
+    fn recall(&mut self, older: bool) {
+        let history = self.history.borrow_mut();
+        let mut recalled = self.recalled.borrow_mut();
+        *recalled = match (*recalled, older) {
+            (None, true) => history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) => Some(i + 1).filter(|i| *i < history.len()),
+        };
+        let text = recalled
+            .and_then(|i| history.get(i))
+            .map_or("", String::as_str);
+        self.input.set_value(text);
+        self.input.set_position(text.len() as i32).ok();
+    }
+
+    fn show_hint(&mut self) {
+        let cursor = self.input.position().max(0) as usize;
+        match command::get_hint(&self.input.value(), cursor) {
+            Some(hint) => self.hint.set_label(hint),
+            None => self.show_step(),
+        }
+    }
+
+    // the statement Step runs, while nothing is being typed
+    fn show_step(&mut self) {
+        let next = self.canvas.get_next_step();
+        if next.is_some() {
+            self.step.activate();
+        } else {
+            self.step.deactivate();
+        }
+        if self.input.value().is_empty() {
+            match next {
+                Some((line, statement)) => self
+                    .hint
+                    .set_label(&format!("next, line {}: {}", line, statement)),
+                None => self.hint.set_label(""),
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "geoviz";
+
+// where the settings are kept: $XDG_CONFIG_HOME or ~/.config on Linux, ~/Library/Application
+// Support on macOS and %APPDATA% on Windows, each with a directory of its own
+pub fn get_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        }
+    };
+    Some(base.join(APP_DIR))
+}
+
+// the "name = value" lines of a file in the config directory, nothing if it isn't there yet
+pub fn read(file: &str) -> Vec<(String, String)> {
+    match get_dir().and_then(|dir| fs::read_to_string(dir.join(file)).ok()) {
+        Some(text) => parse(&text),
+        None => vec![],
+    }
+}
+
+pub fn write(file: &str, heading: &str, entries: &[(String, String)]) -> Result<(), String> {
+    let dir = get_dir().ok_or("there is no config directory")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("can't create {}: {}", dir.display(), e))?;
+    let path = dir.join(file);
+    fs::write(&path, format(heading, entries))
+        .map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
+
+// lines starting with # are comments, the name ends at the first =
+pub fn parse(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+pub fn format(heading: &str, entries: &[(String, String)]) -> String {
+    let mut text = format!("# {}\n", heading);
+    for (name, value) in entries {
+        text.push_str(&format!("{} = {}\n", name, value));
+    }
+    text
+}
@@ -0,0 +1,560 @@
+#![allow(dead_code)]
+use crate::figures::{point::Point, Figure};
+use crate::transform::Transformation;
+use crate::utils::{
+    get_angle_bisectors, get_common_tangents, get_intersections, get_midpoint, get_parallel_line,
+    get_perpendicular_bisector, get_perpendicular_line, get_tangent_at_point,
+    get_tangents_from_point,
+};
+
+// how a dependent figure follows from others, which are referred to by their index in the scene;
+// a parent always comes before the figures that depend on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Construction {
+    Parallel {
+        line: usize,
+        point: usize,
+    },
+    Perpendicular {
+        line: usize,
+        point: usize,
+    },
+    PerpendicularBisector(usize, usize),
+    AngleBisector {
+        first: usize,
+        second: usize,
+        outer: bool,
+    }, // two of them for crossing lines
+    Midpoint(usize, usize),
+    // touching the circle in the direction angle from its center
+    Tangent {
+        circle: usize,
+        angle: f64,
+    },
+    // the tangents in the order utils finds them, like the intersections
+    PointTangent {
+        circle: usize,
+        point: usize,
+        index: usize,
+    },
+    CommonTangent {
+        first: usize,
+        second: usize,
+        index: usize,
+    },
+    // images of a figure, they follow the figure and whatever defines the transformation
+    Reflection {
+        figure: usize,
+        line: usize,
+    },
+    PointReflection {
+        figure: usize,
+        center: usize,
+    },
+    Rotation {
+        figure: usize,
+        center: usize,
+        angle: f64,
+    },
+    Dilation {
+        figure: usize,
+        center: usize,
+        factor: f64,
+    },
+    Translation {
+        figure: usize,
+        from: usize,
+        to: usize,
+    },
+    Inversion {
+        figure: usize,
+        circle: usize,
+    },
+    // the intersections in the order get_intersections finds them
+    Intersection {
+        first: usize,
+        second: usize,
+        index: usize,
+    },
+}
+
+impl Construction {
+    pub fn get_parents(&self) -> Vec<usize> {
+        match *self {
+            Construction::Parallel { line, point }
+            | Construction::Perpendicular { line, point } => {
+                vec![line, point]
+            }
+            Construction::AngleBisector { first, second, .. } => vec![first, second],
+            Construction::PerpendicularBisector(a, b) | Construction::Midpoint(a, b) => vec![a, b],
+            Construction::Tangent { circle, .. } => vec![circle],
+            Construction::PointTangent { circle, point, .. } => vec![circle, point],
+            Construction::CommonTangent { first, second, .. } => vec![first, second],
+            Construction::Reflection { figure, line } => vec![figure, line],
+            Construction::PointReflection { figure, center }
+            | Construction::Rotation { figure, center, .. }
+            | Construction::Dilation { figure, center, .. } => vec![figure, center],
+            Construction::Translation { figure, from, to } => vec![figure, from, to],
+            Construction::Inversion { figure, circle } => vec![figure, circle],
+            Construction::Intersection { first, second, .. } => vec![first, second],
+        }
+    }
+
+    // the same construction with the parents renumbered, after figures before them were deleted
+    pub fn remap(&self, map: impl Fn(usize) -> usize) -> Construction {
+        match *self {
+            Construction::Parallel { line, point } => Construction::Parallel {
+                line: map(line),
+                point: map(point),
+            },
+            Construction::Perpendicular { line, point } => Construction::Perpendicular {
+                line: map(line),
+                point: map(point),
+            },
+            Construction::PerpendicularBisector(a, b) => {
+                Construction::PerpendicularBisector(map(a), map(b))
+            }
+            Construction::AngleBisector {
+                first,
+                second,
+                outer,
+            } => Construction::AngleBisector {
+                first: map(first),
+                second: map(second),
+                outer,
+            },
+            Construction::Midpoint(a, b) => Construction::Midpoint(map(a), map(b)),
+            Construction::Tangent { circle, angle } => Construction::Tangent {
+                circle: map(circle),
+                angle,
+            },
+            Construction::PointTangent {
+                circle,
+                point,
+                index,
+            } => Construction::PointTangent {
+                circle: map(circle),
+                point: map(point),
+                index,
+            },
+            Construction::CommonTangent {
+                first,
+                second,
+                index,
+            } => Construction::CommonTangent {
+                first: map(first),
+                second: map(second),
+                index,
+            },
+            Construction::Reflection { figure, line } => Construction::Reflection {
+                figure: map(figure),
+                line: map(line),
+            },
+            Construction::PointReflection { figure, center } => Construction::PointReflection {
+                figure: map(figure),
+                center: map(center),
+            },
+            Construction::Rotation {
+                figure,
+                center,
+                angle,
+            } => Construction::Rotation {
+                figure: map(figure),
+                center: map(center),
+                angle,
+            },
+            Construction::Dilation {
+                figure,
+                center,
+                factor,
+            } => Construction::Dilation {
+                figure: map(figure),
+                center: map(center),
+                factor,
+            },
+            Construction::Translation { figure, from, to } => Construction::Translation {
+                figure: map(figure),
+                from: map(from),
+                to: map(to),
+            },
+            Construction::Inversion { figure, circle } => Construction::Inversion {
+                figure: map(figure),
+                circle: map(circle),
+            },
+            Construction::Intersection {
+                first,
+                second,
+                index,
+            } => Construction::Intersection {
+                first: map(first),
+                second: map(second),
+                index,
+            },
+        }
+    }
+
+    // the figure for the current state of the parents, None where it isn't defined;
+    // intersections with curves without an end are looked for within bounds
+    pub fn apply<'a>(
+        &self,
+        get: impl Fn(usize) -> Option<&'a Figure>,
+        bounds: (f64, f64, f64, f64),
+    ) -> Option<Figure> {
+        let point = |i: usize| match get(i) {
+            Some(Figure::Point(p)) => Some(*p),
+            _ => None,
+        };
+        let line = |i: usize| match get(i) {
+            Some(Figure::Line(l)) => Some(*l),
+            _ => None,
+        };
+        let circle = |i: usize| match get(i) {
+            Some(Figure::Circle(c)) => Some(*c),
+            _ => None,
+        };
+        match *self {
+            Construction::Parallel { line: l, point: p } => {
+                Some(Figure::Line(get_parallel_line(line(l)?, point(p)?)))
+            }
+            Construction::Perpendicular { line: l, point: p } => {
+                Some(Figure::Line(get_perpendicular_line(line(l)?, point(p)?)))
+            }
+            Construction::PerpendicularBisector(a, b) => {
+                get_perpendicular_bisector(point(a)?, point(b)?).map(Figure::Line)
+            }
+            Construction::AngleBisector {
+                first,
+                second,
+                outer,
+            } => get_angle_bisectors(line(first)?, line(second)?)
+                .get(outer as usize)
+                .map(|l| Figure::Line(*l)),
+            Construction::Midpoint(a, b) => Some(Figure::Point(get_midpoint(point(a)?, point(b)?))),
+            Construction::Tangent { circle: c, angle } => {
+                let c = circle(c)?;
+                let direction = Point::new(
+                    c.get_center().get_x() + angle.cos(),
+                    c.get_center().get_y() + angle.sin(),
+                );
+                get_tangent_at_point(c, direction).map(Figure::Line)
+            }
+            Construction::PointTangent {
+                circle: c,
+                point: p,
+                index,
+            } => get_tangents_from_point(circle(c)?, point(p)?)
+                .get(index)
+                .map(|l| Figure::Line(*l)),
+            Construction::CommonTangent {
+                first,
+                second,
+                index,
+            } => get_common_tangents(circle(first)?, circle(second)?)
+                .get(index)
+                .map(|l| Figure::Line(*l)),
+            Construction::Reflection { .. }
+            | Construction::PointReflection { .. }
+            | Construction::Rotation { .. }
+            | Construction::Dilation { .. }
+            | Construction::Translation { .. }
+            | Construction::Inversion { .. } => {
+                let (transformation, figure) = self.get_transformation(&get)?;
+                transformation.apply(get(figure)?)
+            }
+            Construction::Intersection {
+                first,
+                second,
+                index,
+            } => get_intersections(get(first)?, get(second)?, bounds)
+                .get(index)
+                .map(|p| Figure::Point(*p)),
+        }
+    }
+
+    // why an image can't be made, for the transformations that never have one of the figure
+    pub fn get_refusal<'a>(
+        &self,
+        get: impl Fn(usize) -> Option<&'a Figure>,
+    ) -> Option<&'static str> {
+        let (transformation, figure) = self.get_transformation(&get)?;
+        transformation.get_refusal(get(figure)?)
+    }
+
+    // the transformation of an image with the figure it is applied to
+    fn get_transformation<'a>(
+        &self,
+        get: &dyn Fn(usize) -> Option<&'a Figure>,
+    ) -> Option<(Transformation, usize)> {
+        let point = |i: usize| match get(i) {
+            Some(Figure::Point(p)) => Some(*p),
+            _ => None,
+        };
+        Some(match *self {
+            Construction::Reflection { figure, line } => match get(line)? {
+                Figure::Line(l) => (Transformation::Reflection(*l), figure),
+                _ => return None,
+            },
+            Construction::PointReflection { figure, center } => {
+                (Transformation::PointReflection(point(center)?), figure)
+            }
+            Construction::Rotation {
+                figure,
+                center,
+                angle,
+            } => (Transformation::Rotation(point(center)?, angle), figure),
+            Construction::Dilation {
+                figure,
+                center,
+                factor,
+            } => (Transformation::Dilation(point(center)?, factor), figure),
+            Construction::Translation { figure, from, to } => {
+                let (from, to) = (point(from)?, point(to)?);
+                (
+                    Transformation::Translation(
+                        to.get_x() - from.get_x(),
+                        to.get_y() - from.get_y(),
+                    ),
+                    figure,
+                )
+            }
+            Construction::Inversion { figure, circle } => match get(circle)? {
+                Figure::Circle(c) => (Transformation::Inversion(*c), figure),
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figures::{circle::Circle, line::Line, rectangle::Rectangle};
+    use crate::utils::{get_distance, get_line_distance};
+
+    const BOUNDS: (f64, f64, f64, f64) = (-50.0, -50.0, 50.0, 50.0);
+
+    fn scene() -> Vec<Figure> {
+        vec![
+            Figure::Line(Line::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0))),
+            Figure::Point(Point::new(1.0, 3.0)),
+            Figure::Point(Point::new(5.0, 3.0)),
+            Figure::Line(Line::new(Point::new(0.0, 0.0), Point::new(0.0, 1.0))),
+            Figure::Circle(Circle::new(Point::new(10.0, 0.0), Point::new(12.0, 0.0))),
+            Figure::Circle(Circle::new(Point::new(20.0, 0.0), Point::new(23.0, 0.0))),
+            Figure::Point(Point::new(10.0, 5.0)),
+        ]
+    }
+
+    fn apply(construction: Construction, figures: &[Figure]) -> Option<Figure> {
+        construction.apply(|i| figures.get(i), BOUNDS)
+    }
+
+    fn line(construction: Construction, figures: &[Figure]) -> Line {
+        match apply(construction, figures) {
+            Some(Figure::Line(l)) => l,
+            other => panic!("expected a line from {:?}, got {:?}", construction, other),
+        }
+    }
+
+    fn circle(figures: &[Figure], i: usize) -> Circle {
+        match figures[i] {
+            Figure::Circle(c) => c,
+            _ => panic!("figure {} is no circle", i),
+        }
+    }
+
+    #[test]
+    fn lines_and_points() {
+        let figures = scene();
+        let through = Point::new(1.0, 3.0);
+        let l = line(Construction::Parallel { line: 0, point: 1 }, &figures);
+        assert!(get_line_distance(through, l) < 1e-12 && (l.get_slope() - 0.5).abs() < 1e-12);
+        let l = line(Construction::Perpendicular { line: 0, point: 1 }, &figures);
+        assert!(get_line_distance(through, l) < 1e-12 && (l.get_slope() + 2.0).abs() < 1e-12);
+        let l = line(Construction::PerpendicularBisector(1, 2), &figures);
+        assert!(get_line_distance(Point::new(3.0, -40.0), l) < 1e-12);
+        assert!(get_line_distance(Point::new(3.0, 40.0), l) < 1e-12);
+        for outer in [false, true] {
+            let l = line(
+                Construction::AngleBisector {
+                    first: 0,
+                    second: 3,
+                    outer,
+                },
+                &figures,
+            );
+            // every point on it is as far from one line as from the other
+            let slanted = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+            let upright = Line::new(Point::new(0.0, 0.0), Point::new(0.0, 1.0));
+            for q in [l.get_point_a(), l.get_point_b()] {
+                let difference = get_line_distance(q, slanted) - get_line_distance(q, upright);
+                assert!(difference.abs() < 1e-9);
+            }
+        }
+        match apply(Construction::Midpoint(1, 2), &figures) {
+            Some(Figure::Point(p)) => assert!(get_distance(p, Point::new(3.0, 3.0)) < 1e-12),
+            other => panic!("expected a point, got {:?}", other),
+        }
+
+        // parents which are missing or of the wrong kind give nothing
+        assert!(apply(Construction::Parallel { line: 1, point: 1 }, &figures).is_none());
+        assert!(apply(Construction::Midpoint(1, 9), &figures).is_none());
+    }
+
+    #[test]
+    fn images() {
+        let mut figures = scene();
+        figures.push(Figure::Rectangle(Rectangle::new(
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 1.0),
+        )));
+        let image = |construction: Construction| match apply(construction, &figures) {
+            Some(Figure::Point(p)) => (p.get_x(), p.get_y()),
+            other => panic!("expected a point from {:?}, got {:?}", construction, other),
+        };
+        let close = |(x, y): (f64, f64), (a, b): (f64, f64)| (x - a).abs() + (y - b).abs() < 1e-9;
+
+        assert!(close(
+            image(Construction::Reflection { figure: 1, line: 3 }),
+            (-1.0, 3.0)
+        ));
+        assert!(close(
+            image(Construction::PointReflection {
+                figure: 1,
+                center: 2
+            }),
+            (9.0, 3.0)
+        ));
+        assert!(close(
+            image(Construction::Rotation {
+                figure: 1,
+                center: 2,
+                angle: std::f64::consts::FRAC_PI_2
+            }),
+            (5.0, -1.0)
+        ));
+        assert!(close(
+            image(Construction::Dilation {
+                figure: 1,
+                center: 2,
+                factor: 2.0
+            }),
+            (-3.0, 3.0)
+        ));
+        assert!(close(
+            image(Construction::Translation {
+                figure: 6,
+                from: 1,
+                to: 2
+            }),
+            (14.0, 5.0)
+        ));
+        assert!(close(
+            image(Construction::Inversion {
+                figure: 6,
+                circle: 4
+            }),
+            (10.0, 0.8)
+        ));
+
+        // a rectangle turned would need sides off the axes
+        let turned = Construction::Rotation {
+            figure: 7,
+            center: 2,
+            angle: 1.0,
+        };
+        assert!(apply(turned, &figures).is_none());
+        assert_eq!(
+            turned.get_refusal(|i| figures.get(i)),
+            Some("rectangles can only be translated, dilated or reflected in a point")
+        );
+        let moved = Construction::Translation {
+            figure: 7,
+            from: 1,
+            to: 2,
+        };
+        match apply(moved, &figures) {
+            Some(Figure::Rectangle(r)) => {
+                assert!(get_distance(r.get_point_b(), Point::new(6.0, 1.0)) < 1e-12)
+            }
+            other => panic!("expected a rectangle, got {:?}", other),
+        }
+        assert_eq!(moved.get_refusal(|i| figures.get(i)), None);
+    }
+
+    #[test]
+    fn intersections() {
+        let figures = scene();
+        let crossing = |index: usize| Construction::Intersection {
+            first: 0,
+            second: 3,
+            index,
+        };
+        match apply(crossing(0), &figures) {
+            Some(Figure::Point(p)) => assert!(get_distance(p, Point::new(0.0, 0.0)) < 1e-12),
+            other => panic!("expected a point, got {:?}", other),
+        }
+        assert!(apply(crossing(1), &figures).is_none());
+        // the circles don't meet
+        let apart = Construction::Intersection {
+            first: 4,
+            second: 5,
+            index: 0,
+        };
+        assert!(apply(apart, &figures).is_none());
+    }
+
+    #[test]
+    fn tangents() {
+        let mut figures = scene();
+        let (c1, c2) = (circle(&figures, 4), circle(&figures, 5));
+        let touches =
+            |l: Line, c: Circle| (get_line_distance(c.get_center(), l) - c.get_rad()).abs() < 1e-9;
+
+        let at = Construction::Tangent {
+            circle: 4,
+            angle: std::f64::consts::FRAC_PI_2,
+        };
+        let l = line(at, &figures);
+        assert!(touches(l, c1) && get_line_distance(Point::new(10.0, 2.0), l) < 1e-9);
+
+        for index in 0..2 {
+            let from = Construction::PointTangent {
+                circle: 4,
+                point: 6,
+                index,
+            };
+            let l = line(from, &figures);
+            assert!(touches(l, c1) && get_line_distance(Point::new(10.0, 5.0), l) < 1e-9);
+        }
+        let third = Construction::PointTangent {
+            circle: 4,
+            point: 6,
+            index: 2,
+        };
+        assert!(apply(third, &figures).is_none());
+
+        for index in 0..4 {
+            let common = Construction::CommonTangent {
+                first: 4,
+                second: 5,
+                index,
+            };
+            let l = line(common, &figures);
+            assert!(touches(l, c1) && touches(l, c2));
+        }
+
+        // they follow the circle they depend on
+        figures[4] = Figure::Circle(Circle::new(Point::new(0.0, 20.0), Point::new(0.0, 21.0)));
+        let moved = circle(&figures, 4);
+        let l = line(at, &figures);
+        assert!(touches(l, moved) && get_line_distance(Point::new(0.0, 21.0), l) < 1e-9);
+        let common = Construction::CommonTangent {
+            first: 4,
+            second: 5,
+            index: 3,
+        };
+        assert!(touches(line(common, &figures), moved));
+    }
+}
@@ -0,0 +1,501 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+use std::f64;
+
+// a parsed arithmetic expression like "a * sin(2x) + 1"
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+// name and number of arguments
+const FUNCTIONS: &[(&str, usize)] = &[
+    ("sin", 1),
+    ("cos", 1),
+    ("tan", 1),
+    ("asin", 1),
+    ("acos", 1),
+    ("atan", 1),
+    ("atan2", 2),
+    ("sinh", 1),
+    ("cosh", 1),
+    ("tanh", 1),
+    ("exp", 1),
+    ("ln", 1),
+    ("log", 1),
+    ("sqrt", 1),
+    ("cbrt", 1),
+    ("abs", 1),
+    ("sign", 1),
+    ("floor", 1),
+    ("ceil", 1),
+    ("round", 1),
+    ("min", 2),
+    ("max", 2),
+    ("pow", 2),
+];
+
+pub fn get_constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" => Some(f64::consts::PI),
+        "e" => Some(f64::consts::E),
+        "tau" => Some(f64::consts::TAU),
+        _ => None,
+    }
+}
+
+pub fn parse(input: &str) -> Result<Expr, String> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(String::from("empty expression"));
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("unexpected '{}'", t)),
+    }
+}
+
+// splits "sin(t), cos(t)" at the commas which aren't inside parentheses or brackets
+pub fn split_arguments(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(input[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(input[start..].trim());
+    parts
+}
+
+// takes a trailing range like "[0, 2pi]" off the input
+pub fn split_range(input: &str) -> (&str, Option<&str>) {
+    match input.strip_suffix(']').and_then(|i| i.rsplit_once('[')) {
+        Some((rest, range)) => (rest.trim(), Some(range)),
+        None => (input, None),
+    }
+}
+
+pub fn parse_range(range: &str, params: &HashMap<String, f64>) -> Result<(f64, f64), String> {
+    let bounds = split_arguments(range);
+    if bounds.len() != 2 {
+        return Err(String::from(
+            "a range needs a start and an end like [0, 2pi]",
+        ));
+    }
+    let lookup = |n: &str| params.get(n).copied();
+    let a = parse(bounds[0])?.eval(&lookup)?;
+    let b = parse(bounds[1])?.eval(&lookup)?;
+    if a.is_nan() || b.is_nan() || a >= b {
+        return Err(String::from("the range has to start before it ends"));
+    }
+    Ok((a, b))
+}
+
+impl Expr {
+    // lookup resolves the variables, constants are known without it
+    pub fn eval(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Result<f64, String> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Var(name) => lookup(name)
+                .or_else(|| get_constant(name))
+                .ok_or(format!("unknown variable '{}'", name)),
+            Expr::Neg(e) => Ok(-e.eval(lookup)?),
+            Expr::Binary(op, a, b) => {
+                let a = a.eval(lookup)?;
+                let b = b.eval(lookup)?;
+                Ok(match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Pow => a.powf(b),
+                })
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| a.eval(lookup))
+                    .collect::<Result<Vec<f64>, String>>()?;
+                call(name, &args)
+            }
+        }
+    }
+
+    // the variables the expression needs, without the constants
+    pub fn get_variables(&self) -> Vec<String> {
+        let mut vars = Vec::new();
+        self.collect_variables(&mut vars);
+        vars
+    }
+
+    fn collect_variables(&self, vars: &mut Vec<String>) {
+        match self {
+            Expr::Var(name) => {
+                if get_constant(name).is_none() && !vars.contains(name) {
+                    vars.push(name.clone());
+                }
+            }
+            Expr::Neg(e) => e.collect_variables(vars),
+            Expr::Binary(_, a, b) => {
+                a.collect_variables(vars);
+                b.collect_variables(vars);
+            }
+            Expr::Call(_, args) => {
+                for a in args {
+                    a.collect_variables(vars);
+                }
+            }
+            Expr::Number(_) => {}
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let x = args[0];
+    Ok(match name {
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan(),
+        "asin" => x.asin(),
+        "acos" => x.acos(),
+        "atan" => x.atan(),
+        "atan2" => x.atan2(args[1]),
+        "sinh" => x.sinh(),
+        "cosh" => x.cosh(),
+        "tanh" => x.tanh(),
+        "exp" => x.exp(),
+        "ln" => x.ln(),
+        "log" => x.log10(),
+        "sqrt" => x.sqrt(),
+        "cbrt" => x.cbrt(),
+        "abs" => x.abs(),
+        "sign" => {
+            if x == 0.0 {
+                0.0
+            } else {
+                x.signum()
+            }
+        }
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        "round" => x.round(),
+        "min" => x.min(args[1]),
+        "max" => x.max(args[1]),
+        "pow" => x.powf(args[1]),
+        _ => return Err(format!("unknown function '{}'", name)),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Op(c) => write!(f, "{}", c),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // exponent like 1e-3, but not the constant e in "2e"
+            if i + 1 < chars.len()
+                && (chars[i] == 'e' || chars[i] == 'E')
+                && (chars[i + 1].is_ascii_digit()
+                    || ((chars[i + 1] == '-' || chars[i + 1] == '+')
+                        && i + 2 < chars.len()
+                        && chars[i + 2].is_ascii_digit()))
+            {
+                i += 2;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", text))?;
+            tokens.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '*' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            tokens.push(Token::Op('^'));
+            i += 2;
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character '{}'", c)),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+// expr  := term (('+' | '-') term)*
+// term  := unary (('*' | '/') unary | implicit product)*
+// unary := ('-' | '+') unary | power
+// power := primary ('^' unary)?
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            Some(t) => Err(format!("expected '{}' but found '{}'", token, t)),
+            None => Err(format!("expected '{}'", token)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(c)) = self.peek() {
+            let op = match c {
+                '+' => Op::Add,
+                '-' => Op::Sub,
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.term()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('*')) => Op::Mul,
+                Some(Token::Op('/')) => Op::Div,
+                // "2x", "3(x + 1)" and "x sin(x)" are products
+                Some(Token::Number(_)) | Some(Token::Ident(_)) | Some(Token::LParen) => {
+                    let right = self.power()?;
+                    left = Expr::Binary(Op::Mul, Box::new(left), Box::new(right));
+                    continue;
+                }
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.primary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(Op::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Ident(name)) => {
+                // "a(x + 1)" with a parameter a is a product, left to term()
+                let arity = match FUNCTIONS.iter().find(|(f, _)| *f == name) {
+                    Some((_, arity)) if self.peek() == Some(&Token::LParen) => *arity,
+                    _ => return Ok(Expr::Var(name)),
+                };
+                self.pos += 1;
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                if args.len() != arity {
+                    return Err(format!(
+                        "{} takes {} argument(s) but got {}",
+                        name,
+                        arity,
+                        args.len()
+                    ));
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Some(Token::LParen) => {
+                let e = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(e)
+            }
+            Some(t) => Err(format!("unexpected '{}'", t)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str, x: f64) -> f64 {
+        let lookup = |n: &str| if n == "x" { Some(x) } else { None };
+        parse(input).unwrap().eval(&lookup).unwrap()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3", 0.0), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(eval("8 / 4 / 2", 0.0), 1.0);
+        assert_eq!(eval("10 - 4 - 3", 0.0), 3.0);
+        assert_eq!(eval("-2^2", 0.0), -4.0);
+        assert_eq!(eval("2^3^2", 0.0), 512.0);
+        assert_eq!(eval("2^-1", 0.0), 0.5);
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        assert_eq!(eval("2x", 3.0), 6.0);
+        assert_eq!(eval("3(x + 1)", 1.0), 6.0);
+        assert_eq!(eval("2x^2", 3.0), 18.0);
+        assert_eq!(eval("x x", 4.0), 16.0);
+        assert!((eval("2pi", 0.0) - f64::consts::TAU).abs() < 1e-12);
+        assert!((eval("x sin(x)", 1.0) - 1f64.sin()).abs() < 1e-12);
+        // an exponent, not the constant e
+        assert_eq!(eval("1e-3", 0.0), 0.001);
+        assert!((eval("2e", 0.0) - 2.0 * f64::consts::E).abs() < 1e-12);
+    }
+
+    #[test]
+    fn double_star_is_power() {
+        assert_eq!(eval("x**2", 3.0), 9.0);
+        assert_eq!(eval("2 * x ** 2", 3.0), 18.0);
+        assert_eq!(parse("x**2"), parse("x^2"));
+    }
+
+    #[test]
+    fn functions_and_arity() {
+        assert_eq!(eval("max(x, 2)", 5.0), 5.0);
+        assert_eq!(eval("atan2(0, 1)", 0.0), 0.0);
+        assert_eq!(eval("sign(0)", 0.0), 0.0);
+        assert_eq!(
+            parse("sin(1, 2)"),
+            Err(String::from("sin takes 1 argument(s) but got 2"))
+        );
+        assert_eq!(
+            parse("min(1)"),
+            Err(String::from("min takes 2 argument(s) but got 1"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(""), Err(String::from("empty expression")));
+        assert_eq!(
+            parse("1 +"),
+            Err(String::from("unexpected end of expression"))
+        );
+        assert_eq!(parse("(1"), Err(String::from("expected ')'")));
+        assert_eq!(
+            parse("1 $ 2"),
+            Err(String::from("unexpected character '$'"))
+        );
+        assert_eq!(
+            parse("y").unwrap().eval(&|_| None),
+            Err(String::from("unknown variable 'y'"))
+        );
+    }
+
+    #[test]
+    fn variables_and_ranges() {
+        let e = parse("a x^2 + b x + pi").unwrap();
+        assert_eq!(e.get_variables(), vec!["a", "x", "b"]);
+        assert_eq!(split_range("sin(t) [0, 2pi]"), ("sin(t)", Some("0, 2pi")));
+        assert_eq!(
+            split_arguments("f(1, 2), [3, 4]"),
+            vec!["f(1, 2)", "[3, 4]"]
+        );
+        let params = HashMap::new();
+        assert_eq!(parse_range("-1, 1", &params), Ok((-1.0, 1.0)));
+        assert!(parse_range("1, -1", &params).is_err());
+        assert!(parse_range("1", &params).is_err());
+    }
+}
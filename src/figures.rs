@@ -3,3 +3,190 @@ pub mod point;
 pub mod line;
 pub mod circle;
 pub mod rectangle;
+pub mod arc;
+pub mod bezier;
+pub mod spline;
+pub mod function;
+pub mod parametric;
+pub mod conic;
+
+use std::collections::HashMap;
+
+use self::{
+    arc::Arc, bezier::Bezier, circle::Circle, conic::Conic, function::FunctionGraph,
+    line::Line, parametric::Parametric, point::Point, rectangle::Rectangle, spline::Spline,
+};
+
+// everything the canvas can hold, so intersections can be computed between any two of them
+#[derive(Debug, Clone)]
+pub enum Figure {
+    Point(Point),
+    Line(Line),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Arc(Arc),
+    Bezier(Bezier),
+    Spline(Spline),
+    Function(FunctionGraph),
+    Parametric(Parametric),
+    Conic(Conic),
+}
+
+impl Figure {
+    // the points a user can grab and drag around after the figure is drawn
+    pub fn get_control_points(&self) -> Vec<Point> {
+        match self {
+            Figure::Point(p) => vec![*p],
+            Figure::Line(l) => vec![l.get_point_a(), l.get_point_b()],
+            Figure::Bezier(b) => b.get_controls().to_vec(),
+            Figure::Spline(s) => s.get_points().to_vec(),
+            Figure::Conic(c) => c.get_points().to_vec(),
+            _ => vec![],
+        }
+    }
+
+    pub fn set_control_point(&mut self, i: usize, p: Point) {
+        match self {
+            Figure::Point(q) => *q = p,
+            Figure::Line(l) if i == 0 => *l = Line::new(p, l.get_point_b()),
+            Figure::Line(l) => *l = Line::new(l.get_point_a(), p),
+            Figure::Bezier(b) => b.set_control(i, p),
+            Figure::Spline(s) => s.set_point(i, p),
+            Figure::Conic(c) => c.set_point(i, p),
+            _ => {}
+        }
+    }
+
+    // the numbers that define the figure, named for editing them directly
+    pub fn get_properties(&self) -> Vec<(String, f64)> {
+        let coordinates = |points: &[Point]| -> Vec<(String, f64)> {
+            points
+                .iter()
+                .enumerate()
+                .flat_map(|(i, p)| {
+                    [
+                        (format!("x{}", i + 1), p.get_x()),
+                        (format!("y{}", i + 1), p.get_y()),
+                    ]
+                })
+                .collect()
+        };
+        let named = |names: &[&str], values: &[f64]| -> Vec<(String, f64)> {
+            names
+                .iter()
+                .zip(values)
+                .map(|(n, v)| (String::from(*n), *v))
+                .collect()
+        };
+        match self {
+            Figure::Point(p) => named(&["x", "y"], &[p.get_x(), p.get_y()]),
+            Figure::Line(l) => coordinates(&[l.get_point_a(), l.get_point_b()]),
+            Figure::Circle(c) => named(
+                &["x", "y", "r"],
+                &[c.get_center().get_x(), c.get_center().get_y(), c.get_rad()],
+            ),
+            Figure::Rectangle(r) => coordinates(&[r.get_point_a(), r.get_point_b()]),
+            Figure::Arc(a) => named(
+                &["x", "y", "r", "start°", "sweep°"],
+                &[
+                    a.get_center().get_x(),
+                    a.get_center().get_y(),
+                    a.get_rad(),
+                    a.get_start_angle().to_degrees(),
+                    a.get_sweep().to_degrees(),
+                ],
+            ),
+            Figure::Bezier(_) | Figure::Spline(_) | Figure::Conic(_) => {
+                coordinates(&self.get_control_points())
+            }
+            Figure::Function(_) | Figure::Parametric(_) => vec![],
+        }
+    }
+
+    // takes the values in the order of get_properties, false if they don't make a figure
+    pub fn set_properties(&mut self, values: &[f64]) -> bool {
+        if values.len() != self.get_properties().len() || values.iter().any(|v| !v.is_finite()) {
+            return false;
+        }
+        let point = |i: usize| Point::new(values[i], values[i + 1]);
+        match self {
+            Figure::Point(p) => *p = point(0),
+            Figure::Line(l) => *l = Line::new(point(0), point(2)),
+            Figure::Circle(c) if values[2] > 0.0 => {
+                *c = Circle::new(point(0), Point::new(values[0] + values[2], values[1]))
+            }
+            Figure::Rectangle(r) => *r = Rectangle::new(point(0), point(2)),
+            Figure::Arc(a) if values[2] > 0.0 => {
+                *a = Arc::from_angles(
+                    point(0),
+                    values[2],
+                    values[3].to_radians(),
+                    values[4].to_radians(),
+                )
+            }
+            Figure::Bezier(_) | Figure::Spline(_) | Figure::Conic(_) => {
+                for i in 0..values.len() / 2 {
+                    self.set_control_point(i, point(2 * i));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // what the figure is called in lists
+    pub fn get_kind(&self) -> &'static str {
+        match self {
+            Figure::Point(_) => "Point",
+            Figure::Line(_) => "Line",
+            Figure::Circle(_) => "Circle",
+            Figure::Rectangle(_) => "Rectangle",
+            Figure::Arc(_) => "Arc",
+            Figure::Bezier(_) => "Bezier",
+            Figure::Spline(_) => "Spline",
+            Figure::Function(_) => "Function",
+            Figure::Parametric(_) => "Curve",
+            Figure::Conic(_) => "Conic",
+        }
+    }
+
+    // curves split into bezier pieces, None for the figures that aren't curves
+    pub fn get_bezier_segments(&self) -> Option<Vec<Bezier>> {
+        match self {
+            Figure::Bezier(b) => Some(vec![b.clone()]),
+            Figure::Spline(s) => Some(s.get_segments().to_vec()),
+            _ => None,
+        }
+    }
+
+    // the curved figures as polylines that stay within tolerance of them, the ones without an
+    // end as far as bounds (min x, min y, max x, max y) reach
+    pub fn get_polylines(
+        &self,
+        tolerance: f64,
+        bounds: (f64, f64, f64, f64),
+    ) -> Option<Vec<Vec<Point>>> {
+        match self {
+            Figure::Bezier(b) => Some(vec![b.flatten(tolerance)]),
+            Figure::Spline(s) => Some(
+                s.get_segments()
+                    .iter()
+                    .map(|b| b.flatten(tolerance))
+                    .collect(),
+            ),
+            Figure::Function(g) => Some(g.sample(tolerance, (bounds.0, bounds.2))),
+            Figure::Parametric(c) => Some(c.sample(tolerance)),
+            Figure::Conic(c) => Some(c.sample(tolerance, bounds)),
+            _ => None,
+        }
+    }
+
+    // hands new parameter values to the figures defined by expressions
+    pub fn set_params(&mut self, params: &HashMap<String, f64>) {
+        match self {
+            Figure::Function(g) => g.set_params(params.clone()),
+            Figure::Parametric(c) => c.set_params(params.clone()),
+            _ => {}
+        }
+    }
+}
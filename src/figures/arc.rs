@@ -0,0 +1,229 @@
+#![allow(dead_code)]
+use std::f64;
+
+use crate::utils::{get_angle, get_circumcenter, get_distance, normalize_angle};
+
+use super::{circle::Circle, line::Line, point::Point};
+
+// an arc runs from start_angle in the direction of increasing angle for sweep radians,
+// filled it is drawn as the sector between the arc and its center
+#[derive(Debug, Copy, Clone)]
+pub struct Arc {
+    rad: f64,
+    start_angle: f64,
+    sweep: f64,
+    length: f64,
+    sector_area: f64,
+    chord: f64,
+    center: Point,
+    startpoint: Point,
+    endpoint: Point,
+}
+
+impl Arc {
+    // center, a point where the arc starts and a point that only gives the end angle
+    pub fn new(center: Point, startpoint: Point, endpoint: Point) -> Arc {
+        let rad = get_distance(center, startpoint);
+        let start_angle = get_angle(center, startpoint);
+        let mut sweep = normalize_angle(get_angle(center, endpoint) - start_angle);
+        if sweep == 0.0 {
+            sweep = 2.0 * f64::consts::PI;
+        }
+        Arc::from_angles(center, rad, start_angle, sweep)
+    }
+
+    // the arc through three points which starts at a, passes b and ends at c
+    pub fn from_three_points(a: Point, b: Point, c: Point) -> Option<Arc> {
+        let center = get_circumcenter(a, b, c)?;
+        let rad = get_distance(center, a);
+        let angle_a = get_angle(center, a);
+        let to_b = normalize_angle(get_angle(center, b) - angle_a);
+        let to_c = normalize_angle(get_angle(center, c) - angle_a);
+        if to_b <= to_c {
+            Some(Arc::from_angles(center, rad, angle_a, to_c))
+        } else {
+            // b lies on the other side, so run from c to a instead
+            Some(Arc::from_angles(
+                center,
+                rad,
+                get_angle(center, c),
+                2.0 * f64::consts::PI - to_c,
+            ))
+        }
+    }
+
+    pub fn from_angles(center: Point, rad: f64, start_angle: f64, sweep: f64) -> Arc {
+        let start_angle = normalize_angle(start_angle);
+        let end_angle = start_angle + sweep;
+        let startpoint = Point::new(
+            center.get_x() + rad * start_angle.cos(),
+            center.get_y() + rad * start_angle.sin(),
+        );
+        let endpoint = Point::new(
+            center.get_x() + rad * end_angle.cos(),
+            center.get_y() + rad * end_angle.sin(),
+        );
+        let length = rad * sweep;
+        let sector_area = 0.5 * rad.powi(2) * sweep;
+        let chord = 2.0 * rad * (sweep / 2.0).sin();
+        Arc {
+            rad,
+            start_angle,
+            sweep,
+            length,
+            sector_area,
+            chord,
+            center,
+            startpoint,
+            endpoint,
+        }
+    }
+
+    pub fn move_arc(&mut self, x: f64, y: f64) {
+        self.center.add_point(Point::new(x, y));
+        self.startpoint.add_point(Point::new(x, y));
+        self.endpoint.add_point(Point::new(x, y));
+    }
+
+    // checks if a direction seen from the center lies within the arc
+    pub fn contains_angle(&self, angle: f64) -> bool {
+        let offset = normalize_angle(angle - self.start_angle);
+        offset <= self.sweep + 1e-9 || offset >= 2.0 * f64::consts::PI - 1e-9
+    }
+
+    // only checks the direction, the point is expected to lie on the circle
+    pub fn contains_point(&self, p: Point) -> bool {
+        self.contains_angle(get_angle(self.center, p))
+    }
+
+    pub fn to_circle(self) -> Circle {
+        Circle::new(self.center, self.startpoint)
+    }
+
+    pub fn get_chord_line(&self) -> Line {
+        Line::new(self.startpoint, self.endpoint)
+    }
+
+    pub fn get_rad(&self) -> f64 {
+        self.rad
+    }
+
+    pub fn get_start_angle(&self) -> f64 {
+        self.start_angle
+    }
+
+    pub fn get_end_angle(&self) -> f64 {
+        self.start_angle + self.sweep
+    }
+
+    pub fn get_sweep(&self) -> f64 {
+        self.sweep
+    }
+
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn get_sector_area(&self) -> f64 {
+        self.sector_area
+    }
+
+    pub fn get_chord(&self) -> f64 {
+        self.chord
+    }
+
+    pub fn get_center(&self) -> Point {
+        self.center
+    }
+
+    pub fn get_startpoint(&self) -> Point {
+        self.startpoint
+    }
+
+    pub fn get_endpoint(&self) -> Point {
+        self.endpoint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn three_points_either_way_round() {
+        let pi = f64::consts::PI;
+        // counterclockwise through the top, which is the arc as it is
+        let a = Arc::from_three_points(
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(-1.0, 0.0),
+        )
+        .unwrap();
+        assert!(close(a.get_start_angle(), 0.0) && close(a.get_sweep(), pi));
+
+        // clockwise through the top runs from the last point back to the first
+        let a = Arc::from_three_points(
+            Point::new(-1.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 0.0),
+        )
+        .unwrap();
+        assert!(close(a.get_start_angle(), 0.0) && close(a.get_sweep(), pi));
+        assert!(a.contains_point(Point::new(0.0, 1.0)));
+        assert!(!a.contains_point(Point::new(0.0, -1.0)));
+
+        // counterclockwise through the bottom
+        let a = Arc::from_three_points(
+            Point::new(-1.0, 0.0),
+            Point::new(0.0, -1.0),
+            Point::new(1.0, 0.0),
+        )
+        .unwrap();
+        assert!(close(a.get_start_angle(), pi) && close(a.get_sweep(), pi));
+        assert!(a.contains_point(Point::new(0.0, -1.0)));
+
+        // a short arc which b doesn't split evenly
+        let a = Arc::from_three_points(
+            Point::new(0.0, 1.0),
+            Point::new(0.6, 0.8),
+            Point::new(1.0, 0.0),
+        )
+        .unwrap();
+        assert!(close(a.get_start_angle(), 0.0) && close(a.get_sweep(), pi / 2.0));
+
+        assert!(Arc::from_three_points(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 2.0)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn across_angle_zero() {
+        let pi = f64::consts::PI;
+        let a = Arc::from_angles(Point::new(5.0, 5.0), 2.0, -pi / 4.0, pi / 2.0);
+        assert!(close(a.get_start_angle(), 7.0 * pi / 4.0));
+        for angle in [0.0, 0.5, -0.5, 2.0 * pi - 0.1, pi / 4.0, -pi / 4.0] {
+            assert!(a.contains_angle(angle), "{}", angle);
+        }
+        for angle in [pi, pi / 2.0, -pi / 2.0, pi / 4.0 + 0.01] {
+            assert!(!a.contains_angle(angle), "{}", angle);
+        }
+        assert!(close(a.get_endpoint().get_x(), 5.0 + 2f64.sqrt()));
+        assert!(close(a.get_endpoint().get_y(), 5.0 + 2f64.sqrt()));
+
+        // an end in the same direction as the start makes the whole circle
+        let a = Arc::new(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(3.0, 0.0),
+        );
+        assert!(close(a.get_sweep(), 2.0 * pi));
+        assert!(a.contains_angle(pi));
+    }
+}
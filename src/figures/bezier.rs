@@ -0,0 +1,191 @@
+#![allow(dead_code)]
+use crate::utils::get_distance;
+
+use super::point::Point;
+
+// a quadratic (3 controls) or cubic (4 controls) bezier curve
+#[derive(Debug, Clone)]
+pub struct Bezier {
+    controls: Vec<Point>,
+    length: f64,
+}
+
+impl Bezier {
+    pub fn new(controls: Vec<Point>) -> Bezier {
+        let mut b = Bezier {
+            controls,
+            length: 0.0,
+        };
+        b.length = approximate_length(&b, 0);
+        b
+    }
+
+    pub fn quadratic(start: Point, control: Point, end: Point) -> Bezier {
+        Bezier::new(vec![start, control, end])
+    }
+
+    pub fn cubic(start: Point, control1: Point, control2: Point, end: Point) -> Bezier {
+        Bezier::new(vec![start, control1, control2, end])
+    }
+
+    // de casteljau
+    pub fn point_at(&self, t: f64) -> Point {
+        let mut pts = self.controls.clone();
+        while pts.len() > 1 {
+            pts = pts.windows(2).map(|w| lerp(w[0], w[1], t)).collect();
+        }
+        pts[0]
+    }
+
+    pub fn split(&self, t: f64) -> (Bezier, Bezier) {
+        let mut left = vec![self.controls[0]];
+        let mut right = vec![*self.controls.last().unwrap()];
+        let mut pts = self.controls.clone();
+        while pts.len() > 1 {
+            pts = pts.windows(2).map(|w| lerp(w[0], w[1], t)).collect();
+            left.push(pts[0]);
+            right.push(*pts.last().unwrap());
+        }
+        right.reverse();
+        (
+            Bezier {
+                controls: left,
+                length: 0.0,
+            },
+            Bezier {
+                controls: right,
+                length: 0.0,
+            },
+        )
+    }
+
+    // the curve lies within tolerance of the line from its first to its last control point
+    pub fn is_flat(&self, tolerance: f64) -> bool {
+        let a = self.get_start();
+        let b = self.get_end();
+        let len = get_distance(a, b);
+        self.controls[1..self.controls.len() - 1].iter().all(|p| {
+            if len < 1e-12 {
+                get_distance(a, *p) <= tolerance
+            } else {
+                let cross = (b.get_x() - a.get_x()) * (p.get_y() - a.get_y())
+                    - (b.get_y() - a.get_y()) * (p.get_x() - a.get_x());
+                cross.abs() / len <= tolerance
+            }
+        })
+    }
+
+    // polyline through the curve, used for drawing
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        let mut points = vec![self.get_start()];
+        flatten_into(self, tolerance, 0, &mut points);
+        points
+    }
+
+    // (min x, min y, max x, max y) of the control polygon, the curve is always inside it
+    pub fn get_bounds(&self) -> (f64, f64, f64, f64) {
+        self.controls.iter().fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(x1, y1, x2, y2), p| {
+                (
+                    x1.min(p.get_x()),
+                    y1.min(p.get_y()),
+                    x2.max(p.get_x()),
+                    y2.max(p.get_y()),
+                )
+            },
+        )
+    }
+
+    pub fn set_control(&mut self, i: usize, p: Point) {
+        self.controls[i] = p;
+        self.length = approximate_length(self, 0);
+    }
+
+    pub fn move_bezier(&mut self, x: f64, y: f64) {
+        for p in &mut self.controls {
+            p.add_point(Point::new(x, y));
+        }
+    }
+
+    pub fn get_controls(&self) -> &[Point] {
+        &self.controls
+    }
+
+    pub fn get_start(&self) -> Point {
+        self.controls[0]
+    }
+
+    pub fn get_end(&self) -> Point {
+        *self.controls.last().unwrap()
+    }
+
+    pub fn get_degree(&self) -> usize {
+        self.controls.len() - 1
+    }
+
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+}
+
+fn lerp(a: Point, b: Point, t: f64) -> Point {
+    Point::new(
+        a.get_x() + (b.get_x() - a.get_x()) * t,
+        a.get_y() + (b.get_y() - a.get_y()) * t,
+    )
+}
+
+fn flatten_into(b: &Bezier, tolerance: f64, depth: u32, points: &mut Vec<Point>) {
+    if depth >= 16 || b.is_flat(tolerance) {
+        points.push(b.get_end());
+    } else {
+        let (left, right) = b.split(0.5);
+        flatten_into(&left, tolerance, depth + 1, points);
+        flatten_into(&right, tolerance, depth + 1, points);
+    }
+}
+
+// the arc length lies between the chord and the length of the control polygon,
+// so split until both are close enough
+fn approximate_length(b: &Bezier, depth: u32) -> f64 {
+    let chord = get_distance(b.get_start(), b.get_end());
+    let polygon: f64 = b
+        .controls
+        .windows(2)
+        .map(|w| get_distance(w[0], w[1]))
+        .sum();
+    if depth >= 16 || polygon - chord < 1e-3 {
+        (polygon + chord) / 2.0
+    } else {
+        let (left, right) = b.split(0.5);
+        approximate_length(&left, depth + 1) + approximate_length(&right, depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_halves_follow_the_curve() {
+        let curve = Bezier::cubic(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 3.0),
+            Point::new(4.0, -2.0),
+            Point::new(5.0, 1.0),
+        );
+        let (left, right) = curve.split(0.3);
+        assert_eq!(left.get_degree(), 3);
+        assert!(get_distance(left.get_end(), curve.point_at(0.3)) < 1e-12);
+        assert!(get_distance(right.get_start(), curve.point_at(0.3)) < 1e-12);
+        for s in [0.0, 0.25, 0.5, 1.0] {
+            assert!(get_distance(left.point_at(s), curve.point_at(0.3 * s)) < 1e-12);
+            assert!(get_distance(right.point_at(s), curve.point_at(0.3 + 0.7 * s)) < 1e-12);
+        }
+
+        let points = curve.flatten(1e-3);
+        assert!(get_distance(points[0], curve.get_start()) < 1e-12);
+        assert!(get_distance(*points.last().unwrap(), curve.get_end()) < 1e-12);
+    }
+}
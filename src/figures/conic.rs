@@ -0,0 +1,597 @@
+#![allow(dead_code)]
+use std::f64;
+
+use crate::utils::get_distance;
+
+use super::{circle::Circle, line::Line, point::Point};
+
+// relative to the size of what is compared, so they don't depend on where the conic is
+// or how big it is
+const PARABOLA_TOLERANCE: f64 = 1e-9;
+const DEGENERATE_TOLERANCE: f64 = 1e-12;
+const MAX_SAMPLE_DEPTH: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConicKind {
+    Ellipse,
+    Parabola,
+    Hyperbola,
+    Degenerate, // a pair of lines
+}
+
+// ax² + bxy + cy² + dx + ey + f = 0, usually through five clicked points
+#[derive(Debug, Clone)]
+pub struct Conic {
+    coefficients: [f64; 6],
+    points: Vec<Point>,
+    kind: ConicKind,
+}
+
+impl Conic {
+    // None if the points don't decide a single conic, like when four of them are on one line
+    pub fn from_five_points(points: [Point; 5]) -> Option<Conic> {
+        // the conic is found around the centroid with the points about a unit from it, so how
+        // well they decide it doesn't depend on where they are or how far apart
+        let cx = points.iter().map(|p| p.get_x()).sum::<f64>() / 5.0;
+        let cy = points.iter().map(|p| p.get_y()).sum::<f64>() / 5.0;
+        let spread = (points
+            .iter()
+            .map(|p| (p.get_x() - cx).powi(2) + (p.get_y() - cy).powi(2))
+            .sum::<f64>()
+            / 5.0)
+            .sqrt();
+        if spread == 0.0 {
+            return None;
+        }
+        let rows: Vec<[f64; 6]> = points
+            .iter()
+            .map(|p| {
+                let (x, y) = ((p.get_x() - cx) / spread, (p.get_y() - cy) / spread);
+                [x * x, x * y, y * y, x, y, 1.0]
+            })
+            .collect();
+        // the coefficients are the signed 5x5 minors, which makes every row evaluate to 0
+        let mut coefficients = [0.0; 6];
+        for (i, c) in coefficients.iter_mut().enumerate() {
+            let minor: Vec<Vec<f64>> = rows
+                .iter()
+                .map(|r| (0..6).filter(|&j| j != i).map(|j| r[j]).collect())
+                .collect();
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            *c = sign * get_determinant(minor);
+        }
+        let norm = get_norm(&coefficients);
+        let scale = rows.iter().map(|r| get_norm(r)).product::<f64>();
+        if norm <= 1e-12 * scale {
+            return None;
+        }
+        // x = cx + spread u and y = cy + spread v put back into the conic in u and v
+        let [a, b, c, d, e, f] = coefficients;
+        let (d, e, f) = (d * spread, e * spread, f * spread * spread);
+        let mut conic = Conic::from_coefficients([
+            a,
+            b,
+            c,
+            d - 2.0 * a * cx - b * cy,
+            e - b * cx - 2.0 * c * cy,
+            a * cx * cx + b * cx * cy + c * cy * cy - d * cx - e * cy + f,
+        ]);
+        // the shape is best told around the centroid
+        conic.kind = classify(&coefficients);
+        conic.points = points.to_vec();
+        Some(conic)
+    }
+
+    pub fn from_coefficients(coefficients: [f64; 6]) -> Conic {
+        let norm = get_norm(&coefficients);
+        let coefficients = coefficients.map(|c| c / norm);
+        Conic {
+            coefficients,
+            points: vec![],
+            kind: classify(&coefficients),
+        }
+    }
+
+    pub fn from_circle(c: Circle) -> Conic {
+        let (x, y) = (c.get_center().get_x(), c.get_center().get_y());
+        Conic::from_coefficients([
+            1.0,
+            0.0,
+            1.0,
+            -2.0 * x,
+            -2.0 * y,
+            x * x + y * y - c.get_rad().powi(2),
+        ])
+    }
+
+    // keeps the old conic if the new point makes it undecided
+    pub fn set_point(&mut self, i: usize, p: Point) {
+        if self.points.len() != 5 {
+            return;
+        }
+        let mut points = [self.points[0]; 5];
+        points.copy_from_slice(&self.points);
+        points[i] = p;
+        if let Some(conic) = Conic::from_five_points(points) {
+            *self = conic;
+        }
+    }
+
+    pub fn move_conic(&mut self, x: f64, y: f64) {
+        let mut points = [Point::new(0.0, 0.0); 5];
+        for (i, p) in self.points.iter().enumerate() {
+            points[i] = Point::new(p.get_x() + x, p.get_y() + y);
+        }
+        if let Some(conic) = Conic::from_five_points(points) {
+            *self = conic;
+        }
+    }
+
+    // 0 on the conic, the sign tells the sides apart
+    pub fn eval(&self, p: Point) -> f64 {
+        let [a, b, c, d, e, f] = self.coefficients;
+        let (x, y) = (p.get_x(), p.get_y());
+        a * x * x + b * x * y + c * y * y + d * x + e * y + f
+    }
+
+    // the symmetric matrix M with (x, y, 1) M (x, y, 1)^T = 0
+    pub fn get_matrix(&self) -> [[f64; 3]; 3] {
+        let [a, b, c, d, e, f] = self.coefficients;
+        [
+            [a, b / 2.0, d / 2.0],
+            [b / 2.0, c, e / 2.0],
+            [d / 2.0, e / 2.0, f],
+        ]
+    }
+
+    // center and semi axes (major first) of a real ellipse
+    pub fn get_ellipse_axes(&self) -> Option<(Point, f64, f64)> {
+        if self.kind != ConicKind::Ellipse {
+            return None;
+        }
+        let [a, b, c, d, e, _] = self.coefficients;
+        let det = a * c - b * b / 4.0;
+        let center = Point::new(
+            (b * e / 4.0 - c * d / 2.0) / det,
+            (b * d / 4.0 - a * e / 2.0) / det,
+        );
+        // the eigenvalues of the quadratic part, (u, v) along its eigenvectors gives l1 u² + l2 v² = k
+        let mean = (a + c) / 2.0;
+        let spread = (((a - c) / 2.0).powi(2) + b * b / 4.0).sqrt();
+        let k = -self.eval(center);
+        let (r1, r2) = (k / (mean - spread), k / (mean + spread));
+        if r1 <= 0.0 || r2 <= 0.0 {
+            return None;
+        }
+        Some((center, r1.max(r2).sqrt(), r1.min(r2).sqrt()))
+    }
+
+    // the two lines a degenerate conic is made of
+    pub fn get_lines(&self) -> Vec<Line> {
+        if self.kind != ConicKind::Degenerate {
+            return vec![];
+        }
+        split_degenerate(self.get_matrix())
+            .into_iter()
+            .filter_map(to_line)
+            .collect()
+    }
+
+    // polylines through every branch, long enough to leave the bounds (min x, min y, max x, max y)
+    pub fn sample(&self, tolerance: f64, bounds: (f64, f64, f64, f64)) -> Vec<Vec<Point>> {
+        let view_center = Point::new((bounds.0 + bounds.2) / 2.0, (bounds.1 + bounds.3) / 2.0);
+        let reach = get_distance(view_center, Point::new(bounds.2, bounds.3));
+
+        if self.kind == ConicKind::Degenerate {
+            return self
+                .get_lines()
+                .iter()
+                .map(|l| {
+                    let (a, b) = (l.get_point_a(), l.get_point_b());
+                    let len = reach + get_distance(view_center, a);
+                    let dx = (b.get_x() - a.get_x()) * len;
+                    let dy = (b.get_y() - a.get_y()) * len;
+                    vec![
+                        Point::new(a.get_x() - dx, a.get_y() - dy),
+                        Point::new(a.get_x() + dx, a.get_y() + dy),
+                    ]
+                })
+                .collect();
+        }
+
+        // turn the axes so the xy term goes away: l1 u² + l2 v² + d' u + e' v + f = 0
+        let [a, b, c, d, e, f] = self.coefficients;
+        let phi = 0.5 * b.atan2(a - c);
+        let (sin, cos) = phi.sin_cos();
+        let l1 = a * cos * cos + b * cos * sin + c * sin * sin;
+        let l2 = a * sin * sin - b * sin * cos + c * cos * cos;
+        let d1 = d * cos + e * sin;
+        let e1 = -d * sin + e * cos;
+        let to_world = |u: f64, v: f64| Point::new(u * cos - v * sin, u * sin + v * cos);
+
+        let curve = |u: &dyn Fn(f64) -> f64, v: &dyn Fn(f64) -> f64, t0: f64, t1: f64| {
+            sample_curve(&|t| to_world(u(t), v(t)), t0, t1, tolerance)
+        };
+
+        if self.kind == ConicKind::Parabola {
+            // the axis of the parabola is along u when l1 is the vanishing one
+            if l1.abs() < l2.abs() {
+                let v0 = -e1 / (2.0 * l2);
+                let vertex = to_world(-(l2 * v0 * v0 + e1 * v0 + f) / d1, v0);
+                let len = reach + get_distance(view_center, vertex);
+                let u = |v: f64| -(l2 * v * v + e1 * v + f) / d1;
+                return vec![curve(&u, &|v| v, v0 - len, v0 + len)];
+            }
+            let u0 = -d1 / (2.0 * l1);
+            let vertex = to_world(u0, -(l1 * u0 * u0 + d1 * u0 + f) / e1);
+            let len = reach + get_distance(view_center, vertex);
+            let v = |u: f64| -(l1 * u * u + d1 * u + f) / e1;
+            return vec![curve(&|u| u, &v, u0 - len, u0 + len)];
+        }
+
+        // l1 (u - u0)² + l2 (v - v0)² = k
+        let u0 = -d1 / (2.0 * l1);
+        let v0 = -e1 / (2.0 * l2);
+        let k = d1 * d1 / (4.0 * l1) + e1 * e1 / (4.0 * l2) - f;
+        let (ru, rv) = (k / l1, k / l2);
+        if self.kind == ConicKind::Ellipse {
+            if ru <= 0.0 || rv <= 0.0 {
+                return vec![];
+            }
+            let (ru, rv) = (ru.sqrt(), rv.sqrt());
+            let u = |t: f64| u0 + ru * t.cos();
+            let v = |t: f64| v0 + rv * t.sin();
+            return vec![curve(&u, &v, 0.0, 2.0 * f64::consts::PI)];
+        }
+
+        let (ru, rv) = (ru.abs().sqrt(), rv.abs().sqrt());
+        let center = to_world(u0, v0);
+        let s = ((reach + get_distance(view_center, center)) / ru.min(rv)).asinh();
+        let mut branches = Vec::new();
+        for side in [1.0, -1.0] {
+            // the branches open along the axis where k / l is positive
+            if k / l1 > 0.0 {
+                let u = |t: f64| u0 + side * ru * t.cosh();
+                let v = |t: f64| v0 + rv * t.sinh();
+                branches.push(curve(&u, &v, -s, s));
+            } else {
+                let u = |t: f64| u0 + ru * t.sinh();
+                let v = |t: f64| v0 + side * rv * t.cosh();
+                branches.push(curve(&u, &v, -s, s));
+            }
+        }
+        branches
+    }
+
+    pub fn get_coefficients(&self) -> [f64; 6] {
+        self.coefficients
+    }
+
+    pub fn get_points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn get_kind(&self) -> ConicKind {
+        self.kind
+    }
+}
+
+// every test compares a value with the size of the terms it is made of, which makes them the
+// same wherever the conic is and however big it is
+fn classify(coefficients: &[f64; 6]) -> ConicKind {
+    let [a, b, c, d, e, f] = *coefficients;
+    let quadratic = a * a + b * b + c * c;
+    if quadratic == 0.0 {
+        return ConicKind::Degenerate; // a line
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant.abs() < PARABOLA_TOLERANCE * quadratic {
+        // a (w.(x, y))² + d x + e y + f with w along the quadratic part is a pair of parallel
+        // lines when (d, e) has nothing across w
+        let w = if a.abs() >= c.abs() {
+            (a, b / 2.0)
+        } else {
+            (b / 2.0, c)
+        };
+        let across = (e * w.0 - d * w.1).abs() / w.0.hypot(w.1);
+        if across <= DEGENERATE_TOLERANCE * d.hypot(e) {
+            return ConicKind::Degenerate;
+        }
+        return ConicKind::Parabola;
+    }
+    // the value at the center, which is 0 for a pair of crossing lines
+    let det = a * c - b * b / 4.0;
+    let (x, y) = (
+        (b * e / 4.0 - c * d / 2.0) / det,
+        (b * d / 4.0 - a * e / 2.0) / det,
+    );
+    let k = f + (d * x + e * y) / 2.0;
+    if k.abs() <= DEGENERATE_TOLERANCE * (f.abs() + (d * x).abs() / 2.0 + (e * y).abs() / 2.0) {
+        ConicKind::Degenerate
+    } else if discriminant < 0.0 {
+        ConicKind::Ellipse
+    } else {
+        ConicKind::Hyperbola
+    }
+}
+
+// splits the matrix of a degenerate conic into the two lines (l1, l2, l3) with
+// l1 x + l2 y + l3 = 0, following Richter-Gebert, Perspectives on Projective Geometry;
+// empty if the lines aren't real and the conic is a single point
+pub fn split_degenerate(m: [[f64; 3]; 3]) -> Vec<[f64; 3]> {
+    let adj = get_adjugate(m);
+    let i = (0..3)
+        .max_by(|&i, &j| adj[i][i].abs().total_cmp(&adj[j][j].abs()))
+        .unwrap();
+    let scale = m.iter().flatten().map(|v| v * v).sum::<f64>();
+    if adj[i][i].abs() <= 1e-12 * scale * scale {
+        // rank 1, a double line
+        let row = m
+            .iter()
+            .max_by(|a, b| get_norm(&a[..]).total_cmp(&get_norm(&b[..])))
+            .unwrap();
+        return vec![*row];
+    }
+    if adj[i][i] > 0.0 {
+        return vec![];
+    }
+    let beta = (-adj[i][i]).sqrt();
+    let p = [adj[0][i] / beta, adj[1][i] / beta, adj[2][i] / beta];
+    // adding the cross product matrix of the crossing point leaves a rank 1 matrix g h^T
+    let c = [
+        [m[0][0], m[0][1] + p[2], m[0][2] - p[1]],
+        [m[1][0] - p[2], m[1][1], m[1][2] + p[0]],
+        [m[2][0] + p[1], m[2][1] - p[0], m[2][2]],
+    ];
+    let (mut row, mut col) = (0, 0);
+    for r in 0..3 {
+        for k in 0..3 {
+            if c[r][k].abs() > c[row][col].abs() {
+                row = r;
+                col = k;
+            }
+        }
+    }
+    vec![c[row], [c[0][col], c[1][col], c[2][col]]]
+}
+
+// a line from l1 x + l2 y + l3 = 0, None for the line at infinity
+pub fn to_line(l: [f64; 3]) -> Option<Line> {
+    let len = (l[0] * l[0] + l[1] * l[1]).sqrt();
+    if len <= 1e-12 * get_norm(&l) {
+        return None;
+    }
+    // the point closest to the origin and one unit along the line
+    let a = Point::new(-l[2] * l[0] / (len * len), -l[2] * l[1] / (len * len));
+    let b = Point::new(a.get_x() - l[1] / len, a.get_y() + l[0] / len);
+    Some(Line::new(a, b))
+}
+
+// halves parameter intervals until the midpoint is within tolerance of the chord
+fn sample_curve(curve: &dyn Fn(f64) -> Point, t0: f64, t1: f64, tolerance: f64) -> Vec<Point> {
+    let n = 32;
+    let mut points = vec![curve(t0)];
+    for i in 0..n {
+        let a = t0 + (t1 - t0) * i as f64 / n as f64;
+        let b = t0 + (t1 - t0) * (i + 1) as f64 / n as f64;
+        refine(curve, a, b, tolerance, 0, &mut points);
+    }
+    points
+}
+
+fn refine(
+    curve: &dyn Fn(f64) -> Point,
+    t0: f64,
+    t1: f64,
+    tolerance: f64,
+    depth: u32,
+    points: &mut Vec<Point>,
+) {
+    let (a, b) = (curve(t0), curve(t1));
+    let mid = curve((t0 + t1) / 2.0);
+    let chord = Point::new((a.get_x() + b.get_x()) / 2.0, (a.get_y() + b.get_y()) / 2.0);
+    if get_distance(mid, chord) > tolerance && depth < MAX_SAMPLE_DEPTH {
+        refine(curve, t0, (t0 + t1) / 2.0, tolerance, depth + 1, points);
+        refine(curve, (t0 + t1) / 2.0, t1, tolerance, depth + 1, points);
+    } else {
+        points.push(b);
+    }
+}
+
+fn get_norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+// gaussian elimination with partial pivoting
+fn get_determinant(mut m: Vec<Vec<f64>>) -> f64 {
+    let n = m.len();
+    let mut det = 1.0;
+    for i in 0..n {
+        let pivot = (i..n)
+            .max_by(|&a, &b| m[a][i].abs().total_cmp(&m[b][i].abs()))
+            .unwrap();
+        if m[pivot][i] == 0.0 {
+            return 0.0;
+        }
+        if pivot != i {
+            m.swap(pivot, i);
+            det = -det;
+        }
+        det *= m[i][i];
+        let (top, rest) = m.split_at_mut(i + 1);
+        let pivot_row = &top[i];
+        for row in rest.iter_mut() {
+            let factor = row[i] / pivot_row[i];
+            for (v, p) in row.iter_mut().zip(pivot_row.iter()).skip(i) {
+                *v -= factor * p;
+            }
+        }
+    }
+    det
+}
+
+pub fn get_determinant3(m: [[f64; 3]; 3]) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+fn get_adjugate(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut adj = [[0.0; 3]; 3];
+    for (i, row) in adj.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            // the cofactor of m[j][i], the cyclic indices take care of the sign
+            let (r1, r2) = ((j + 1) % 3, (j + 2) % 3);
+            let (c1, c2) = ((i + 1) % 3, (i + 2) % 3);
+            *v = m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1];
+        }
+    }
+    adj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conic(points: [(f64, f64); 5]) -> Option<Conic> {
+        Conic::from_five_points(points.map(|(x, y)| Point::new(x, y)))
+    }
+
+    #[test]
+    fn five_points() {
+        let s = f64::consts::FRAC_1_SQRT_2;
+        let circle = conic([(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0), (s, s)]).unwrap();
+        assert_eq!(circle.get_kind(), ConicKind::Ellipse);
+        assert!(circle.eval(Point::new(-s, s)).abs() < 1e-12);
+        assert!(circle.eval(Point::new(0.0, 0.0)) * circle.eval(Point::new(2.0, 0.0)) < 0.0);
+        let (center, major, minor) = circle.get_ellipse_axes().unwrap();
+        assert!(get_distance(center, Point::new(0.0, 0.0)) < 1e-9);
+        assert!((major - 1.0).abs() < 1e-9 && (minor - 1.0).abs() < 1e-9);
+        // four points on a line leave any line through the fifth
+        assert!(conic([(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (0.0, 1.0)]).is_none());
+        assert!(conic([(0.0, 0.0), (0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (0.0, 2.0)]).is_none());
+    }
+
+    #[test]
+    fn kinds() {
+        let parabola = conic([(-2.0, 4.0), (-1.0, 1.0), (0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]);
+        assert_eq!(parabola.unwrap().get_kind(), ConicKind::Parabola);
+        let hyperbola = conic([
+            (1.0, 1.0),
+            (2.0, 0.5),
+            (-1.0, -1.0),
+            (4.0, 0.25),
+            (-2.0, -0.5),
+        ]);
+        assert_eq!(hyperbola.unwrap().get_kind(), ConicKind::Hyperbola);
+        let lines = conic([(0.0, 1.0), (0.0, 2.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)]);
+        assert_eq!(lines.unwrap().get_kind(), ConicKind::Degenerate);
+        assert_eq!(
+            classify(&[1.0, 0.0, 4.0, 0.0, 0.0, -4.0]),
+            ConicKind::Ellipse
+        );
+        assert_eq!(
+            classify(&[1.0, 0.0, -1.0, 0.0, 0.0, 0.0]),
+            ConicKind::Degenerate
+        );
+    }
+
+    // l as a multiple of (a, b, c), the sign and the length don't matter
+    fn is_line(l: [f64; 3], a: f64, b: f64, c: f64) -> bool {
+        let cross = [
+            l[1] * c - l[2] * b,
+            l[2] * a - l[0] * c,
+            l[0] * b - l[1] * a,
+        ];
+        get_norm(&cross) < 1e-9 * get_norm(&l)
+    }
+
+    #[test]
+    fn kinds_away_from_the_origin() {
+        let circle = |x: f64, y: f64, r: f64| {
+            Conic::from_circle(Circle::new(Point::new(x, y), Point::new(x + r, y)))
+        };
+        for (x, y, r) in [
+            (50.0, 50.0, 1.0),
+            (300.0, 300.0, 5.0),
+            (0.0, 0.0, 1000.0),
+            (-2000.0, 1500.0, 0.01),
+            (0.001, 0.0, 1e-4),
+        ] {
+            assert_eq!(circle(x, y, r).get_kind(), ConicKind::Ellipse);
+        }
+
+        // five points of a small circle far out
+        let on_circle = |x: f64, y: f64, r: f64| {
+            [0.0, 1.0, 2.0, 3.5, 5.0].map(|t: f64| (x + r * t.cos(), y + r * t.sin()))
+        };
+        for (x, y, r) in [(20.0, 20.0, 0.5), (-500.0, 800.0, 2.0), (0.01, 0.02, 1e-3)] {
+            let c = conic(on_circle(x, y, r)).unwrap();
+            assert_eq!(c.get_kind(), ConicKind::Ellipse);
+            let (center, major, minor) = c.get_ellipse_axes().unwrap();
+            assert!(get_distance(center, Point::new(x, y)) < 1e-6 * r);
+            assert!((major - r).abs() < 1e-6 * r && (minor - r).abs() < 1e-6 * r);
+        }
+
+        // y - 100 = (x - 100)² and a steep small one
+        let parabola = [-2.0, -1.0, 0.0, 1.0, 2.0].map(|t| (100.0 + t, 100.0 + t * t));
+        assert_eq!(conic(parabola).unwrap().get_kind(), ConicKind::Parabola);
+        let parabola = [-2.0, -1.0, 0.0, 1.0, 2.0].map(|t| (1e-3 * t, 1e-3 * t * t));
+        assert_eq!(conic(parabola).unwrap().get_kind(), ConicKind::Parabola);
+
+        // (x - 400)(y + 300) = 1000
+        let hyperbola = [10.0, 20.0, 50.0, -10.0, -40.0].map(|t| (400.0 + t, -300.0 + 1000.0 / t));
+        assert_eq!(conic(hyperbola).unwrap().get_kind(), ConicKind::Hyperbola);
+
+        // lines crossing at (40, -30), and x = 49 and x = 51
+        let lines = [
+            (41.0, -30.0),
+            (45.0, -30.0),
+            (40.0, -29.0),
+            (40.0, -20.0),
+            (40.0, -35.0),
+        ];
+        assert_eq!(conic(lines).unwrap().get_kind(), ConicKind::Degenerate);
+        assert_eq!(
+            Conic::from_coefficients([1.0, 0.0, 0.0, -100.0, 0.0, 2499.0]).get_kind(),
+            ConicKind::Degenerate
+        );
+    }
+
+    #[test]
+    fn degenerate_splits() {
+        // x² - y² = 0 is x - y = 0 and x + y = 0
+        let lines = split_degenerate([[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 0.0]]);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().any(|&l| is_line(l, 1.0, -1.0, 0.0)));
+        assert!(lines.iter().any(|&l| is_line(l, 1.0, 1.0, 0.0)));
+        // (x - 1) y = 0
+        let lines = split_degenerate([[0.0, 0.5, 0.0], [0.5, 0.0, -0.5], [0.0, -0.5, 0.0]]);
+        assert!(lines.iter().any(|&l| is_line(l, 1.0, 0.0, -1.0)));
+        assert!(lines.iter().any(|&l| is_line(l, 0.0, 1.0, 0.0)));
+        // (x - 2)² = 0 is a double line
+        let lines = split_degenerate([[1.0, 0.0, -2.0], [0.0, 0.0, 0.0], [-2.0, 0.0, 4.0]]);
+        assert_eq!(lines.len(), 1);
+        assert!(is_line(lines[0], 1.0, 0.0, -2.0));
+        // x² + y² = 0 is only the origin
+        assert!(split_degenerate([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]).is_empty());
+    }
+
+    #[test]
+    fn samples_reach_the_bounds() {
+        let hyperbola = Conic::from_coefficients([0.0, 1.0, 0.0, 0.0, 0.0, -1.0]);
+        for bounds in [(-10.0, -10.0, 10.0, 10.0), (90.0, -10.0, 110.0, 10.0)] {
+            let branches = hyperbola.sample(0.01, bounds);
+            assert_eq!(branches.len(), 2);
+            let max_x = branches
+                .iter()
+                .flatten()
+                .map(|p| p.get_x())
+                .fold(f64::MIN, f64::max);
+            assert!(max_x >= bounds.2);
+            for p in branches.iter().flatten() {
+                assert!(hyperbola.eval(*p).abs() < 1e-9 * (1.0 + p.get_x().abs()));
+            }
+        }
+    }
+}
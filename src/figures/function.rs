@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::expr::{self, Expr};
+
+use super::point::Point;
+
+// how often a sampling interval may be halved while looking for detail
+const MAX_SAMPLE_DEPTH: u32 = 12;
+
+// the graph of y = f(x), which is sampled over whatever x range is visible
+#[derive(Debug, Clone)]
+pub struct FunctionGraph {
+    source: String,
+    expr: Expr,
+    params: HashMap<String, f64>,
+}
+
+impl FunctionGraph {
+    pub fn new(source: &str, params: HashMap<String, f64>) -> Result<FunctionGraph, String> {
+        let expr = expr::parse(source)?;
+        for var in expr.get_variables() {
+            if var != "x" && !params.contains_key(&var) {
+                return Err(format!("unknown variable '{}'", var));
+            }
+        }
+        Ok(FunctionGraph {
+            source: String::from(source),
+            expr,
+            params,
+        })
+    }
+
+    // None where the function isn't defined
+    pub fn eval(&self, x: f64) -> Option<f64> {
+        let lookup = |name: &str| {
+            if name == "x" {
+                Some(x)
+            } else {
+                self.params.get(name).copied()
+            }
+        };
+        match self.expr.eval(&lookup) {
+            Ok(y) if y.is_finite() => Some(y),
+            _ => None,
+        }
+    }
+
+    // polylines through the graph from x = a to b, split where it is undefined or jumps;
+    // intervals are halved until the midpoint is within tolerance of the chord
+    pub fn sample(&self, tolerance: f64, (a, b): (f64, f64)) -> Vec<Vec<Point>> {
+        let n = 64;
+        let mut pieces: Vec<Vec<Point>> = vec![vec![]];
+        let mut prev = (a, self.eval(a));
+        if let Some(y) = prev.1 {
+            pieces[0].push(Point::new(a, y));
+        }
+        for i in 1..=n {
+            let x = a + (b - a) * i as f64 / n as f64;
+            let next = (x, self.eval(x));
+            self.refine(prev, next, tolerance, 0, &mut pieces);
+            prev = next;
+        }
+        pieces.into_iter().filter(|p| p.len() > 1).collect()
+    }
+
+    fn refine(
+        &self,
+        p0: (f64, Option<f64>),
+        p1: (f64, Option<f64>),
+        tolerance: f64,
+        depth: u32,
+        pieces: &mut Vec<Vec<Point>>,
+    ) {
+        let mid_x = (p0.0 + p1.0) / 2.0;
+        let pm = (mid_x, self.eval(mid_x));
+        let smooth = match (p0.1, pm.1, p1.1) {
+            (Some(y0), Some(ym), Some(y1)) => (ym - (y0 + y1) / 2.0).abs() <= tolerance,
+            (None, None, None) => true,
+            _ => false,
+        };
+        if !smooth && depth < MAX_SAMPLE_DEPTH {
+            self.refine(p0, pm, tolerance, depth + 1, pieces);
+            self.refine(pm, p1, tolerance, depth + 1, pieces);
+            return;
+        }
+        match (p0.1, p1.1) {
+            // still not smooth this close together means a pole like in tan(x)
+            (Some(y0), Some(y1)) if smooth || (y1 - y0).abs() <= 100.0 * tolerance => {
+                pieces.last_mut().unwrap().push(Point::new(p1.0, y1));
+            }
+            (_, Some(y1)) => pieces.push(vec![Point::new(p1.0, y1)]),
+            (_, None) => {
+                if !pieces.last().unwrap().is_empty() {
+                    pieces.push(vec![]);
+                }
+            }
+        }
+    }
+
+    pub fn set_params(&mut self, params: HashMap<String, f64>) {
+        self.params = params;
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_params(&self) -> &HashMap<String, f64> {
+        &self.params
+    }
+}
@@ -0,0 +1,249 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::expr::{self, Expr};
+use crate::utils::get_distance;
+
+use super::point::Point;
+
+// how often a sampling interval may be halved while looking for detail
+const MAX_SAMPLE_DEPTH: u32 = 14;
+// the most the direction may turn between two sampled segments, in radians
+const MAX_TURN: f64 = 0.05;
+
+#[derive(Debug, Clone)]
+enum Definition {
+    Cartesian(Expr, Expr), // x(t), y(t)
+    Polar(Expr),           // r(t) where t is the angle
+}
+
+// a curve (x(t), y(t)) or r(t) for t in a range
+#[derive(Debug, Clone)]
+pub struct Parametric {
+    source: String,
+    definition: Definition,
+    params: HashMap<String, f64>,
+    range: (f64, f64),
+}
+
+impl Parametric {
+    pub fn new(
+        x_source: &str,
+        y_source: &str,
+        params: HashMap<String, f64>,
+        range: (f64, f64),
+    ) -> Result<Parametric, String> {
+        let x = expr::parse(x_source)?;
+        let y = expr::parse(y_source)?;
+        check_variables(&x, &params, false)?;
+        check_variables(&y, &params, false)?;
+        Ok(Parametric {
+            source: format!("({}, {})", x_source, y_source),
+            definition: Definition::Cartesian(x, y),
+            params,
+            range,
+        })
+    }
+
+    // the angle can be written as t, theta or θ
+    pub fn polar(
+        r_source: &str,
+        params: HashMap<String, f64>,
+        range: (f64, f64),
+    ) -> Result<Parametric, String> {
+        let r = expr::parse(r_source)?;
+        check_variables(&r, &params, true)?;
+        Ok(Parametric {
+            source: format!("r = {}", r_source),
+            definition: Definition::Polar(r),
+            params,
+            range,
+        })
+    }
+
+    // None where the curve isn't defined
+    pub fn eval(&self, t: f64) -> Option<Point> {
+        let polar = self.is_polar();
+        let lookup = |name: &str| {
+            if name == "t" || (polar && is_angle_name(name)) {
+                Some(t)
+            } else {
+                self.params.get(name).copied()
+            }
+        };
+        let p = match &self.definition {
+            Definition::Cartesian(x, y) => Point::new(x.eval(&lookup).ok()?, y.eval(&lookup).ok()?),
+            Definition::Polar(r) => {
+                let r = r.eval(&lookup).ok()?;
+                Point::new(r * t.cos(), r * t.sin())
+            }
+        };
+        if p.get_x().is_finite() && p.get_y().is_finite() {
+            Some(p)
+        } else {
+            None
+        }
+    }
+
+    // polylines through the curve, split where it is undefined or jumps;
+    // intervals are halved while the curve bends away from the chord or turns too sharply
+    pub fn sample(&self, tolerance: f64) -> Vec<Vec<Point>> {
+        let (a, b) = self.range;
+        let n = 64;
+        let mut pieces: Vec<Vec<Point>> = vec![vec![]];
+        let mut prev = (a, self.eval(a));
+        if let Some(p) = prev.1 {
+            pieces[0].push(p);
+        }
+        for i in 1..=n {
+            let t = a + (b - a) * i as f64 / n as f64;
+            let next = (t, self.eval(t));
+            self.refine(prev, next, tolerance, 0, &mut pieces);
+            prev = next;
+        }
+        pieces.into_iter().filter(|p| p.len() > 1).collect()
+    }
+
+    fn refine(
+        &self,
+        p0: (f64, Option<Point>),
+        p1: (f64, Option<Point>),
+        tolerance: f64,
+        depth: u32,
+        pieces: &mut Vec<Vec<Point>>,
+    ) {
+        let mid_t = (p0.0 + p1.0) / 2.0;
+        let pm = (mid_t, self.eval(mid_t));
+        let smooth = match (p0.1, pm.1, p1.1) {
+            (Some(a), Some(m), Some(b)) => {
+                let chord =
+                    Point::new((a.get_x() + b.get_x()) / 2.0, (a.get_y() + b.get_y()) / 2.0);
+                get_distance(m, chord) <= tolerance
+                    && (get_distance(a, b) <= tolerance || get_turn(a, m, b) <= MAX_TURN)
+            }
+            (None, None, None) => true,
+            _ => false,
+        };
+        if !smooth && depth < MAX_SAMPLE_DEPTH {
+            self.refine(p0, pm, tolerance, depth + 1, pieces);
+            self.refine(pm, p1, tolerance, depth + 1, pieces);
+            return;
+        }
+        match (p0.1, p1.1) {
+            // still a long way apart this close together means the curve jumps
+            (Some(a), Some(b)) if smooth || get_distance(a, b) <= 100.0 * tolerance => {
+                pieces.last_mut().unwrap().push(b);
+            }
+            (_, Some(b)) => pieces.push(vec![b]),
+            (_, None) => {
+                if !pieces.last().unwrap().is_empty() {
+                    pieces.push(vec![]);
+                }
+            }
+        }
+    }
+
+    pub fn is_polar(&self) -> bool {
+        matches!(self.definition, Definition::Polar(_))
+    }
+
+    pub fn set_params(&mut self, params: HashMap<String, f64>) {
+        self.params = params;
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_params(&self) -> &HashMap<String, f64> {
+        &self.params
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        self.range
+    }
+}
+
+fn is_angle_name(name: &str) -> bool {
+    name == "theta" || name == "θ"
+}
+
+fn check_variables(e: &Expr, params: &HashMap<String, f64>, polar: bool) -> Result<(), String> {
+    for var in e.get_variables() {
+        if var != "t" && !(polar && is_angle_name(&var)) && !params.contains_key(&var) {
+            return Err(format!("unknown variable '{}'", var));
+        }
+    }
+    Ok(())
+}
+
+// how far the direction turns going from a over m to b
+fn get_turn(a: Point, m: Point, b: Point) -> f64 {
+    let first = (m.get_y() - a.get_y()).atan2(m.get_x() - a.get_x());
+    let second = (b.get_y() - m.get_y()).atan2(b.get_x() - m.get_x());
+    let turn = (second - first).abs() % (2.0 * std::f64::consts::PI);
+    turn.min(2.0 * std::f64::consts::PI - turn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAU: f64 = std::f64::consts::TAU;
+
+    #[test]
+    fn samples_stay_on_the_curve() {
+        let params: HashMap<String, f64> = [(String::from("a"), 3.0)].into_iter().collect();
+        let circle = Parametric::new("a cos(t) + 1", "a sin(t)", params, (0.0, TAU)).unwrap();
+        let pieces = circle.sample(1e-3);
+        assert_eq!(pieces.len(), 1);
+        let center = Point::new(1.0, 0.0);
+        assert!(pieces[0]
+            .iter()
+            .all(|p| (get_distance(*p, center) - 3.0).abs() < 1e-9));
+        // closed, and no chord is far from the circle
+        let (first, last) = (pieces[0][0], *pieces[0].last().unwrap());
+        assert!(get_distance(first, last) < 1e-9);
+        assert!(pieces[0].windows(2).all(|w| {
+            let m = Point::new(
+                (w[0].get_x() + w[1].get_x()) / 2.0,
+                (w[0].get_y() + w[1].get_y()) / 2.0,
+            );
+            3.0 - get_distance(m, center) <= 1e-3
+        }));
+    }
+
+    #[test]
+    fn polar_curves() {
+        // a cardioid, theta and θ being the same as t
+        for source in ["1 + cos(t)", "1 + cos(theta)", "1 + cos(θ)"] {
+            let cardioid = Parametric::polar(source, HashMap::new(), (0.0, TAU)).unwrap();
+            assert!(cardioid.is_polar());
+            let p = cardioid.eval(TAU / 4.0).unwrap();
+            assert!(p.get_x().abs() < 1e-12 && (p.get_y() - 1.0).abs() < 1e-12);
+            let pieces = cardioid.sample(1e-3);
+            assert_eq!(pieces.len(), 1);
+            for p in &pieces[0] {
+                let t = p.get_y().atan2(p.get_x());
+                let r = (p.get_x().powi(2) + p.get_y().powi(2)).sqrt();
+                assert!((r - (1.0 + t.cos())).abs() < 1e-9);
+            }
+        }
+        assert!(Parametric::polar("1 + x", HashMap::new(), (0.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn gaps_and_jumps_split_the_curve() {
+        // undefined for t <= 0
+        let curve = Parametric::new("t", "ln(t)", HashMap::new(), (-1.0, 1.0)).unwrap();
+        let pieces = curve.sample(1e-3);
+        assert_eq!(pieces.len(), 1);
+        assert!(pieces[0].iter().all(|p| p.get_x() > 0.0));
+        // a pole at t = 0 leaves one piece on either side
+        let hyperbola = Parametric::new("t", "1 / t", HashMap::new(), (-1.0, 1.0)).unwrap();
+        let pieces = hyperbola.sample(1e-3);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[0].iter().all(|p| p.get_x() < 0.0));
+        assert!(pieces[1].iter().all(|p| p.get_x() > 0.0));
+    }
+}
@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+use super::{bezier::Bezier, point::Point};
+
+// a uniform catmull-rom spline that passes through all of its points,
+// stored as one cubic bezier per segment so it can reuse the bezier math
+#[derive(Debug, Clone)]
+pub struct Spline {
+    points: Vec<Point>,
+    segments: Vec<Bezier>,
+    length: f64,
+}
+
+impl Spline {
+    pub fn new(points: Vec<Point>) -> Spline {
+        let segments = get_segments(&points);
+        let length = segments.iter().map(|s| s.get_length()).sum();
+        Spline {
+            points,
+            segments,
+            length,
+        }
+    }
+
+    pub fn set_point(&mut self, i: usize, p: Point) {
+        self.points[i] = p;
+        *self = Spline::new(self.points.clone());
+    }
+
+    pub fn move_spline(&mut self, x: f64, y: f64) {
+        for p in &mut self.points {
+            p.add_point(Point::new(x, y));
+        }
+        *self = Spline::new(self.points.clone());
+    }
+
+    pub fn get_points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn get_segments(&self) -> &[Bezier] {
+        &self.segments
+    }
+
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+}
+
+// the end points are repeated so the first and last segment have a neighbour
+fn get_segments(points: &[Point]) -> Vec<Bezier> {
+    let n = points.len();
+    let mut segments = Vec::new();
+    for i in 0..n.saturating_sub(1) {
+        let p0 = points[i.saturating_sub(1)];
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        let p3 = points[(i + 2).min(n - 1)];
+        let c1 = Point::new(
+            p1.get_x() + (p2.get_x() - p0.get_x()) / 6.0,
+            p1.get_y() + (p2.get_y() - p0.get_y()) / 6.0,
+        );
+        let c2 = Point::new(
+            p2.get_x() - (p3.get_x() - p1.get_x()) / 6.0,
+            p2.get_y() - (p3.get_y() - p1.get_y()) / 6.0,
+        );
+        segments.push(Bezier::cubic(p1, c1, c2, p2));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::get_distance;
+
+    #[test]
+    fn passes_through_its_points() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 2.0),
+            Point::new(3.0, -1.0),
+            Point::new(4.0, 0.5),
+        ];
+        let spline = Spline::new(points.clone());
+        let segments = spline.get_segments();
+        assert_eq!(segments.len(), 3);
+        for (i, segment) in segments.iter().enumerate() {
+            assert!(get_distance(segment.get_start(), points[i]) < 1e-12);
+            assert!(get_distance(segment.get_end(), points[i + 1]) < 1e-12);
+        }
+        // no corners where the segments meet
+        for pair in segments.windows(2) {
+            let (a, b) = (pair[0].get_controls(), pair[1].get_controls());
+            let incoming = (a[3].get_x() - a[2].get_x(), a[3].get_y() - a[2].get_y());
+            let outgoing = (b[1].get_x() - b[0].get_x(), b[1].get_y() - b[0].get_y());
+            assert!((incoming.0 - outgoing.0).abs() < 1e-12);
+            assert!((incoming.1 - outgoing.1).abs() < 1e-12);
+        }
+        assert!(Spline::new(vec![Point::new(1.0, 1.0)])
+            .get_segments()
+            .is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+#![allow(dead_code)]
+use fltk::dialog::alert_default;
+use fltk::enums::{Align, CallbackTrigger, Color, FrameType};
+use fltk::prelude::{BrowserExt, ButtonExt, GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{
+    browser::HoldBrowser,
+    button::CheckButton,
+    frame::Frame,
+    group::Group,
+    input::{FloatInput, Input},
+};
+
+use crate::canvas::Canvas;
+use crate::tooltip::format_number;
+
+// more numbers than any figure with a handful of control points has
+const MAX_PROPERTIES: usize = 12;
+const ROW_HEIGHT: i32 = 26;
+const LABEL_WIDTH: i32 = 60;
+
+// a side panel with every object in the scene and the numbers of the selected one,
+// which can be typed in instead of clicking pixels, and how it is drawn
+#[derive(Clone)]
+pub struct Inspector {
+    group: Group,
+    list: HoldBrowser,
+    visible: CheckButton,
+    style: Input,
+    heading: Frame,
+    inputs: Vec<FloatInput>,
+    canvas: Canvas,
+}
+
+impl Inspector {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let mut group = Group::new(x, y, w, h, None);
+        group.set_frame(FrameType::FlatBox);
+        group.set_color(Color::Background2);
+
+        let list_height = h / 2 - ROW_HEIGHT;
+        let mut list = HoldBrowser::new(x + 5, y + 5, w - 10, list_height, None);
+        list.set_column_char('\t');
+        list.set_column_widths(&[(w - 10) / 5, (w - 10) * 2 / 5, (w - 10) / 5]);
+
+        let mut row = y + 10 + list_height;
+        let visible = CheckButton::new(x + 5, row, w - 10, ROW_HEIGHT, "Visible");
+        row += ROW_HEIGHT;
+        let mut style = Input::new(
+            x + LABEL_WIDTH,
+            row,
+            w - LABEL_WIDTH - 5,
+            ROW_HEIGHT,
+            "Style",
+        );
+        style.set_trigger(CallbackTrigger::EnterKeyAlways);
+        style.set_tooltip("color=#rrggbb fill=#rrggbb|none width=3 dash=solid|dashed|dotted|dashdot marker=dot|circle|cross|square|diamond size=3 opacity=1");
+        row += ROW_HEIGHT + 2;
+        let mut heading = Frame::new(x + 5, row, w - 10, ROW_HEIGHT, None);
+        heading.set_align(Align::Left | Align::Inside);
+        row += ROW_HEIGHT;
+
+        let mut inputs = vec![];
+        for _ in 0..MAX_PROPERTIES {
+            if row + ROW_HEIGHT > y + h {
+                break;
+            }
+            let mut input =
+                FloatInput::new(x + LABEL_WIDTH, row, w - LABEL_WIDTH - 5, ROW_HEIGHT, None);
+            input.set_trigger(CallbackTrigger::EnterKeyAlways);
+            input.hide();
+            inputs.push(input);
+            row += ROW_HEIGHT + 2;
+        }
+        group.end();
+        group.resizable(&list);
+        group.hide();
+
+        let mut inspector = Inspector {
+            group,
+            list,
+            visible,
+            style,
+            heading,
+            inputs,
+            canvas,
+        };
+
+        inspector.list.set_callback({
+            let mut inspector = inspector.clone();
+            move |_| inspector.show_properties()
+        });
+        inspector.visible.set_callback({
+            let mut inspector = inspector.clone();
+            move |b| {
+                if let Some(i) = inspector.get_selected() {
+                    inspector.canvas.set_visible(i, b.value());
+                }
+            }
+        });
+        inspector.style.set_callback({
+            let mut inspector = inspector.clone();
+            move |input| inspector.apply_style(&input.value())
+        });
+        for (j, input) in inspector.inputs.clone().iter_mut().enumerate() {
+            input.set_callback({
+                let mut inspector = inspector.clone();
+                move |input| inspector.apply(j, &input.value())
+            });
+        }
+        inspector.canvas.add_on_change({
+            let mut inspector = inspector.clone();
+            move || inspector.refresh()
+        });
+        inspector
+    }
+
+    // shows the panel if it is hidden and the other way around
+    pub fn toggle(&mut self) {
+        if self.group.visible() {
+            self.group.hide();
+        } else {
+            self.refresh();
+            self.group.show();
+        }
+        // the canvas beside the panel grows or shrinks with it
+        if let Some(mut window) = self.group.window() {
+            window.redraw();
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.group.visible()
+    }
+
+    // deletes the object selected in the list with everything built on it, false when
+    // nothing is selected
+    pub fn delete_selected(&mut self) -> bool {
+        match self.get_selected() {
+            Some(i) => {
+                self.canvas.delete(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // only the list grows, the rows for the selected object keep their height
+    // for the layout to dock it
+    pub fn get_group(&self) -> &Group {
+        &self.group
+    }
+
+    // lists the objects again, keeping the selection
+    pub fn refresh(&mut self) {
+        let selected = self.list.value();
+        self.list.clear();
+        for object in self.canvas.get_objects() {
+            let (r, g, b) = object.get_color();
+            let mut kind = String::from(object.get_kind());
+            if object.is_dependent() {
+                kind.push('*');
+            }
+            self.list.add(&format!(
+                "{}\t{}\t#{:02x}{:02x}{:02x}\t{}",
+                object.get_name(),
+                kind,
+                r,
+                g,
+                b,
+                if object.is_visible() {
+                    "shown"
+                } else {
+                    "hidden"
+                }
+            ));
+        }
+        if selected > 0 && selected <= self.list.size() {
+            self.list.select(selected);
+        }
+        self.show_properties();
+    }
+
+    // the index in the scene, browser lines count from 1
+    fn get_selected(&self) -> Option<usize> {
+        match self.list.value() {
+            0 => None,
+            line => Some(line as usize - 1),
+        }
+    }
+
+    fn show_properties(&mut self) {
+        let objects = self.canvas.get_objects();
+        let object = self
+            .get_selected()
+            .and_then(|i| objects.get(i).map(|o| (i, o)));
+        let (i, object) = match object {
+            Some(found) => found,
+            None => {
+                self.heading.set_label("");
+                self.visible.deactivate();
+                self.style.set_value("");
+                self.style.deactivate();
+                for input in self.inputs.iter_mut() {
+                    input.hide();
+                }
+                return;
+            }
+        };
+        self.visible.activate();
+        self.visible.set_value(object.is_visible());
+        self.style.activate();
+        if !self.style.has_focus() {
+            if let Some(style) = self.canvas.get_style(i) {
+                self.style.set_value(&style.to_text());
+            }
+        }
+        // dependent objects follow their parents, so their numbers are only shown
+        if object.is_dependent() {
+            self.heading.set_label(&format!(
+                "{} {} (dependent)",
+                object.get_kind(),
+                object.get_name()
+            ));
+        } else {
+            self.heading
+                .set_label(&format!("{} {}", object.get_kind(), object.get_name()));
+        }
+        let properties = self.canvas.get_properties(i);
+        let precision = self.canvas.get_precision().max(2);
+        for (j, input) in self.inputs.iter_mut().enumerate() {
+            match properties.get(j) {
+                Some((name, value)) => {
+                    input.set_label(name);
+                    // what is being typed stays until it is entered
+                    if !input.has_focus() {
+                        input.set_value(&format_number(*value, precision));
+                    }
+                    input.set_readonly(object.is_dependent());
+                    input.show();
+                }
+                None => input.hide(),
+            }
+        }
+        self.group.redraw();
+    }
+
+    fn apply(&mut self, j: usize, text: &str) {
+        let i = match self.get_selected() {
+            Some(i) => i,
+            None => return,
+        };
+        let result = match text.trim().parse::<f64>() {
+            Ok(value) => self.canvas.set_property(i, j, value),
+            Err(_) => Err(format!("'{}' is not a number", text)),
+        };
+        if let Err(e) = result {
+            alert_default(&e);
+            self.show_properties();
+        }
+    }
+
+    // "width=2 dash=dotted", whatever isn't mentioned stays
+    fn apply_style(&mut self, text: &str) {
+        let i = match self.get_selected() {
+            Some(i) => i,
+            None => return,
+        };
+        let result = match self.canvas.get_style(i) {
+            Some(style) => style.parse(text),
+            None => Err(String::from("there is no such object")),
+        };
+        match result {
+            Ok(style) => self.canvas.set_style(i, style),
+            Err(e) => {
+                alert_default(&e);
+                self.show_properties();
+            }
+        }
+    }
+}
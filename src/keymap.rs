@@ -0,0 +1,266 @@
+use fltk::enums::{EventState, Key, Shortcut};
+
+use crate::config;
+use crate::toolbar::TOOLS;
+
+const FILE: &str = "keys.conf";
+
+// what there is besides the tools, named by where it is in the menus, with its default key
+const DEFAULTS: [(&str, &str); 21] = [
+    ("File/Open…", "Ctrl+o"),
+    ("File/Export image…", "Ctrl+e"),
+    ("File/Quit", "Ctrl+q"),
+    ("Edit/Undo", "Ctrl+z"),
+    ("Edit/Cancel figure", "Escape"),
+    ("Edit/Delete", "Delete"),
+    ("Edit/Color…", "Ctrl+k"),
+    ("Edit/Fill", "Ctrl+f"),
+    ("Edit/Tolerance…", "none"),
+    ("Edit/Angle tolerance…", "none"),
+    ("Edit/Decimals…", "none"),
+    ("Edit/Clear", "Ctrl+Delete"),
+    ("Edit/Preferences…", "Ctrl+,"),
+    ("View/Zoom in", "Ctrl+="),
+    ("View/Zoom out", "Ctrl+-"),
+    ("View/Original size", "Ctrl+0"),
+    ("View/Objects", "Ctrl+1"),
+    ("View/Algebra", "Ctrl+2"),
+    ("View/Protocol", "Ctrl+3"),
+    ("Construct/Plot…", "Ctrl+p"),
+    ("Help/Commands", "F1"),
+];
+const MENUS: [&str; 6] = ["File", "Edit", "View", "Construct", "Measure", "Help"];
+
+// written before the key, in this order
+const MODIFIERS: [(&str, Shortcut, EventState); 3] = [
+    ("Ctrl", Shortcut::Ctrl, EventState::Ctrl),
+    ("Alt", Shortcut::Alt, EventState::Alt),
+    ("Shift", Shortcut::Shift, EventState::Shift),
+];
+// the keys which aren't written as the character they type
+const NAMED_KEYS: [(&str, Key); 26] = [
+    ("Delete", Key::Delete),
+    ("BackSpace", Key::BackSpace),
+    ("Escape", Key::Escape),
+    ("Enter", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Insert", Key::Insert),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+];
+
+// the key of every action, written like "Ctrl+Shift+z", "F2" or "none"
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    keys: Vec<(String, String)>, // action and key, in the order of the menus
+}
+
+impl Keymap {
+    pub fn new() -> Keymap {
+        let mut keys: Vec<(String, String)> = TOOLS
+            .iter()
+            .map(|(_, label, menu, key, _)| (format!("{}/{}", menu, label), key.to_string()))
+            .chain(DEFAULTS.iter().map(|(a, k)| (a.to_string(), k.to_string())))
+            .collect();
+        keys.sort_by_key(|(action, _)| MENUS.iter().position(|m| action.starts_with(m)));
+        Keymap { keys }
+    }
+
+    // the defaults with what the config file changes, anything in it that doesn't make sense
+    // is left out
+    pub fn load() -> Keymap {
+        Keymap::from_entries(config::read(FILE))
+    }
+
+    fn from_entries(entries: Vec<(String, String)>) -> Keymap {
+        let mut keymap = Keymap::new();
+        for (action, key) in entries {
+            keymap.set_key(&action, &key).ok();
+        }
+        keymap
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        config::write(
+            FILE,
+            "keyboard shortcuts of geoviz, action = key",
+            &self.keys,
+        )
+    }
+
+    pub fn get_keys(&self) -> &[(String, String)] {
+        &self.keys
+    }
+
+    pub fn get_key(&self, action: &str) -> &str {
+        self.keys
+            .iter()
+            .find(|(a, _)| a == action)
+            .map_or("none", |(_, key)| key)
+    }
+
+    pub fn get_shortcut(&self, action: &str) -> Shortcut {
+        parse_key(self.get_key(action)).map_or(Shortcut::None, |(shortcut, _)| shortcut)
+    }
+
+    // gives the action the key, which one had it before loses it and is returned
+    pub fn set_key(&mut self, action: &str, key: &str) -> Result<Option<String>, String> {
+        let (_, key) = parse_key(key)?;
+        let i = self
+            .keys
+            .iter()
+            .position(|(a, _)| a == action)
+            .ok_or_else(|| format!("there is no action '{}'", action))?;
+        let mut previous = None;
+        if key != "none" {
+            for (a, k) in self.keys.iter_mut() {
+                if *k == key && a != action {
+                    *k = String::from("none");
+                    previous = Some(a.clone());
+                }
+            }
+        }
+        self.keys[i].1 = key;
+        Ok(previous)
+    }
+}
+
+// the shortcut for "Ctrl+z", "Shift+F2" or "none", with the text written the usual way
+pub fn parse_key(text: &str) -> Result<(Shortcut, String), String> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("none") {
+        return Ok((Shortcut::None, String::from("none")));
+    }
+    let error = || format!("'{}' is not a key like Ctrl+z, Shift+a or F2", text);
+    // the key itself may be a +
+    let (modifiers, key) = match text.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => text.rsplit_once('+').unwrap_or(("", text)),
+    };
+    let modifiers: Vec<&str> = modifiers.split('+').filter(|m| !m.is_empty()).collect();
+    if modifiers.iter().any(|m| {
+        !MODIFIERS
+            .iter()
+            .any(|(name, ..)| name.eq_ignore_ascii_case(m))
+    }) {
+        return Err(error());
+    }
+    let mut shortcut = Shortcut::None;
+    let mut names = vec![];
+    for (name, modifier, _) in MODIFIERS {
+        if modifiers.iter().any(|m| name.eq_ignore_ascii_case(m)) {
+            shortcut = shortcut | modifier;
+            names.push(name.to_string());
+        }
+    }
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_whitespace() => {
+            let c = c.to_ascii_lowercase();
+            shortcut = shortcut | c;
+            names.push(c.to_string());
+        }
+        _ => {
+            let (name, key) = NAMED_KEYS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .ok_or_else(error)?;
+            shortcut = shortcut | *key;
+            names.push(name.to_string());
+        }
+    }
+    Ok((shortcut, names.join("+")))
+}
+
+// a key pressed with the modifiers held down, written like parse_key reads it; None for a
+// modifier on its own
+pub fn format_key(key: Key, state: EventState) -> Option<String> {
+    let name = match NAMED_KEYS.iter().find(|(_, k)| *k == key) {
+        Some((name, _)) => name.to_string(),
+        None => key
+            .to_char()
+            .filter(|c| c.is_ascii_graphic())?
+            .to_ascii_lowercase()
+            .to_string(),
+    };
+    let mut names: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(_, _, s)| state.contains(*s))
+        .map(|(name, ..)| name.to_string())
+        .collect();
+    names.push(name);
+    Some(names.join("+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_written_the_usual_way() {
+        let key = |text: &str| parse_key(text).map(|(_, name)| name);
+        assert_eq!(key("ctrl+Z"), Ok(String::from("Ctrl+z")));
+        assert_eq!(key("shift+ctrl+f2"), Ok(String::from("Ctrl+Shift+F2")));
+        assert_eq!(key("Ctrl++"), Ok(String::from("Ctrl++")));
+        assert_eq!(key("Ctrl+="), Ok(String::from("Ctrl+=")));
+        assert_eq!(key(" None "), Ok(String::from("none")));
+        assert_eq!(key(""), Ok(String::from("none")));
+        assert!(key("Hyper+x").is_err());
+        assert!(key("Ctrl+xy").is_err());
+        assert!(key("Ctrl+").is_err());
+        assert_eq!(parse_key("Ctrl+z").unwrap().0, Shortcut::Ctrl | 'z');
+        assert_eq!(parse_key("Escape").unwrap().0, Shortcut::None | Key::Escape);
+    }
+
+    #[test]
+    fn a_key_belongs_to_one_action() {
+        let mut keymap = Keymap::new();
+        assert_eq!(keymap.get_key("Edit/Undo"), "Ctrl+z");
+        assert_eq!(
+            keymap.set_key("File/Quit", "ctrl+z"),
+            Ok(Some(String::from("Edit/Undo")))
+        );
+        assert_eq!(keymap.get_key("File/Quit"), "Ctrl+z");
+        assert_eq!(keymap.get_key("Edit/Undo"), "none");
+        assert_eq!(keymap.set_key("Edit/Undo", "none"), Ok(None));
+        assert!(keymap.set_key("Edit/Nothing", "F3").is_err());
+        assert!(keymap.set_key("Edit/Undo", "Ctrl+").is_err());
+    }
+
+    #[test]
+    fn saved_keys_read_back() {
+        let mut keymap = Keymap::new();
+        keymap.set_key("View/Zoom in", "Alt+=").unwrap();
+        keymap.set_key("File/Open…", "none").unwrap();
+        keymap.set_key("Help/Commands", "Shift+F1").unwrap();
+        let text = config::format("keys", keymap.get_keys());
+        assert!(text.contains("View/Zoom in = Alt+=\n"));
+        let read = Keymap::from_entries(config::parse(&text));
+        assert_eq!(read.get_keys(), keymap.get_keys());
+
+        // what doesn't make sense is left out, the rest still counts
+        let text = "# keys\nEdit/Nothing = F3\nEdit/Undo = Hyper+u\nEdit/Delete = F4\nF5\n";
+        let read = Keymap::from_entries(config::parse(text));
+        assert_eq!(read.get_key("Edit/Undo"), "Ctrl+z");
+        assert_eq!(read.get_key("Edit/Delete"), "F4");
+        assert_eq!(read.get_keys().len(), Keymap::new().get_keys().len());
+    }
+}
@@ -1,155 +1,583 @@
 #![allow(dead_code, unused)]
 
 use std::borrow::BorrowMut;
+use std::fs;
+use std::mem::discriminant;
+use std::path::{Path, PathBuf};
 
-use fltk::dialog::{color_chooser, ColorMode};
+use fltk::dialog::{
+    alert_default, choice2_default, color_chooser, color_chooser_with_default, input_default,
+    message_default, message_title_default, ColorMode,
+};
 use fltk::enums::{Color, FrameType};
+use fltk::menu::{self, MenuFlag};
 use fltk::{app, button, prelude::*, window};
 use fltk::{frame, group};
-use fltk_theme::{ThemeType, WidgetTheme};
+use fltk_theme::WidgetTheme;
 
-use crate::canvas::Canvas;
+use crate::canvas::{Canvas, Tool};
+use crate::preferences::Preferences;
+use crate::relation::Tolerance;
 use crate::figures::circle::{Circle, self};
 use crate::figures::line::Line;
 use crate::figures::point::Point;
 use crate::reader::*;
 use crate::utils::*;
 
+mod algebra;
+mod algebra_view;
 mod canvas;
+mod command;
+mod command_bar;
+mod config;
+mod construction;
+mod expr;
 mod figures;
+mod inspector;
+mod keymap;
+mod measurement;
+mod png;
+mod preferences;
+mod preferences_dialog;
+mod protocol;
+mod protocol_view;
+mod raster;
 mod reader;
+mod relation;
+mod render;
+mod script;
+mod status_bar;
+mod style;
+mod svg;
+mod toolbar;
+mod tooltip;
+mod transform;
 mod utils;
+mod view;
 
 fn main() {
     const PROGRAMMWIDTH: i32 = 1080;
     const PROGRAMMHEIGHT: i32 = 720;
-    const BUTTONHEIGHT: i32 = 50;
-    const BUTTONWIDTH: i32 = 200;
+    const MENUHEIGHT: i32 = 25;
+    const TOOLBARHEIGHT: i32 = 36;
+    const INSPECTORWIDTH: i32 = 260;
+    const ALGEBRAWIDTH: i32 = 320;
+    const COMMANDHEIGHT: i32 = 30;
+    const STATUSHEIGHT: i32 = 24;
+    const PROTOCOLHEIGHT: i32 = 200;
+    const CANVASHEIGHT: i32 =
+        PROGRAMMHEIGHT - MENUHEIGHT - TOOLBARHEIGHT - STATUSHEIGHT - COMMANDHEIGHT;
+    // the toolbar needs about this much room, the canvas and the protocol the height
+    const MINWIDTH: i32 = 1000;
+    const MINHEIGHT: i32 = 480;
+    // how much one step of zooming in magnifies
+    const ZOOMFACTOR: f64 = 1.25;
 
     // lets build some gui
+    // "geoviz diagram.gvz [image.svg or image.png]" runs a script without a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        let size = (PROGRAMMWIDTH, CANVASHEIGHT);
+        if let Err(e) = run_headless(&args, size) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // theme, window size and what new objects look like from the last time
+    let settings = Preferences::load();
+
     let a = app::App::default();
     let mut win = window::Window::new(200, 200, PROGRAMMWIDTH, PROGRAMMHEIGHT, "Geoviz");
-    let theme = WidgetTheme::new(ThemeType::Greybird);
+    win.make_resizable(true);
+    win.size_range(MINWIDTH, MINHEIGHT, 0, 0);
+    let theme = WidgetTheme::new(settings.get_theme());
     theme.apply();
 
-    // open file button
-    let mut open_filebtn = button::Button::new(
-        PROGRAMMWIDTH - BUTTONWIDTH,
-        PROGRAMMHEIGHT - BUTTONHEIGHT,
-        BUTTONWIDTH,
-        BUTTONHEIGHT,
-        "Open file",
+    // menus and tools on top, the canvas, the status bar and the command bar below them
+    let mut column = group::Flex::new(0, 0, PROGRAMMWIDTH, PROGRAMMHEIGHT, None).column();
+    column.set_pad(0);
+
+    let mut menu = menu::MenuBar::default();
+    column.set_size(&menu, MENUHEIGHT);
+
+    // the toolbar needs the canvas, its place is kept until the canvas is there
+    let mut toolbar_place = group::Group::new(0, MENUHEIGHT, PROGRAMMWIDTH, TOOLBARHEIGHT, None);
+    toolbar_place.end();
+    column.set_size(&toolbar_place, TOOLBARHEIGHT);
+
+    // the canvas with the panels docked around it, a hidden panel leaves its room to the
+    // canvas; the protocol is below the others
+    let mut stage = group::Flex::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT,
+        PROGRAMMWIDTH,
+        CANVASHEIGHT,
+        None,
+    )
+    .column();
+    stage.set_pad(0);
+    let mut middle = group::Flex::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT,
+        PROGRAMMWIDTH,
+        CANVASHEIGHT,
+        None,
+    )
+    .row();
+    middle.set_pad(0);
+
+    // canvas, created first as the panels need it
+    let cvs: canvas::Canvas = canvas::Canvas::new(
+        ALGEBRAWIDTH,
+        MENUHEIGHT + TOOLBARHEIGHT,
+        PROGRAMMWIDTH - ALGEBRAWIDTH - INSPECTORWIDTH,
+        CANVASHEIGHT,
+        "",
     );
 
-    // clear screen button
-    let mut clrscrn = button::Button::new(
-        PROGRAMMWIDTH - BUTTONWIDTH * 2,
-        PROGRAMMHEIGHT - BUTTONHEIGHT,
-        BUTTONWIDTH,
-        BUTTONHEIGHT,
-        "Clear",
+    // equations of the figures, left of the canvas while it is shown
+    let algebra_view = algebra_view::AlgebraView::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT,
+        ALGEBRAWIDTH,
+        CANVASHEIGHT,
+        cvs.clone(),
     );
+    middle.insert(algebra_view.get_group(), 0);
+    middle.set_size(algebra_view.get_group(), ALGEBRAWIDTH);
 
-    // RadioRoundButtons
-    let flex = group::Flex::new(
-        PROGRAMMWIDTH - BUTTONWIDTH * 3 + 150,
-        PROGRAMMHEIGHT - BUTTONHEIGHT,
-        50,
-        BUTTONHEIGHT,
-        "",
+    // object inspector, right of the canvas while it is shown
+    let inspector = inspector::Inspector::new(
+        PROGRAMMWIDTH - INSPECTORWIDTH,
+        MENUHEIGHT + TOOLBARHEIGHT,
+        INSPECTORWIDTH,
+        CANVASHEIGHT,
+        cvs.clone(),
     );
-    let mut rad1 = button::RadioRoundButton::new(0, 0, 0, 0, "L");
-    rad1.toggle(true);
-    let mut rad2 = button::RadioRoundButton::new(0, 0, 0, 0, "R");
-    rad2.toggle(false);
-    let mut rad3 = button::RadioRoundButton::new(0, 0, 0, 0, "C");
-    rad3.toggle(false);
-    flex.end();
-    
-    // color selector button
-    let mut colorbtn = button::Button::new(
-        PROGRAMMWIDTH - BUTTONWIDTH * 4 + 150,
-        PROGRAMMHEIGHT - BUTTONHEIGHT,
-        BUTTONWIDTH,
-        BUTTONHEIGHT,
-        "Colorselector",
+    middle.set_size(inspector.get_group(), INSPECTORWIDTH);
+    middle.end();
+
+    // steps of the construction, below the canvas while it is shown
+    let protocol_view = protocol_view::ProtocolView::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT + CANVASHEIGHT - PROTOCOLHEIGHT,
+        PROGRAMMWIDTH,
+        PROTOCOLHEIGHT,
+        cvs.clone(),
     );
+    stage.set_size(protocol_view.get_group(), PROTOCOLHEIGHT);
+    stage.end();
 
-    // check box for color fill
-    let mut fill = button::CheckButton::new(
-        PROGRAMMWIDTH - BUTTONWIDTH * 5 + 250,
-        PROGRAMMHEIGHT - BUTTONHEIGHT,
-        50,
-        BUTTONHEIGHT,
-        "Fill",
+    // cursor position, tool, number of objects and what to click next, below the canvas
+    let status = group::Group::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT + CANVASHEIGHT,
+        PROGRAMMWIDTH,
+        STATUSHEIGHT,
+        None,
+    );
+    let _status_bar = status_bar::StatusBar::new(
+        0,
+        MENUHEIGHT + TOOLBARHEIGHT + CANVASHEIGHT,
+        PROGRAMMWIDTH,
+        STATUSHEIGHT,
+        cvs.clone(),
     );
+    status.end();
+    column.set_size(&status, STATUSHEIGHT);
 
+    // input bar for the construction language, below the status bar
+    let bar = group::Group::new(
+        0,
+        PROGRAMMHEIGHT - COMMANDHEIGHT,
+        PROGRAMMWIDTH,
+        COMMANDHEIGHT,
+        None,
+    );
+    let _command_bar = command_bar::CommandBar::new(
+        0,
+        PROGRAMMHEIGHT - COMMANDHEIGHT,
+        PROGRAMMWIDTH,
+        COMMANDHEIGHT,
+        cvs.clone(),
+    );
+    bar.end();
+    column.set_size(&bar, COMMANDHEIGHT);
+    column.end();
 
-    // canvas
-    let cvs: canvas::Canvas =
-        canvas::Canvas::new(0, 0, PROGRAMMWIDTH, PROGRAMMHEIGHT - BUTTONHEIGHT, "");
+    // a button for every tool, with the fill and color new objects get
+    toolbar_place.begin();
+    let mut toolbar =
+        toolbar::Toolbar::new(0, MENUHEIGHT, PROGRAMMWIDTH, TOOLBARHEIGHT, cvs.clone());
+    toolbar_place.end();
 
     win.end();
+    // the layout is made for the default size, the flex containers fit it to the saved one
+    if let Some((w, h)) = settings.get_window_size() {
+        win.resize(win.x(), win.y(), w.max(MINWIDTH), h.max(MINHEIGHT));
+    }
     win.show();
+    apply_preferences(&mut cvs.clone(), &settings);
+
+    // every menu item has its key from the keymap, which can be changed in the preferences
+    let keymap = keymap::Keymap::load();
+    toolbar.show_keys(&keymap);
+    let mut preferences =
+        preferences_dialog::PreferencesDialog::new(keymap.clone(), settings.clone());
+
+    toolbar.set_callback({
+        let mut clone = cvs.clone();
+        move |tool| choose_tool(&mut clone, tool)
+    });
 
-    // if any RadioRoundButton is toggled they should use the toggled function
-    rad1.set_callback({
+    add_item(&mut menu, &keymap, "File/Open…", MenuFlag::Normal, {
         let mut clone = cvs.clone();
+        move |_| open_file(&mut clone)
+    });
+
+    add_item(&mut menu, &keymap, "File/Export image…", MenuFlag::MenuDivider, {
+        let clone = cvs.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            cvs_clone.toggle((true, false, false));
+            if let Some(path) = reader::choose_image_file() {
+                if let Err(e) = clone.export_image(&path) {
+                    alert_default(&e);
+                }
+            }
         }
     });
 
-    rad2.set_callback({
+    add_item(&mut menu, &keymap, "File/Quit", MenuFlag::Normal, {
+        let mut win = win.clone();
+        move |_| win.hide()
+    });
+
+    add_item(&mut menu, &keymap, "Edit/Undo", MenuFlag::Normal, {
+        let mut clone = cvs.clone();
+        move |_| clone.undo()
+    });
+
+    // drops the clicks of a figure that isn't finished, and keeps Escape from closing the window
+    add_item(&mut menu, &keymap, "Edit/Cancel figure", MenuFlag::Normal, {
         let mut clone = cvs.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            cvs_clone.toggle((false, true, false));
+            clone.cancel();
+        }
+    });
+
+    add_item(&mut menu, &keymap, "Edit/Delete", MenuFlag::MenuDivider, {
+        let mut inspector = inspector.clone();
+        move |_| {
+            if !inspector.is_shown() || !inspector.delete_selected() {
+                alert_default("Select what to delete in the Objects panel first.");
+            }
         }
     });
 
-    rad3.set_callback({
+    add_item(&mut menu, &keymap, "Edit/Color…", MenuFlag::Normal, {
         let mut clone = cvs.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            cvs_clone.toggle((false, false, true));
+            let color = color_chooser_with_default(
+                "Color of new objects",
+                ColorMode::Rgb,
+                clone.get_color(),
+            );
+            clone.set_color(color);
         }
     });
 
-    colorbtn.set_callback({
+    add_item(&mut menu, &keymap, "Edit/Fill", MenuFlag::Toggle | MenuFlag::MenuDivider, {
         let mut clone = cvs.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            let color = color_chooser("Color selector", ColorMode::Rgb);
-            cvs_clone.set_color(color.unwrap());
+            let fill = clone.get_fill();
+            clone.set_fill(!fill);
         }
     });
 
-    open_filebtn.set_callback({
+    // tolerances for the relation tool
+    add_item(&mut menu, &keymap, "Edit/Tolerance…", MenuFlag::Normal, {
         let mut clone = cvs.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            cvs_clone.load(reader::filereader());
+            let tolerance = clone.get_tolerance();
+            let distance = ask_number(
+                "Distance tolerance for the relations:",
+                tolerance.get_distance(),
+            );
+            clone.set_tolerance(Tolerance::new(distance, tolerance.get_angle()));
         }
     });
 
-    clrscrn.set_callback({
+    add_item(&mut menu, &keymap, "Edit/Angle tolerance…", MenuFlag::Normal, {
         let mut clone = cvs.clone();
         move |_| {
-            let clone = clone.borrow_mut();
-            clone.clear();
+            let tolerance = clone.get_tolerance();
+            let angle = ask_number(
+                "Angle tolerance for the relations in degrees:",
+                tolerance.get_angle().to_degrees(),
+            );
+            clone.set_tolerance(Tolerance::new(tolerance.get_distance(), angle.to_radians()));
         }
     });
 
-    fill.set_callback({
+    // decimals in the tooltips and the relations
+    add_item(&mut menu, &keymap, "Edit/Decimals…", MenuFlag::MenuDivider, {
         let mut clone = cvs.clone();
-        let fill = fill.clone();
         move |_| {
-            let cvs_clone = clone.borrow_mut();
-            cvs_clone.set_fill(fill.value());
+            let precision = ask_number(
+                "Decimals in the tooltips and the relations:",
+                clone.get_precision() as f64,
+            );
+            clone.set_precision(precision.round().clamp(0.0, 10.0) as usize);
         }
     });
 
+    add_item(&mut menu, &keymap, "Edit/Preferences…", MenuFlag::MenuDivider, {
+        let mut preferences = preferences.clone();
+        move |_| preferences.show()
+    });
+
+    add_item(&mut menu, &keymap, "Edit/Clear", MenuFlag::Normal, {
+        let mut clone = cvs.clone();
+        move |_| clone.clear()
+    });
+
+    add_item(&mut menu, &keymap, "View/Zoom in", MenuFlag::Normal, {
+        let mut clone = cvs.clone();
+        move |_| clone.zoom(ZOOMFACTOR)
+    });
+
+    add_item(&mut menu, &keymap, "View/Zoom out", MenuFlag::Normal, {
+        let mut clone = cvs.clone();
+        move |_| clone.zoom(1.0 / ZOOMFACTOR)
+    });
+
+    add_item(&mut menu, &keymap, "View/Original size", MenuFlag::MenuDivider, {
+        let mut clone = cvs.clone();
+        move |_| clone.reset_zoom()
+    });
+
+    // the canvas is laid out again to take the room of a panel or give it up
+    add_item(&mut menu, &keymap, "View/Objects", MenuFlag::Toggle, {
+        let mut inspector = inspector.clone();
+        let middle = middle.clone();
+        move |_| {
+            inspector.toggle();
+            middle.recalc();
+        }
+    });
+
+    add_item(&mut menu, &keymap, "View/Algebra", MenuFlag::Toggle, {
+        let mut algebra_view = algebra_view.clone();
+        let middle = middle.clone();
+        move |_| {
+            algebra_view.toggle();
+            middle.recalc();
+        }
+    });
+
+    add_item(&mut menu, &keymap, "View/Protocol", MenuFlag::Toggle, {
+        let mut protocol_view = protocol_view.clone();
+        let stage = stage.clone();
+        move |_| {
+            protocol_view.toggle();
+            stage.recalc();
+        }
+    });
+
+    // the tools like on the toolbar, the one in use is checked
+    for (tool, label, menu_name, ..) in toolbar::TOOLS {
+        let flag = if toolbar::ends_group(&tool) {
+            MenuFlag::Radio | MenuFlag::MenuDivider
+        } else {
+            MenuFlag::Radio
+        };
+        add_item(&mut menu, &keymap, &format!("{}/{}", menu_name, label), flag, {
+            let mut clone = cvs.clone();
+            move |_| choose_tool(&mut clone, tool)
+        });
+    }
+
+    // function plot
+    add_item(&mut menu, &keymap, "Construct/Plot…", MenuFlag::Normal, {
+        let mut clone = cvs.clone();
+        move |_| {
+            let input = input_default("Curve (y = a*sin(x), (cos(t), sin(t)), r = 1 + cos(θ) [0, 2pi]) or parameter (a = 2):", "");
+            if let Some(input) = input {
+                if let Err(e) = clone.plot(&input) {
+                    alert_default(&e);
+                }
+            }
+        }
+    });
+
+    add_item(&mut menu, &keymap, "Help/Commands", MenuFlag::Normal, |_| {
+        let usage: Vec<&str> = command::COMMANDS.iter().map(|(_, usage)| *usage).collect();
+        message_title_default("Commands");
+        message_default(&format!(
+            "Type these in the input bar, like A = (1, 2):\n\n{}",
+            usage.join("\n")
+        ));
+    });
+
+    add_item(&mut menu, &keymap, "Help/About Geoviz", MenuFlag::Normal, |_| {
+        message_title_default("About Geoviz");
+        message_default(
+            "Geoviz draws geometric constructions that stay connected when you move them.",
+        );
+    });
+
+    // new keys from the preferences go to the menus and the tooltips of the toolbar,
+    // the other settings to the canvas
+    preferences.set_callback({
+        let menu = menu.clone();
+        let mut toolbar = toolbar.clone();
+        let mut clone = cvs.clone();
+        move |keymap, settings| {
+            for (action, _) in keymap.get_keys() {
+                if let Some(mut item) = menu.find_item(action) {
+                    item.set_shortcut(keymap.get_shortcut(action));
+                }
+            }
+            toolbar.show_keys(keymap);
+            WidgetTheme::new(settings.get_theme()).apply();
+            app::redraw();
+            apply_preferences(&mut clone, settings);
+        }
+    });
+
+    // the checked tool and fill follow the canvas, whichever way they were changed
+    let mut clone = cvs.clone();
+    clone.add_on_change({
+        let clone = cvs.clone();
+        let menu = menu.clone();
+        move || {
+            let tool = clone.get_tool();
+            for (t, label, menu_name, ..) in toolbar::TOOLS.iter() {
+                if let Some(mut item) = menu.find_item(&format!("{}/{}", menu_name, label)) {
+                    if discriminant(t) == discriminant(&tool) {
+                        item.set();
+                    } else {
+                        item.clear();
+                    }
+                }
+            }
+            if let Some(mut item) = menu.find_item("Edit/Fill") {
+                if clone.get_fill() {
+                    item.set();
+                } else {
+                    item.clear();
+                }
+            }
+        }
+    });
+    if let Some(mut item) = menu.find_item("Construct/Line") {
+        item.set();
+    }
+
     a.run().unwrap();
+
+    // the window opens as big as it was left
+    let mut settings = Preferences::load();
+    settings.set_window_size(win.w(), win.h());
+    settings.save().ok();
+}
+
+// what new objects look like and how the canvas shows them
+fn apply_preferences(canvas: &mut Canvas, preferences: &Preferences) {
+    canvas.set_color(preferences.get_color());
+    canvas.set_fill(preferences.get_fill());
+    canvas.set_line_width(preferences.get_width());
+    canvas.set_precision(preferences.get_precision());
+    canvas.set_snap(preferences.get_snap());
+    canvas.set_grid(preferences.get_grid());
+}
+
+// a menu item with the key the keymap has for it, the action is its place in the menus
+fn add_item(
+    menu: &mut menu::MenuBar,
+    keymap: &keymap::Keymap,
+    action: &str,
+    flag: MenuFlag,
+    callback: impl FnMut(&mut menu::MenuBar) + 'static,
+) {
+    menu.add(action, keymap.get_shortcut(action), flag, callback);
+}
+
+// picks the tool, asking for the angle or factor of a rotation or dilation first
+fn choose_tool(canvas: &mut Canvas, tool: Tool) {
+    let tool = match tool {
+        Tool::Rotate(_) => {
+            Tool::Rotate(ask_number("Rotation angle in degrees:", 90.0).to_radians())
+        }
+        Tool::Dilate(_) => Tool::Dilate(ask_number("Dilation factor:", 2.0)),
+        tool => tool,
+    };
+    canvas.toggle(tool);
+}
+
+// points from a file, or a script which replaces the scene
+fn open_file(canvas: &mut Canvas) {
+    // the dialog starts where the last file was, which is remembered for the next time
+    let mut preferences = Preferences::load();
+    let path = match reader::choose_file(preferences.get_last_dir()) {
+        Some(path) => path,
+        None => return,
+    };
+    preferences.set_last_file(&path);
+    preferences.save().ok();
+    if !script::is_script(&path) {
+        match reader::filereader(&path) {
+            Ok(points) => canvas.load(points),
+            Err(e) => canvas.set_message(&e),
+        }
+        return;
+    }
+    // all at once or a statement per click on Step
+    let script = match script::Script::read(&path) {
+        Ok(script) => script,
+        Err(e) => return alert_default(&e),
+    };
+    let all = match choice2_default(
+        "Run the whole script or step through it?",
+        "Cancel",
+        "Step by step",
+        "Run all",
+    ) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return,
+    };
+    if let Err(e) = canvas.load_script(script, all) {
+        alert_default(&e);
+    }
+}
+
+// runs the script in args[0] and writes the image to args[1] or next to the script
+fn run_headless(args: &[String], size: (i32, i32)) -> Result<(), String> {
+    let input = Path::new(&args[0]);
+    let output = match args.get(1) {
+        Some(output) => PathBuf::from(output),
+        None => input.with_extension("svg"),
+    };
+    let format = canvas::get_image_format(&output)?;
+    let mut script = script::Script::read(input)?;
+    let image = canvas::export_script(&mut script, size.0, size.1, format)
+        .map_err(|e| format!("{}: {}", input.display(), e))?;
+    fs::write(&output, image).map_err(|e| format!("can't write {}: {}", output.display(), e))
+}
+
+// asks for an expression like "45" or "pi / 2", the default is used when there is no answer
+fn ask_number(message: &str, default: f64) -> f64 {
+    let input = match input_default(message, &default.to_string()) {
+        Some(input) => input,
+        None => return default,
+    };
+    match expr::parse(&input).and_then(|e| e.eval(&|_| None)) {
+        Ok(value) => value,
+        Err(e) => {
+            alert_default(&e);
+            default
+        }
+    }
 }
@@ -0,0 +1,241 @@
+use std::f64;
+
+use crate::figures::{point::Point, Figure};
+use crate::tooltip::format_number;
+use crate::utils::{
+    get_angle, get_distance, get_midpoint, get_perpendicular_line, get_point_of_intersection,
+    is_parallel, normalize_angle,
+};
+
+// lines closer to parallel than this don't make an angle
+const PARALLEL_TOLERANCE: f64 = 1e-12;
+
+// a value read off other figures, which are referred to by their index in the scene
+// like the parents of a construction, so it changes whenever they do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measurement {
+    // two points
+    Distance(usize, usize),
+    LineDistance { point: usize, line: usize },
+    // two lines, counterclockwise from the first
+    Angle(usize, usize),
+    // counterclockwise from the first point around the second
+    ThreePointAngle(usize, usize, usize),
+    // area and perimeter of a closed figure
+    Area(usize),
+}
+
+// what gets drawn for a measurement, all in world coordinates
+#[derive(Debug, Clone)]
+pub struct Label {
+    position: Point,
+    text: String,
+    segment: Option<(Point, Point)>, // what a distance was measured along
+    angle: Option<(Point, f64, f64)>, // vertex, start angle and sweep
+}
+
+impl Label {
+    pub fn get_position(&self) -> Point {
+        self.position
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_segment(&self) -> Option<(Point, Point)> {
+        self.segment
+    }
+
+    pub fn get_angle(&self) -> Option<(Point, f64, f64)> {
+        self.angle
+    }
+}
+
+impl Measurement {
+    pub fn get_parents(&self) -> Vec<usize> {
+        match *self {
+            Measurement::Distance(a, b) | Measurement::Angle(a, b) => vec![a, b],
+            Measurement::LineDistance { point, line } => vec![point, line],
+            Measurement::ThreePointAngle(a, b, c) => vec![a, b, c],
+            Measurement::Area(figure) => vec![figure],
+        }
+    }
+
+    // the same measurement with the figures renumbered, like Construction::remap
+    pub fn remap(&self, map: impl Fn(usize) -> usize) -> Measurement {
+        match *self {
+            Measurement::Distance(a, b) => Measurement::Distance(map(a), map(b)),
+            Measurement::LineDistance { point, line } => Measurement::LineDistance {
+                point: map(point),
+                line: map(line),
+            },
+            Measurement::Angle(a, b) => Measurement::Angle(map(a), map(b)),
+            Measurement::ThreePointAngle(a, b, c) => {
+                Measurement::ThreePointAngle(map(a), map(b), map(c))
+            }
+            Measurement::Area(figure) => Measurement::Area(map(figure)),
+        }
+    }
+
+    // the label for the current state of the parents with values rounded to the given
+    // decimals, None where there is nothing to measure
+    pub fn measure<'a>(
+        &self,
+        get: impl Fn(usize) -> Option<&'a Figure>,
+        precision: usize,
+    ) -> Option<Label> {
+        let point = |i: usize| match get(i) {
+            Some(Figure::Point(p)) => Some(*p),
+            _ => None,
+        };
+        let line = |i: usize| match get(i) {
+            Some(Figure::Line(l)) => Some(*l),
+            _ => None,
+        };
+        match *self {
+            Measurement::Distance(a, b) => {
+                let (a, b) = (point(a)?, point(b)?);
+                Some(Label {
+                    position: get_midpoint(a, b),
+                    text: format_number(get_distance(a, b), precision),
+                    segment: Some((a, b)),
+                    angle: None,
+                })
+            }
+            Measurement::LineDistance { point: p, line: l } => {
+                let (p, l) = (point(p)?, line(l)?);
+                let foot = get_point_of_intersection(l, get_perpendicular_line(l, p));
+                Some(Label {
+                    position: get_midpoint(p, foot),
+                    text: format_number(get_distance(p, foot), precision),
+                    segment: Some((p, foot)),
+                    angle: None,
+                })
+            }
+            Measurement::Angle(first, second) => {
+                let (l1, l2) = (line(first)?, line(second)?);
+                if is_parallel(l1.get_slope(), l2.get_slope(), PARALLEL_TOLERANCE) {
+                    return None;
+                }
+                let vertex = get_point_of_intersection(l1, l2);
+                let start = get_angle(l1.get_point_a(), l1.get_point_b());
+                let end = get_angle(l2.get_point_a(), l2.get_point_b());
+                // lines have no direction, so the angle is below 180°
+                let sweep = normalize_angle(end - start) % f64::consts::PI;
+                Some(angle_label(vertex, start, sweep, precision))
+            }
+            Measurement::ThreePointAngle(a, vertex, c) => {
+                let (a, vertex, c) = (point(a)?, point(vertex)?, point(c)?);
+                if get_distance(a, vertex) == 0.0 || get_distance(c, vertex) == 0.0 {
+                    return None;
+                }
+                let start = get_angle(vertex, a);
+                let sweep = normalize_angle(get_angle(vertex, c) - start);
+                Some(angle_label(vertex, start, sweep, precision))
+            }
+            Measurement::Area(figure) => {
+                let (center, area, perimeter) = match get(figure)? {
+                    Figure::Circle(c) => (c.get_center(), c.get_area(), c.get_perimeter()),
+                    Figure::Rectangle(r) => (
+                        get_midpoint(r.get_point_a(), r.get_point_b()),
+                        // the sides are signed by the corner the rectangle was drawn from
+                        r.get_area().abs(),
+                        2.0 * (r.get_width().abs() + r.get_height().abs()),
+                    ),
+                    // the sector, closed by both radii
+                    Figure::Arc(a) => (
+                        a.get_center(),
+                        a.get_sector_area(),
+                        a.get_length() + 2.0 * a.get_rad(),
+                    ),
+                    Figure::Conic(c) => {
+                        let (center, major, minor) = c.get_ellipse_axes()?;
+                        (
+                            center,
+                            f64::consts::PI * major * minor,
+                            get_ellipse_perimeter(major, minor),
+                        )
+                    }
+                    _ => return None,
+                };
+                Some(Label {
+                    position: center,
+                    text: format!(
+                        "A = {}  P = {}",
+                        format_number(area, precision),
+                        format_number(perimeter, precision)
+                    ),
+                    segment: None,
+                    angle: None,
+                })
+            }
+        }
+    }
+}
+
+fn angle_label(vertex: Point, start: f64, sweep: f64, precision: usize) -> Label {
+    Label {
+        position: vertex,
+        text: format!("{}°", format_number(sweep.to_degrees(), precision)),
+        segment: None,
+        angle: Some((vertex, start, sweep)),
+    }
+}
+
+// Ramanujan's approximation, exact for circles and off by far less than a pixel otherwise
+fn get_ellipse_perimeter(a: f64, b: f64) -> f64 {
+    let h = ((a - b) / (a + b)).powi(2);
+    f64::consts::PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figures::{circle::Circle, line::Line, rectangle::Rectangle};
+
+    fn text(measurement: Measurement, figures: &[Figure], precision: usize) -> Option<String> {
+        measurement
+            .measure(|i| figures.get(i), precision)
+            .map(|label| label.get_text().to_string())
+    }
+
+    #[test]
+    fn labels_follow_the_precision() {
+        let figures = [
+            Figure::Point(Point::new(0.0, 0.0)),
+            Figure::Point(Point::new(1.0, 1.0)),
+            Figure::Line(Line::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0))),
+            Figure::Line(Line::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0))),
+        ];
+        let distance = Measurement::Distance(0, 1);
+        assert_eq!(text(distance, &figures, 2).unwrap(), "1.41");
+        assert_eq!(text(distance, &figures, 5).unwrap(), "1.41421");
+        assert_eq!(text(distance, &figures, 0).unwrap(), "1");
+        let angle = Measurement::Angle(2, 3);
+        assert_eq!(text(angle, &figures, 3).unwrap(), "63.435°");
+        let foot = Measurement::LineDistance { point: 1, line: 2 };
+        assert_eq!(text(foot, &figures, 1).unwrap(), "1.0");
+    }
+
+    #[test]
+    fn areas_of_closed_figures_only() {
+        let figures = [
+            Figure::Circle(Circle::new(Point::new(50.0, 50.0), Point::new(52.0, 50.0))),
+            Figure::Rectangle(Rectangle::new(Point::new(0.0, 0.0), Point::new(3.0, 2.0))),
+            Figure::Point(Point::new(1.0, 1.0)),
+            Figure::Line(Line::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0))),
+        ];
+        assert_eq!(
+            text(Measurement::Area(0), &figures, 3).unwrap(),
+            "A = 12.566  P = 12.566"
+        );
+        assert_eq!(
+            text(Measurement::Area(1), &figures, 1).unwrap(),
+            "A = 6.0  P = 10.0"
+        );
+        assert_eq!(text(Measurement::Area(2), &figures, 2), None);
+        assert_eq!(text(Measurement::Area(3), &figures, 2), None);
+        assert_eq!(text(Measurement::Area(4), &figures, 2), None);
+    }
+}
@@ -0,0 +1,353 @@
+#![allow(dead_code)]
+
+// the longest match deflate has and how far back it may look
+const MAX_MATCH: usize = 258;
+const MAX_DISTANCE: usize = 32768;
+
+// lengths 3 to 258 and distances 1 to 32768 as a base and extra bits, see RFC 1951
+const LENGTHS: [(usize, u32); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+const DISTANCES: [(usize, u32); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+// an RGB image, three bytes a pixel row by row, as a PNG file
+pub fn encode(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    for row in rgb.chunks(width * 3).take(height) {
+        raw.push(0); // no filter
+        raw.extend_from_slice(row);
+    }
+
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bits, RGB, deflate, no filter, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    add_chunk(&mut png, b"IHDR", &header);
+    add_chunk(&mut png, b"IDAT", &compress(&raw, width * 3 + 1));
+    add_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn add_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = get_crc(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn get_crc(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn get_adler(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// zlib data with one block of fixed Huffman codes; a scene is mostly the same color,
+// so looking back one pixel and one row finds nearly all of it
+fn compress(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = Bits::default();
+    bits.write(1, 1); // the last block
+    bits.write(1, 2); // fixed codes
+    let mut i = 0;
+    while i < data.len() {
+        let best = [3, stride]
+            .iter()
+            .filter(|d| **d <= i && **d <= MAX_DISTANCE)
+            .map(|d| (get_match(data, i, *d), *d))
+            .max();
+        match best {
+            Some((len, distance)) if len >= 3 => {
+                write_match(&mut bits, len, distance);
+                i += len;
+            }
+            _ => {
+                write_literal(&mut bits, data[i] as usize);
+                i += 1;
+            }
+        }
+    }
+    write_literal(&mut bits, 256); // the end of the block
+
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend(bits.finish());
+    zlib.extend_from_slice(&get_adler(data).to_be_bytes());
+    zlib
+}
+
+// how many bytes from i on repeat the ones distance before them
+fn get_match(data: &[u8], i: usize, distance: usize) -> usize {
+    let end = (i + MAX_MATCH).min(data.len());
+    (i..end)
+        .take_while(|&j| data[j] == data[j - distance])
+        .count()
+}
+
+// the fixed literal and length codes of RFC 1951
+fn write_literal(bits: &mut Bits, value: usize) {
+    let (code, len) = match value {
+        0..=143 => (0x30 + value, 8),
+        144..=255 => (0x190 + value - 144, 9),
+        256..=279 => (value - 256, 7),
+        _ => (0xc0 + value - 280, 8),
+    };
+    bits.write_code(code as u32, len);
+}
+
+fn write_match(bits: &mut Bits, len: usize, distance: usize) {
+    let k = LENGTHS
+        .iter()
+        .rposition(|(base, _)| *base <= len)
+        .unwrap_or(0);
+    write_literal(bits, 257 + k);
+    bits.write((len - LENGTHS[k].0) as u32, LENGTHS[k].1);
+    let k = DISTANCES
+        .iter()
+        .rposition(|(base, _)| *base <= distance)
+        .unwrap_or(0);
+    bits.write_code(k as u32, 5);
+    bits.write((distance - DISTANCES[k].0) as u32, DISTANCES[k].1);
+}
+
+// a stream of bits, filling every byte from its lowest bit
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    current: u32,
+    count: u32,
+}
+
+impl Bits {
+    fn write(&mut self, value: u32, len: u32) {
+        for k in 0..len {
+            self.current |= ((value >> k) & 1) << self.count;
+            self.count += 1;
+            if self.count == 8 {
+                self.bytes.push(self.current as u8);
+                self.current = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    // Huffman codes go in from their highest bit
+    fn write_code(&mut self, code: u32, len: u32) {
+        for k in (0..len).rev() {
+            self.write((code >> k) & 1, 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reads the bits the way Bits writes them
+    struct Reader<'a> {
+        bytes: &'a [u8],
+        pos: usize, // in bits
+    }
+
+    impl Reader<'_> {
+        fn read(&mut self, len: u32) -> usize {
+            let mut value = 0;
+            for k in 0..len {
+                let bit = (self.bytes[self.pos / 8] >> (self.pos % 8)) & 1;
+                value |= (bit as usize) << k;
+                self.pos += 1;
+            }
+            value
+        }
+
+        fn read_code(&mut self, len: u32) -> usize {
+            (0..len).fold(0, |code, _| (code << 1) | self.read(1))
+        }
+
+        // a symbol of the fixed literal and length codes
+        fn read_literal(&mut self) -> usize {
+            let code = self.read_code(7);
+            if code <= 0b0010111 {
+                return 256 + code;
+            }
+            let code = (code << 1) | self.read(1);
+            match code {
+                0x30..=0xbf => code - 0x30,
+                0xc0..=0xc7 => 280 + code - 0xc0,
+                _ => 144 + ((code << 1) | self.read(1)) - 0x190,
+            }
+        }
+    }
+
+    // undoes compress, which only writes a single block of fixed codes
+    fn inflate(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(((zlib[0] as u32) << 8 | zlib[1] as u32) % 31, 0);
+        let mut reader = Reader {
+            bytes: &zlib[2..zlib.len() - 4],
+            pos: 0,
+        };
+        assert_eq!(reader.read(1), 1);
+        assert_eq!(reader.read(2), 1);
+        let mut data: Vec<u8> = vec![];
+        loop {
+            let symbol = reader.read_literal();
+            match symbol {
+                0..=255 => data.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let (base, extra) = LENGTHS[symbol - 257];
+                    let len = base + reader.read(extra);
+                    let (base, extra) = DISTANCES[reader.read_code(5)];
+                    let distance = base + reader.read(extra);
+                    for _ in 0..len {
+                        data.push(data[data.len() - distance]);
+                    }
+                }
+            }
+        }
+        let adler = u32::from_be_bytes(zlib[zlib.len() - 4..].try_into().unwrap());
+        assert_eq!(adler, get_adler(&data));
+        data
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(get_crc(b"123456789"), 0xcbf4_3926);
+        assert_eq!(get_crc(b"IEND"), 0xae42_6082);
+        assert_eq!(get_adler(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(get_adler(&[]), 1);
+        // long enough for the sums to be reduced on the way
+        assert_eq!(get_adler(&[0xff; 100_000]), 0x149a_302c);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut data: Vec<u8> = (0..=255).collect();
+        data.extend_from_slice(&[7; 1000]);
+        // rows that repeat the one before, with a pixel here and there that doesn't
+        for row in 0..40u32 {
+            for x in 0..97u32 {
+                data.push(if (x * row) % 31 == 5 { x as u8 } else { 200 });
+            }
+        }
+        for stride in [1, 97, 301] {
+            assert_eq!(inflate(&compress(&data, stride)), data);
+        }
+        assert_eq!(inflate(&compress(&[], 1)), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn png_chunks() {
+        let (width, height) = (3, 2);
+        let rgb: Vec<u8> = (0..18).collect();
+        let png = encode(width, height, &rgb);
+        assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+        let mut chunks = vec![];
+        let mut i = 8;
+        while i < png.len() {
+            let len = u32::from_be_bytes(png[i..i + 4].try_into().unwrap()) as usize;
+            let body = &png[i + 4..i + 8 + len];
+            let crc = u32::from_be_bytes(png[i + 8 + len..i + 12 + len].try_into().unwrap());
+            assert_eq!(crc, get_crc(body));
+            chunks.push((body[..4].to_vec(), body[4..].to_vec()));
+            i += 12 + len;
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| &kind[..]).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        let mut raw = vec![0];
+        raw.extend_from_slice(&rgb[..9]);
+        raw.push(0);
+        raw.extend_from_slice(&rgb[9..]);
+        assert_eq!(inflate(&chunks[1].1), raw);
+    }
+}
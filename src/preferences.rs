@@ -0,0 +1,248 @@
+use std::path::{Path, PathBuf};
+
+use fltk_theme::ThemeType;
+
+use crate::config;
+use crate::style::{format_color, parse_color};
+
+const FILE: &str = "preferences.conf";
+
+// the themes of fltk-theme, with the names they are saved and listed by
+pub const THEMES: [(&str, ThemeType); 8] = [
+    ("Classic", ThemeType::Classic),
+    ("Aero", ThemeType::Aero),
+    ("Metro", ThemeType::Metro),
+    ("AquaClassic", ThemeType::AquaClassic),
+    ("Greybird", ThemeType::Greybird),
+    ("Blue", ThemeType::Blue),
+    ("Dark", ThemeType::Dark),
+    ("HighContrast", ThemeType::HighContrast),
+];
+
+// what geoviz starts with, kept in the config directory between runs
+#[derive(Debug, Clone)]
+pub struct Preferences {
+    theme: ThemeType,
+    color: (u8, u8, u8), // of new objects
+    fill: bool,
+    width: i32, // of the lines of new objects, in pixels
+    grid: bool,
+    snap: bool, // new points go to the nearest grid point
+    precision: usize,
+    window_size: Option<(i32, i32)>, // None for the size the layout is made for
+    last_dir: Option<PathBuf>,       // where the last file was opened
+}
+
+impl Preferences {
+    pub fn new() -> Preferences {
+        Preferences {
+            theme: ThemeType::Greybird,
+            color: (0, 0, 0),
+            fill: false,
+            width: 3,
+            grid: false,
+            snap: false,
+            precision: 2,
+            window_size: None,
+            last_dir: None,
+        }
+    }
+
+    // the defaults with what the config file changes, anything in it that doesn't make sense
+    // is left out
+    pub fn load() -> Preferences {
+        Preferences::from_entries(config::read(FILE))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        config::write(
+            FILE,
+            "preferences of geoviz, setting = value",
+            &self.get_entries(),
+        )
+    }
+
+    fn from_entries(entries: Vec<(String, String)>) -> Preferences {
+        let mut preferences = Preferences::new();
+        for (name, value) in entries {
+            preferences.set(&name, &value);
+        }
+        preferences
+    }
+
+    fn get_entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("theme", get_theme_name(self.theme).to_string()),
+            ("color", format_color(self.color)),
+            ("fill", self.fill.to_string()),
+            ("width", self.width.to_string()),
+            ("grid", self.grid.to_string()),
+            ("snap", self.snap.to_string()),
+            ("precision", self.precision.to_string()),
+        ];
+        if let Some((w, h)) = self.window_size {
+            entries.push(("window", format!("{}x{}", w, h)));
+        }
+        if let Some(dir) = &self.last_dir {
+            entries.push(("directory", dir.display().to_string()));
+        }
+        entries
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    // one line of the config file, a value that can't be read keeps the setting as it is
+    fn set(&mut self, name: &str, value: &str) {
+        match name {
+            "theme" => {
+                if let Some((_, theme)) = THEMES.iter().find(|(n, _)| *n == value) {
+                    self.theme = *theme;
+                }
+            }
+            "color" => self.color = parse_color(value).unwrap_or(self.color),
+            "fill" => self.fill = value.parse().unwrap_or(self.fill),
+            "width" => self.set_width(value.parse().unwrap_or(self.width)),
+            "grid" => self.grid = value.parse().unwrap_or(self.grid),
+            "snap" => self.snap = value.parse().unwrap_or(self.snap),
+            "precision" => self.set_precision(value.parse().unwrap_or(self.precision)),
+            "window" => {
+                let size = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+                if let Some((w, h)) = size {
+                    self.set_window_size(w, h);
+                }
+            }
+            "directory" if !value.is_empty() => self.last_dir = Some(PathBuf::from(value)),
+            _ => {}
+        }
+    }
+
+    pub fn get_theme(&self) -> ThemeType {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: ThemeType) {
+        self.theme = theme;
+    }
+
+    pub fn get_color(&self) -> (u8, u8, u8) {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: (u8, u8, u8)) {
+        self.color = color;
+    }
+
+    pub fn get_fill(&self) -> bool {
+        self.fill
+    }
+
+    pub fn set_fill(&mut self, fill: bool) {
+        self.fill = fill;
+    }
+
+    pub fn get_width(&self) -> i32 {
+        self.width
+    }
+
+    // as thin or thick as a style can make a line
+    pub fn set_width(&mut self, width: i32) {
+        self.width = width.clamp(0, 20);
+    }
+
+    pub fn get_grid(&self) -> bool {
+        self.grid
+    }
+
+    pub fn set_grid(&mut self, grid: bool) {
+        self.grid = grid;
+    }
+
+    pub fn get_snap(&self) -> bool {
+        self.snap
+    }
+
+    pub fn set_snap(&mut self, snap: bool) {
+        self.snap = snap;
+    }
+
+    pub fn get_precision(&self) -> usize {
+        self.precision
+    }
+
+    pub fn set_precision(&mut self, precision: usize) {
+        self.precision = precision.min(10);
+    }
+
+    pub fn get_window_size(&self) -> Option<(i32, i32)> {
+        self.window_size
+    }
+
+    pub fn set_window_size(&mut self, w: i32, h: i32) {
+        if w > 0 && h > 0 {
+            self.window_size = Some((w, h));
+        }
+    }
+
+    pub fn get_last_dir(&self) -> Option<&Path> {
+        self.last_dir.as_deref()
+    }
+
+    // the directory of a file that was opened
+    pub fn set_last_file(&mut self, path: &Path) {
+        self.last_dir = path.parent().map(Path::to_path_buf);
+    }
+}
+
+pub fn get_theme_name(theme: ThemeType) -> &'static str {
+    THEMES
+        .iter()
+        .find(|(_, t)| *t == theme)
+        .map_or("Greybird", |(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_preferences_read_back() {
+        let mut preferences = Preferences::new();
+        preferences.set_theme(ThemeType::Dark);
+        preferences.set_color((255, 128, 0));
+        preferences.set_fill(true);
+        preferences.set_width(7);
+        preferences.set_grid(true);
+        preferences.set_snap(true);
+        preferences.set_precision(4);
+        preferences.set_window_size(1024, 768);
+        preferences.set_last_file(Path::new("/home/me/drawings/house.gvz"));
+        let text = config::format("preferences", &preferences.get_entries());
+        let read = Preferences::from_entries(config::parse(&text));
+        assert_eq!(get_theme_name(read.get_theme()), "Dark");
+        assert_eq!(read.get_color(), (255, 128, 0));
+        assert!(read.get_fill() && read.get_grid() && read.get_snap());
+        assert_eq!(read.get_width(), 7);
+        assert_eq!(read.get_precision(), 4);
+        assert_eq!(read.get_window_size(), Some((1024, 768)));
+        assert_eq!(read.get_last_dir(), Some(Path::new("/home/me/drawings")));
+        assert_eq!(read.get_entries(), preferences.get_entries());
+    }
+
+    #[test]
+    fn unreadable_values_keep_the_defaults() {
+        let text = "theme = Plaid\ncolor = orange\nfill = maybe\nwidth = 99\nprecision = -1\n\
+                    window = 0x600\nsnap = true\nunknown = 1\n";
+        let read = Preferences::from_entries(config::parse(text));
+        let defaults = Preferences::new();
+        assert_eq!(get_theme_name(read.get_theme()), "Greybird");
+        assert_eq!(read.get_color(), defaults.get_color());
+        assert_eq!(read.get_fill(), defaults.get_fill());
+        assert_eq!(read.get_width(), 20);
+        assert_eq!(read.get_precision(), defaults.get_precision());
+        assert_eq!(read.get_window_size(), None);
+        assert!(read.get_snap());
+    }
+}
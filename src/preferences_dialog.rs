@@ -0,0 +1,303 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use fltk::app;
+use fltk::dialog::{alert_default, color_chooser_with_default, ColorMode};
+use fltk::enums::{Align, Color, Event, FrameType};
+use fltk::prelude::*;
+use fltk::{
+    browser::HoldBrowser,
+    button::{Button, CheckButton, ReturnButton},
+    frame::Frame,
+    group::{Group, Tabs},
+    menu::Choice,
+    valuator::ValueInput,
+    window::Window,
+};
+
+use crate::keymap::{format_key, Keymap};
+use crate::preferences::{Preferences, THEMES};
+
+const WIDTH: i32 = 520;
+const HEIGHT: i32 = 480;
+const ROW_HEIGHT: i32 = 25;
+const BUTTON_WIDTH: i32 = 90;
+// the settings of the General tab are right of their labels
+const LABEL_WIDTH: i32 = 160;
+
+type OnApply = Box<dyn FnMut(&Keymap, &Preferences)>;
+
+// a window with the settings, they are only used and saved once it is closed with OK
+#[derive(Clone)]
+pub struct PreferencesDialog {
+    window: Window,
+    theme: Choice,
+    color: Button, // shows the color of new objects, a click changes it
+    fill: CheckButton,
+    width: ValueInput,
+    precision: ValueInput,
+    grid: CheckButton,
+    snap: CheckButton,
+    list: HoldBrowser, // every action with its key
+    capture: Frame,    // takes the new key for the selected action
+    message: Frame,
+    keymap: Rc<RefCell<Keymap>>,           // as it is being changed
+    applied: Rc<RefCell<Keymap>>,          // as it was when OK was last clicked
+    preferences: Rc<RefCell<Preferences>>, // as they were when OK was last clicked
+    on_apply: Rc<RefCell<Option<OnApply>>>,
+}
+
+impl PreferencesDialog {
+    pub fn new(keymap: Keymap, preferences: Preferences) -> Self {
+        let mut window = Window::default()
+            .with_size(WIDTH, HEIGHT)
+            .with_label("Preferences");
+        window.make_modal(true);
+
+        let tabs = Tabs::new(10, 10, WIDTH - 20, HEIGHT - 60, None);
+        let general = Group::new(10, 35, WIDTH - 20, HEIGHT - 85, "General");
+        let x = 20 + LABEL_WIDTH;
+        let row = |i: i32| 50 + i * (ROW_HEIGHT + 10);
+        let mut theme = Choice::new(x, row(0), 200, ROW_HEIGHT, "Theme");
+        let names: Vec<&str> = THEMES.iter().map(|(name, _)| *name).collect();
+        theme.add_choice(&names.join("|"));
+        let mut color = Button::new(x, row(1), 60, ROW_HEIGHT, "Color of new objects");
+        color.set_frame(FrameType::DownBox);
+        color.set_align(Align::Left);
+        let fill = CheckButton::new(x, row(2), 200, ROW_HEIGHT, "Fill new objects");
+        let mut width = ValueInput::new(x, row(3), 60, ROW_HEIGHT, "Line width in pixels");
+        width.set_range(0.0, 20.0);
+        width.set_step(1.0, 1);
+        let mut precision = ValueInput::new(x, row(4), 60, ROW_HEIGHT, "Decimals");
+        precision.set_range(0.0, 10.0);
+        precision.set_step(1.0, 1);
+        let grid = CheckButton::new(x, row(5), 200, ROW_HEIGHT, "Show the grid");
+        let snap = CheckButton::new(x, row(6), 200, ROW_HEIGHT, "Snap new points to the grid");
+        general.end();
+
+        let keys = Group::new(10, 35, WIDTH - 20, HEIGHT - 85, "Keys");
+        let mut list = HoldBrowser::new(20, 45, WIDTH - 40, HEIGHT - 180, None);
+        list.set_column_char('\t');
+        list.set_column_widths(&[320]);
+        let row = HEIGHT - 125;
+        let mut capture = Frame::new(20, row, 200, ROW_HEIGHT, None);
+        capture.set_frame(FrameType::DownBox);
+        capture.set_color(Color::Background2);
+        capture.set_tooltip("Click here and press the new key");
+        let mut none = Button::new(230, row, BUTTON_WIDTH, ROW_HEIGHT, "No key");
+        let mut defaults = Button::new(
+            WIDTH - 20 - BUTTON_WIDTH,
+            row,
+            BUTTON_WIDTH,
+            ROW_HEIGHT,
+            "Defaults",
+        );
+        let mut message = Frame::new(20, row + ROW_HEIGHT + 5, WIDTH - 40, ROW_HEIGHT, None);
+        message.set_align(Align::Left | Align::Inside);
+        keys.end();
+        tabs.end();
+
+        let bottom = HEIGHT - ROW_HEIGHT - 15;
+        let mut cancel = Button::new(
+            WIDTH - 2 * BUTTON_WIDTH - 20,
+            bottom,
+            BUTTON_WIDTH,
+            ROW_HEIGHT + 5,
+            "Cancel",
+        );
+        let mut ok = ReturnButton::new(
+            WIDTH - BUTTON_WIDTH - 10,
+            bottom,
+            BUTTON_WIDTH,
+            ROW_HEIGHT + 5,
+            "OK",
+        );
+        window.end();
+
+        let dialog = PreferencesDialog {
+            window,
+            theme,
+            color,
+            fill,
+            width,
+            precision,
+            grid,
+            snap,
+            list,
+            capture,
+            message,
+            keymap: Rc::new(RefCell::new(keymap.clone())),
+            applied: Rc::new(RefCell::new(keymap)),
+            preferences: Rc::new(RefCell::new(preferences)),
+            on_apply: Rc::new(RefCell::new(None)),
+        };
+
+        dialog.color.clone().set_callback(|color| {
+            let (r, g, b) = color_chooser_with_default(
+                "Color of new objects",
+                ColorMode::Rgb,
+                color.color().to_rgb(),
+            );
+            color.set_color(Color::from_rgb(r, g, b));
+            color.redraw();
+        });
+        dialog.list.clone().set_callback({
+            let mut dialog = dialog.clone();
+            move |_| dialog.show_selected()
+        });
+        dialog.capture.clone().handle({
+            let mut dialog = dialog.clone();
+            move |capture, event| match event {
+                Event::Push => {
+                    capture.take_focus().ok();
+                    true
+                }
+                Event::Focus => {
+                    capture.set_color(Color::Selection.lighter());
+                    capture.redraw();
+                    true
+                }
+                Event::Unfocus => {
+                    capture.set_color(Color::Background2);
+                    capture.redraw();
+                    true
+                }
+                Event::KeyDown => {
+                    if let Some(key) = format_key(app::event_key(), app::event_state()) {
+                        dialog.bind(&key);
+                    }
+                    true
+                }
+                _ => false,
+            }
+        });
+        none.set_callback({
+            let mut dialog = dialog.clone();
+            move |_| dialog.bind("none")
+        });
+        defaults.set_callback({
+            let mut dialog = dialog.clone();
+            move |_| {
+                *dialog.keymap.borrow_mut() = Keymap::new();
+                dialog
+                    .message
+                    .set_label("All keys are back to their defaults");
+                dialog.refresh();
+            }
+        });
+        cancel.set_callback({
+            let mut window = dialog.window.clone();
+            move |_| window.hide()
+        });
+        ok.set_callback({
+            let mut dialog = dialog.clone();
+            move |_| dialog.apply()
+        });
+        dialog
+    }
+
+    // what OK does with the new settings, besides saving them
+    pub fn set_callback(&mut self, on_apply: impl FnMut(&Keymap, &Preferences) + 'static) {
+        *self.on_apply.borrow_mut() = Some(Box::new(on_apply));
+    }
+
+    // opens the dialog with the settings in use now
+    pub fn show(&mut self) {
+        *self.keymap.borrow_mut() = self.applied.borrow_mut().clone();
+        self.message.set_label("");
+        self.refresh();
+        self.show_general();
+        self.window.show();
+    }
+
+    fn show_general(&mut self) {
+        let preferences = self.preferences.borrow_mut().clone();
+        let theme = THEMES
+            .iter()
+            .position(|(_, t)| *t == preferences.get_theme())
+            .unwrap_or(0);
+        self.theme.set_value(theme as i32);
+        let (r, g, b) = preferences.get_color();
+        self.color.set_color(Color::from_rgb(r, g, b));
+        self.fill.set_checked(preferences.get_fill());
+        self.width.set_value(preferences.get_width() as f64);
+        self.precision.set_value(preferences.get_precision() as f64);
+        self.grid.set_checked(preferences.get_grid());
+        self.snap.set_checked(preferences.get_snap());
+    }
+
+    // the settings of the General tab, the window size and the directory stay the way
+    // the config file has them, they change while the dialog is closed
+    fn get_general(&self) -> Preferences {
+        let mut preferences = Preferences::load();
+        if let Some((_, theme)) = THEMES.get(self.theme.value().max(0) as usize) {
+            preferences.set_theme(*theme);
+        }
+        preferences.set_color(self.color.color().to_rgb());
+        preferences.set_fill(self.fill.is_checked());
+        preferences.set_width(self.width.value().round() as i32);
+        preferences.set_precision(self.precision.value().round().max(0.0) as usize);
+        preferences.set_grid(self.grid.is_checked());
+        preferences.set_snap(self.snap.is_checked());
+        preferences
+    }
+
+    fn refresh(&mut self) {
+        let selected = self.list.value();
+        self.list.clear();
+        for (action, key) in self.keymap.borrow_mut().get_keys() {
+            self.list.add(&format!("{}\t{}", action, key));
+        }
+        if selected > 0 {
+            self.list.select(selected);
+        }
+        self.show_selected();
+    }
+
+    fn get_selected(&self) -> Option<String> {
+        let i = self.list.value();
+        let keymap = self.keymap.borrow_mut();
+        let (action, _) = keymap.get_keys().get((i as usize).checked_sub(1)?)?;
+        Some(action.clone())
+    }
+
+    fn show_selected(&mut self) {
+        let label = match self.get_selected() {
+            Some(action) => self.keymap.borrow_mut().get_key(&action).to_string(),
+            None => String::from("select an action"),
+        };
+        self.capture.set_label(&label);
+        self.capture.redraw();
+    }
+
+    // gives the selected action the key
+    fn bind(&mut self, key: &str) {
+        let action = match self.get_selected() {
+            Some(action) => action,
+            None => return self.message.set_label("Select the action first"),
+        };
+        let result = self.keymap.borrow_mut().set_key(&action, key);
+        match result {
+            Ok(Some(previous)) => self
+                .message
+                .set_label(&format!("{} has no key now, it had {}", previous, key)),
+            Ok(None) => self.message.set_label(""),
+            Err(e) => self.message.set_label(&e),
+        }
+        self.refresh();
+    }
+
+    fn apply(&mut self) {
+        let keymap = self.keymap.borrow_mut().clone();
+        *self.applied.borrow_mut() = keymap.clone();
+        let preferences = self.get_general();
+        *self.preferences.borrow_mut() = preferences.clone();
+        if let Err(e) = keymap.save().and_then(|_| preferences.save()) {
+            alert_default(&e);
+        }
+        if let Some(on_apply) = self.on_apply.borrow_mut().as_mut() {
+            on_apply(&keymap, &preferences);
+        }
+        self.window.hide();
+    }
+}
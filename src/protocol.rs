@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+
+// one step of the construction protocol; the scene only grows between two clears, so what a
+// step added is everything between where the lists of shapes, clicked points and measurements
+// ended after the step before and where they end after it
+#[derive(Debug, Clone)]
+pub struct Step {
+    tool: String,
+    inputs: Vec<String>,  // names of the objects used or coordinates of the clicks
+    outputs: Vec<String>, // names of the objects made
+    shapes_end: usize,
+    points_end: usize,
+    measurements_end: usize,
+}
+
+impl Step {
+    pub fn new(
+        tool: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        ends: (usize, usize, usize),
+    ) -> Step {
+        Step {
+            tool,
+            inputs,
+            outputs,
+            shapes_end: ends.0,
+            points_end: ends.1,
+            measurements_end: ends.2,
+        }
+    }
+
+    pub fn get_tool(&self) -> &str {
+        &self.tool
+    }
+
+    pub fn get_inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn get_outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    // how many shapes, clicked points and measurements there are after this step
+    pub fn get_ends(&self) -> (usize, usize, usize) {
+        (self.shapes_end, self.points_end, self.measurements_end)
+    }
+}
+
+// the steps once some objects are deleted; kept_shapes[n] and kept_measurements[n] are how
+// many of the first n shapes and measurements are left, a step with nothing left is dropped
+pub fn remove_objects(
+    steps: &[Step],
+    kept_shapes: &[usize],
+    kept_measurements: &[usize],
+    deleted: &[String],
+) -> Vec<Step> {
+    let mut result: Vec<Step> = vec![];
+    for step in steps {
+        let start = result.last().map_or((0, 0, 0), |s| s.get_ends());
+        let end = (
+            kept_shapes[step.shapes_end],
+            step.points_end,
+            kept_measurements[step.measurements_end],
+        );
+        if end == start {
+            continue;
+        }
+        let mut step = step.clone();
+        step.outputs.retain(|name| !deleted.contains(name));
+        if end.2 == start.2 {
+            step.outputs.retain(|output| output != "measurement");
+        }
+        (step.shapes_end, step.points_end, step.measurements_end) = end;
+        result.push(step);
+    }
+    result
+}
+
+// where the lists end after the first n steps, None when all of them are shown
+pub fn get_ends(steps: &[Step], n: Option<usize>) -> Option<(usize, usize, usize)> {
+    match n {
+        Some(0) => Some((0, 0, 0)),
+        Some(n) if n < steps.len() => Some(steps[n - 1].get_ends()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // how many of the first n items are left, like the canvas works it out
+    fn kept(deleted: &[bool]) -> Vec<usize> {
+        let mut kept = vec![0];
+        for d in deleted {
+            kept.push(kept[kept.len() - 1] + !*d as usize);
+        }
+        kept
+    }
+
+    fn step(tool: &str, outputs: &[&str], ends: (usize, usize, usize)) -> Step {
+        let outputs = outputs.iter().map(|o| o.to_string()).collect();
+        Step::new(tool.to_string(), vec![], outputs, ends)
+    }
+
+    // A, then the line a through it, the parallel b and a measurement of a
+    fn steps() -> Vec<Step> {
+        vec![
+            step("Point", &["A"], (1, 1, 0)),
+            step("Line", &["a"], (2, 3, 0)),
+            step("Parallel", &["b"], (3, 3, 0)),
+            step("Distance", &["measurement"], (3, 3, 1)),
+        ]
+    }
+
+    #[test]
+    fn replay_shows_what_each_step_made() {
+        let shapes = ["A", "a", "b"];
+        let steps = steps();
+        let replayed = |n: usize| {
+            let (end, ..) = get_ends(&steps, Some(n)).unwrap_or((shapes.len(), 0, 0));
+            shapes[..end].to_vec()
+        };
+        assert!(replayed(0).is_empty());
+        assert_eq!(replayed(1), ["A"]);
+        assert_eq!(replayed(2), ["A", "a"]);
+        assert_eq!(replayed(3), ["A", "a", "b"]);
+        assert_eq!(get_ends(&steps, Some(3)), Some((3, 3, 0)));
+        assert_eq!(get_ends(&steps, Some(4)), None);
+        assert_eq!(get_ends(&steps, None), None);
+    }
+
+    #[test]
+    fn deleting_keeps_the_steps_in_line() {
+        // deleting a takes b and the measurement with it
+        let deleted = [false, true, true];
+        let left = remove_objects(
+            &steps(),
+            &kept(&deleted),
+            &kept(&[true]),
+            &[String::from("a"), String::from("b")],
+        );
+        // the line still has its clicked points, so its step stays for them
+        assert_eq!(left.len(), 2);
+        assert_eq!(left[0].get_ends(), (1, 1, 0));
+        assert_eq!(left[1].get_ends(), (1, 3, 0));
+        assert!(left[1].get_outputs().is_empty());
+
+        // deleting b leaves the measurement of a
+        let deleted = [false, false, true];
+        let left = remove_objects(
+            &steps(),
+            &kept(&deleted),
+            &kept(&[false]),
+            &[String::from("b")],
+        );
+        let tools: Vec<&str> = left.iter().map(|s| s.get_tool()).collect();
+        assert_eq!(tools, ["Point", "Line", "Distance"]);
+        assert_eq!(left[2].get_ends(), (2, 3, 1));
+        assert_eq!(left[2].get_outputs(), ["measurement"]);
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(dead_code)]
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use fltk::app::{self, TimeoutHandle};
+use fltk::enums::{Align, Color, FrameType};
+use fltk::prelude::{BrowserExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::{browser::HoldBrowser, button::Button, frame::Frame, group::Group};
+
+use crate::canvas::Canvas;
+
+const ROW_HEIGHT: i32 = 30;
+const BUTTON_WIDTH: i32 = 40;
+const PLAY_WIDTH: i32 = 90;
+// seconds between two steps while playing
+const PLAY_DELAY: f64 = 0.8;
+
+// a panel with every step of the construction, which tool made what from which objects,
+// and buttons to go through it again; the canvas only shows the steps up to the
+// selected one, the later ones come back at the last step or with the next new object
+#[derive(Clone)]
+pub struct ProtocolView {
+    group: Group,
+    list: HoldBrowser,
+    position: Frame,
+    play: Button,
+    canvas: Canvas,
+    playing: Rc<RefCell<Option<TimeoutHandle>>>,
+}
+
+impl ProtocolView {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let mut group = Group::new(x, y, w, h, None);
+        group.set_frame(FrameType::FlatBox);
+        group.set_color(Color::Background2);
+
+        let labels = ["@|<", "@<", "@>", "@>|"];
+        let mut buttons = vec![];
+        for (i, label) in labels.iter().enumerate() {
+            let bx = x + 5 + i as i32 * BUTTON_WIDTH;
+            buttons.push(Button::new(bx, y + 5, BUTTON_WIDTH, ROW_HEIGHT, *label));
+        }
+        let play_x = x + 10 + labels.len() as i32 * BUTTON_WIDTH;
+        let play = Button::new(play_x, y + 5, PLAY_WIDTH, ROW_HEIGHT, "Play");
+        let text_x = play_x + PLAY_WIDTH + 10;
+        let mut position = Frame::new(text_x, y + 5, x + w - text_x - 5, ROW_HEIGHT, None);
+        position.set_align(Align::Left | Align::Inside);
+
+        let mut list = HoldBrowser::new(
+            x + 5,
+            y + ROW_HEIGHT + 10,
+            w - 10,
+            h - ROW_HEIGHT - 15,
+            None,
+        );
+        list.set_column_char('\t');
+        list.set_column_widths(&[40, 200, (w - 250) / 2]);
+        group.end();
+        // the buttons keep their place on the left when the panel gets wider
+        group.resizable(&position);
+        group.hide();
+
+        let mut view = ProtocolView {
+            group,
+            list,
+            position,
+            play,
+            canvas,
+            playing: Rc::new(RefCell::new(None)),
+        };
+
+        // first, previous, next and last step
+        let moves: [fn(usize, usize) -> usize; 4] = [
+            |_, _| 1,
+            |current, _| current.saturating_sub(1),
+            |current, len| (current + 1).min(len),
+            |_, len| len,
+        ];
+        for (mut button, step) in buttons.into_iter().zip(moves) {
+            let mut view = view.clone();
+            button.set_callback(move |_| {
+                view.stop();
+                let len = view.canvas.get_protocol().len();
+                let current = view.canvas.get_replay().unwrap_or(len);
+                view.canvas.set_replay(Some(step(current, len)));
+            });
+        }
+        view.play.set_callback({
+            let mut view = view.clone();
+            move |_| view.toggle_play()
+        });
+        view.list.set_callback({
+            let mut view = view.clone();
+            move |list| {
+                if list.value() > 0 {
+                    view.stop();
+                    view.canvas.set_replay(Some(list.value() as usize));
+                }
+            }
+        });
+        view.canvas.add_on_change({
+            let mut view = view.clone();
+            move || view.refresh()
+        });
+        view
+    }
+
+    // shows the panel if it is hidden and the other way around
+    pub fn toggle(&mut self) {
+        if self.group.visible() {
+            self.group.hide();
+        } else {
+            self.refresh();
+            self.group.show();
+        }
+        // the canvas beside the panel grows or shrinks with it
+        if let Some(mut window) = self.group.window() {
+            window.redraw();
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.group.visible()
+    }
+
+    // for the layout to dock it
+    pub fn get_group(&self) -> &Group {
+        &self.group
+    }
+
+    // writes the steps again with the one the canvas is at selected
+    pub fn refresh(&mut self) {
+        let protocol = self.canvas.get_protocol();
+        self.list.clear();
+        for (i, step) in protocol.iter().enumerate() {
+            self.list.add(&format!(
+                "{}\t{}\t{}\t{}",
+                i + 1,
+                step.get_tool(),
+                step.get_inputs().join(", "),
+                step.get_outputs().join(", ")
+            ));
+        }
+        let current = self.canvas.get_replay().unwrap_or(protocol.len());
+        if current > 0 {
+            self.list.select(current as i32);
+            self.list.middle_line(current as i32);
+        }
+        let position = match self.canvas.get_replay() {
+            Some(n) => format!("step {} of {}", n, protocol.len()),
+            None => format!("all {} steps", protocol.len()),
+        };
+        self.position.set_label(&position);
+        self.group.redraw();
+    }
+
+    // plays the construction from the first step, or stops playing it
+    fn toggle_play(&mut self) {
+        if self.playing.borrow_mut().is_some() {
+            return self.stop();
+        }
+        if self.canvas.get_protocol().is_empty() {
+            return;
+        }
+        self.canvas.set_replay(Some(1));
+        self.play.set_label("Pause");
+        let handle = app::add_timeout3(PLAY_DELAY, {
+            let mut view = self.clone();
+            move |handle| {
+                // showing all of them, or anything else changing the replay, is the end
+                let next = view.canvas.get_replay().map(|n| n + 1);
+                view.canvas.set_replay(next);
+                if view.canvas.get_replay().is_some() {
+                    app::repeat_timeout3(PLAY_DELAY, handle);
+                } else {
+                    view.playing.borrow_mut().take();
+                    view.play.set_label("Play");
+                }
+            }
+        });
+        *self.playing.borrow_mut() = Some(handle);
+    }
+
+    fn stop(&mut self) {
+        if let Some(handle) = self.playing.borrow_mut().take() {
+            app::remove_timeout3(handle);
+        }
+        self.play.set_label("Play");
+    }
+}
@@ -0,0 +1,397 @@
+#![allow(dead_code)]
+use std::f64;
+
+use crate::png;
+use crate::render::{Pen, Renderer};
+use crate::style::{LinePattern, Marker};
+
+// rows are sampled this many times, along a row the coverage is exact
+const SUBSAMPLES: usize = 4;
+// how far a flattened circle may be off, in device pixels
+const FLATNESS: f64 = 0.2;
+
+// an anti-aliased RGB image the scene is drawn into; it takes coordinates in screen
+// pixels and has ratio device pixels for each of them, so it stays sharp on HiDPI screens
+pub struct Raster {
+    width: usize,
+    height: usize,
+    ratio: f64,
+    pixels: Vec<u8>,
+    pen: Pen,
+}
+
+// an edge of a filled outline going from top to bottom, dir is -1 if it was drawn upwards
+struct Edge {
+    x0: f64,
+    y0: f64,
+    y1: f64,
+    slope: f64,
+    dir: i32,
+}
+
+impl Raster {
+    // a white image of width by height screen pixels
+    pub fn new(width: i32, height: i32, ratio: f64) -> Raster {
+        let width = (width.max(1) as f64 * ratio).round() as usize;
+        let height = (height.max(1) as f64 * ratio).round() as usize;
+        Raster {
+            width,
+            height,
+            ratio,
+            pixels: vec![255; width * height * 3],
+            pen: Pen::new((0, 0, 0), 1.0, LinePattern::Solid, 1.0),
+        }
+    }
+
+    // the size in device pixels
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    // three bytes for each pixel, row by row from the top
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode(self.width, self.height, &self.pixels)
+    }
+
+    fn to_device(&self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        points
+            .iter()
+            .map(|(x, y)| (x * self.ratio, y * self.ratio))
+            .collect()
+    }
+
+    // fills the union of the outlines, which all have to go around the same way where they
+    // overlap, with the color blended in by how much of each pixel they cover
+    fn fill(&mut self, outlines: &[Vec<(f64, f64)>], color: (u8, u8, u8), opacity: f64) {
+        let mut edges: Vec<Edge> = vec![];
+        for outline in outlines.iter().filter(|o| o.len() > 2) {
+            for (i, &(xa, ya)) in outline.iter().enumerate() {
+                let (xb, yb) = outline[(i + 1) % outline.len()];
+                if ya == yb
+                    || !(xa.is_finite() && ya.is_finite() && xb.is_finite() && yb.is_finite())
+                {
+                    continue;
+                }
+                let (x0, y0, y1, dir) = if ya < yb {
+                    (xa, ya, yb, 1)
+                } else {
+                    (xb, yb, ya, -1)
+                };
+                edges.push(Edge {
+                    x0,
+                    y0,
+                    y1,
+                    slope: (xb - xa) / (yb - ya),
+                    dir,
+                });
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+        edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+        let top = edges[0].y0.floor().max(0.0) as usize;
+        let bottom = edges
+            .iter()
+            .map(|e| e.y1)
+            .fold(f64::MIN, f64::max)
+            .ceil()
+            .min(self.height as f64) as usize;
+
+        let weight = 1.0 / SUBSAMPLES as f64;
+        let mut coverage = vec![0.0; self.width];
+        let mut active: Vec<usize> = vec![];
+        let mut next = 0;
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for row in top..bottom {
+            coverage.iter_mut().for_each(|c| *c = 0.0);
+            let (mut left, mut right) = (self.width, 0);
+            for k in 0..SUBSAMPLES {
+                let y = row as f64 + (k as f64 + 0.5) * weight;
+                while next < edges.len() && edges[next].y0 <= y {
+                    active.push(next);
+                    next += 1;
+                }
+                active.retain(|&i| edges[i].y1 > y);
+                crossings.clear();
+                for &i in active.iter().filter(|&&i| edges[i].y0 <= y) {
+                    let e = &edges[i];
+                    crossings.push((e.x0 + (y - e.y0) * e.slope, e.dir));
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+                // nonzero winding, a span starts where the count leaves 0 and ends where it is back
+                let mut winding = 0;
+                let mut start = 0.0;
+                for &(x, dir) in crossings.iter() {
+                    if winding == 0 {
+                        start = x;
+                    }
+                    winding += dir;
+                    if winding == 0 {
+                        let (a, b) = (start.max(0.0), x.min(self.width as f64));
+                        if a < b {
+                            add_span(&mut coverage, a, b, weight);
+                            left = left.min(a as usize);
+                            right = right.max(b.ceil() as usize);
+                        }
+                    }
+                }
+            }
+            let end = right.min(self.width);
+            for (x, covered) in coverage.iter().enumerate().take(end).skip(left) {
+                let alpha = covered.min(1.0) * opacity;
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let i = (row * self.width + x) * 3;
+                for (channel, value) in [color.0, color.1, color.2].iter().enumerate() {
+                    let old = self.pixels[i + channel] as f64;
+                    self.pixels[i + channel] = (old + (*value as f64 - old) * alpha).round() as u8;
+                }
+            }
+        }
+    }
+
+    // the outlines a line along the points makes with the pen, in device pixels
+    fn get_stroke(&self, points: &[(f64, f64)]) -> Vec<Vec<(f64, f64)>> {
+        let width = self.pen.get_width() * self.ratio;
+        let pieces = match self.pen.get_pattern() {
+            LinePattern::Solid => vec![points.to_vec()],
+            LinePattern::Dashed => split_dashes(points, &[width * 4.0, width * 2.0]),
+            // round ends make a dot out of a dash without length
+            LinePattern::Dotted => split_dashes(points, &[0.0, width * 2.0]),
+            LinePattern::DashDot => {
+                split_dashes(points, &[width * 4.0, width * 2.0, 0.0, width * 2.0])
+            }
+        };
+        let r = width / 2.0;
+        let mut outlines = vec![];
+        for piece in pieces {
+            for (i, &(x, y)) in piece.iter().enumerate() {
+                // round joins and ends
+                outlines.push(get_circle((x, y), r));
+                let (qx, qy) = match piece.get(i + 1) {
+                    Some(q) => *q,
+                    None => break,
+                };
+                let len = ((qx - x).powi(2) + (qy - y).powi(2)).sqrt();
+                if len == 0.0 {
+                    continue;
+                }
+                let (nx, ny) = (-(qy - y) / len * r, (qx - x) / len * r);
+                outlines.push(vec![
+                    (x + nx, y + ny),
+                    (qx + nx, qy + ny),
+                    (qx - nx, qy - ny),
+                    (x - nx, y - ny),
+                ]);
+            }
+        }
+        for outline in outlines.iter_mut() {
+            orient(outline);
+        }
+        outlines
+    }
+
+    fn stroke(&mut self, points: &[(f64, f64)]) {
+        let outlines = self.get_stroke(&self.to_device(points));
+        self.fill(&outlines, self.pen.get_color(), self.pen.get_opacity());
+    }
+
+    fn fill_outline(&mut self, outline: Vec<(f64, f64)>, fill: Option<(u8, u8, u8)>) {
+        if let Some(fill) = fill {
+            self.fill(&[outline], fill, self.pen.get_opacity());
+        }
+    }
+}
+
+impl Renderer for Raster {
+    fn set_pen(&mut self, pen: Pen) {
+        self.pen = pen;
+    }
+
+    fn draw_polyline(&mut self, points: &[(f64, f64)]) {
+        self.stroke(points);
+    }
+
+    fn draw_polygon(&mut self, points: &[(f64, f64)], fill: Option<(u8, u8, u8)>) {
+        self.fill_outline(self.to_device(points), fill);
+        let mut closed = points.to_vec();
+        closed.extend(points.first());
+        self.stroke(&closed);
+    }
+
+    fn draw_circle(&mut self, (x, y): (f64, f64), rad: f64, fill: Option<(u8, u8, u8)>) {
+        let outline = get_circle((x * self.ratio, y * self.ratio), rad * self.ratio);
+        self.fill_outline(outline.clone(), fill);
+        let mut closed: Vec<(f64, f64)> = outline
+            .iter()
+            .map(|(x, y)| (x / self.ratio, y / self.ratio))
+            .collect();
+        closed.extend(closed.first().copied());
+        self.stroke(&closed);
+    }
+
+    fn draw_arc(
+        &mut self,
+        (x, y): (f64, f64),
+        rad: f64,
+        start: f64,
+        sweep: f64,
+        fill: Option<(u8, u8, u8)>,
+    ) {
+        let n = get_segments(rad * self.ratio, sweep.abs());
+        let arc: Vec<(f64, f64)> = (0..=n)
+            .map(|i| {
+                let angle = start + sweep * i as f64 / n as f64;
+                (x + rad * angle.cos(), y - rad * angle.sin())
+            })
+            .collect();
+        if fill.is_some() {
+            let mut pie = arc.clone();
+            pie.push((x, y));
+            self.fill_outline(self.to_device(&pie), fill);
+        }
+        self.stroke(&arc);
+    }
+
+    fn draw_marker(&mut self, (x, y): (f64, f64), marker: Marker, r: f64) {
+        let pen = self.pen;
+        let thin = Pen::new(pen.get_color(), 2.0, LinePattern::Solid, pen.get_opacity());
+        match marker {
+            Marker::Dot => {
+                let dot = get_circle((x * self.ratio, y * self.ratio), r * self.ratio);
+                self.fill(&[dot], pen.get_color(), pen.get_opacity());
+            }
+            Marker::Circle => {
+                self.pen = thin;
+                self.draw_circle((x, y), r, None);
+            }
+            Marker::Cross => {
+                self.pen = thin;
+                let outlines: Vec<Vec<(f64, f64)>> = [
+                    [(x - r, y - r), (x + r, y + r)],
+                    [(x - r, y + r), (x + r, y - r)],
+                ]
+                .iter()
+                .flat_map(|line| self.get_stroke(&self.to_device(line)))
+                .collect();
+                self.fill(&outlines, pen.get_color(), pen.get_opacity());
+            }
+            Marker::Square => {
+                self.pen = thin;
+                let square = [
+                    (x - r, y - r),
+                    (x + r, y - r),
+                    (x + r, y + r),
+                    (x - r, y + r),
+                ];
+                self.draw_polygon(&square, None);
+            }
+            Marker::Diamond => {
+                self.pen = thin;
+                self.draw_polygon(&[(x, y - r), (x + r, y), (x, y + r), (x - r, y)], None);
+            }
+        }
+        self.pen = pen;
+    }
+}
+
+// adds weight for each pixel between a and b, the pixels at the ends only partly
+fn add_span(coverage: &mut [f64], a: f64, b: f64, weight: f64) {
+    let (first, last) = (a.floor() as usize, b.floor() as usize);
+    if first == last {
+        coverage[first] += (b - a) * weight;
+        return;
+    }
+    coverage[first] += (first as f64 + 1.0 - a) * weight;
+    for c in coverage[first + 1..last].iter_mut() {
+        *c += weight;
+    }
+    if last < coverage.len() {
+        coverage[last] += (b - last as f64) * weight;
+    }
+}
+
+// how many straight pieces an arc of this radius and angle needs to look round
+fn get_segments(rad: f64, angle: f64) -> usize {
+    if rad <= FLATNESS {
+        return 4;
+    }
+    let step = 2.0 * (1.0 - FLATNESS / rad).acos();
+    ((angle / step).ceil() as usize).clamp(4, 1 << 16)
+}
+
+fn get_circle((x, y): (f64, f64), rad: f64) -> Vec<(f64, f64)> {
+    let n = get_segments(rad, 2.0 * f64::consts::PI).max(8);
+    (0..n)
+        .map(|i| {
+            let angle = 2.0 * f64::consts::PI * i as f64 / n as f64;
+            (x + rad * angle.cos(), y + rad * angle.sin())
+        })
+        .collect()
+}
+
+// turns the outline around if it goes the other way, so overlapping ones add up
+fn orient(outline: &mut [(f64, f64)]) {
+    let area: f64 = (0..outline.len())
+        .map(|i| {
+            let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    if area < 0.0 {
+        outline.reverse();
+    }
+}
+
+// the pieces of the line the dashes cover, the pattern is on, off, on, ... in pixels
+fn split_dashes(points: &[(f64, f64)], pattern: &[f64]) -> Vec<Vec<(f64, f64)>> {
+    if pattern.iter().sum::<f64>() <= 0.0 {
+        return vec![points.to_vec()];
+    }
+    let mut pieces = vec![];
+    let mut current: Vec<(f64, f64)> = vec![];
+    let (mut k, mut left) = (0, pattern[0]);
+    for (i, &p) in points.iter().enumerate() {
+        if k % 2 == 0 {
+            current.push(p);
+        }
+        let q = match points.get(i + 1) {
+            Some(q) => *q,
+            None => break,
+        };
+        let len = ((q.0 - p.0).powi(2) + (q.1 - p.1).powi(2)).sqrt();
+        if len == 0.0 {
+            continue;
+        }
+        let mut done = 0.0;
+        // every change between on and off along this segment
+        while done + left <= len {
+            done += left;
+            let t = done / len;
+            let at = (p.0 + (q.0 - p.0) * t, p.1 + (q.1 - p.1) * t);
+            if k % 2 == 0 {
+                current.push(at);
+                pieces.push(std::mem::take(&mut current));
+            } else {
+                current.push(at);
+            }
+            k = (k + 1) % pattern.len();
+            left = pattern[k];
+        }
+        left -= len - done;
+    }
+    if k % 2 == 0 && !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
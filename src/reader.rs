@@ -4,25 +4,53 @@ use std::{path::{Path, PathBuf}, fs::File, io::{BufReader, BufRead}};
 use crate::figures::point::Point;
 use tinyfiledialogs;
 
-pub fn filereader() -> Vec<Point> {
-    let p = tinyfiledialogs::open_file_dialog("Select a file: ", "", None);
-    let path = match p {
-        Some(path) => Some(PathBuf::from(path)),
-        _ => None
-    };
+// starts in dir, if there is one
+pub fn choose_file(dir: Option<&Path>) -> Option<PathBuf> {
+    // with the separator at the end the dialog shows the directory instead of naming a file
+    let start = dir.map_or(String::new(), |dir| {
+        format!("{}{}", dir.display(), std::path::MAIN_SEPARATOR)
+    });
+    let p = tinyfiledialogs::open_file_dialog("Select a file: ", &start, None);
+    p.map(PathBuf::from)
+}
+
+pub fn choose_image_file() -> Option<PathBuf> {
+    let p = tinyfiledialogs::save_file_dialog_with_filter(
+        "Export image: ",
+        "image.png",
+        &["*.png", "*.svg"],
+        "PNG or SVG image",
+    );
+    p.map(PathBuf::from)
+}
 
-    let f = File::open(path.unwrap()).expect("File not found!");
+// one point per line, "x,y" in pixels from the top left of the canvas, blank lines are left out;
+// the error names the file and the line that can't be read
+pub fn filereader(path: &Path) -> Result<Vec<Point>, String> {
+    let f = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
     let buf = BufReader::new(f);
-    let input: Vec<String> = buf.lines().map(|l| l.expect("could not parse line")).collect();
     
     let mut points: Vec<Point> = Vec::new();
     
-    for line in input {
-        let mut split = line.split(',');
-        let x = split.next().unwrap().parse::<f64>().unwrap();
-        let y = split.next().unwrap().parse::<f64>().unwrap();
-        points.push(Point::new(x, y));
+    for (i, line) in buf.lines().enumerate() {
+        let line = line.map_err(|e| format!("{}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let point = line.split_once(',').and_then(|(x, y)| {
+            Some(Point::new(x.trim().parse::<f64>().ok()?, y.trim().parse::<f64>().ok()?))
+        });
+        match point {
+            Some(p) => points.push(p),
+            None => {
+                return Err(format!(
+                    "{}: line {}: a point is written like 200.0,400.8",
+                    path.display(),
+                    i + 1
+                ))
+            }
+        }
     }
 
-    points
+    Ok(points)
 }
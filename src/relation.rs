@@ -0,0 +1,398 @@
+use crate::figures::{circle::Circle, line::Line, point::Point, Figure};
+use crate::tooltip::{format_number, format_point};
+use crate::utils::{
+    are_collinear, are_concyclic, get_angle_between_slopes, get_circles_intersection, get_distance,
+    get_intersections, get_line_circle_intersection, get_line_distance, get_perpendicular_line,
+    get_point_of_intersection, get_segment_distance, is_orthogonal, is_parallel,
+};
+
+// distances and angles closer than this count as equal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    distance: f64, // in world units
+    angle: f64,    // in radians
+}
+
+impl Tolerance {
+    pub fn new(distance: f64, angle: f64) -> Tolerance {
+        Tolerance {
+            distance: distance.abs(),
+            angle: angle.abs(),
+        }
+    }
+
+    pub fn get_distance(&self) -> f64 {
+        self.distance
+    }
+
+    pub fn get_angle(&self) -> f64 {
+        self.angle
+    }
+}
+
+// everything that can be said about two figures, the numbers with precision decimals
+pub fn get_relations(
+    f1: &Figure,
+    f2: &Figure,
+    tolerance: Tolerance,
+    precision: usize,
+    bounds: (f64, f64, f64, f64),
+) -> Vec<String> {
+    let distance = tolerance.get_distance();
+    match (f1, f2) {
+        (Figure::Point(p), Figure::Point(q)) => {
+            if get_distance(*p, *q) <= distance {
+                vec![String::from("the points are the same")]
+            } else {
+                vec![format!(
+                    "the points are {} apart",
+                    format_number(get_distance(*p, *q), precision)
+                )]
+            }
+        }
+        (Figure::Point(p), other) | (other, Figure::Point(p)) => {
+            get_point_relations(*p, other, distance, precision, bounds)
+        }
+        (Figure::Line(l1), Figure::Line(l2)) => get_line_relations(*l1, *l2, tolerance, precision),
+        (Figure::Line(l), Figure::Circle(c)) | (Figure::Circle(c), Figure::Line(l)) => {
+            get_line_circle_relations(*l, *c, distance, precision)
+        }
+        (Figure::Circle(c1), Figure::Circle(c2)) => {
+            get_circle_relations(*c1, *c2, distance, precision)
+        }
+        _ => vec![describe_intersections(
+            &get_intersections(f1, f2, bounds),
+            precision,
+        )],
+    }
+}
+
+// collinear and concyclic for three or more points, tolerance is a distance
+pub fn get_points_relations(points: &[Point], tolerance: f64) -> Vec<String> {
+    let mut relations = vec![];
+    if points.len() >= 3 {
+        if are_collinear(points, tolerance) {
+            relations.push(String::from("the points are collinear"));
+        } else {
+            relations.push(String::from("the points are not collinear"));
+        }
+    }
+    if points.len() >= 4 {
+        if are_concyclic(points, tolerance) {
+            relations.push(String::from("the points are concyclic"));
+        } else {
+            relations.push(String::from("the points are not concyclic"));
+        }
+    }
+    relations
+}
+
+fn get_point_relations(
+    p: Point,
+    figure: &Figure,
+    tolerance: f64,
+    precision: usize,
+    bounds: (f64, f64, f64, f64),
+) -> Vec<String> {
+    let distance = match figure {
+        Figure::Line(l) => get_line_distance(p, *l),
+        Figure::Circle(c) => {
+            let d = get_distance(p, c.get_center());
+            if (d - c.get_rad()).abs() <= tolerance {
+                return vec![String::from("the point lies on the circle")];
+            } else if d < c.get_rad() {
+                return vec![String::from("the point lies inside the circle")];
+            }
+            return vec![String::from("the point lies outside the circle")];
+        }
+        Figure::Arc(a) => {
+            if a.contains_point(p) {
+                (get_distance(p, a.get_center()) - a.get_rad()).abs()
+            } else {
+                get_distance(p, a.get_startpoint()).min(get_distance(p, a.get_endpoint()))
+            }
+        }
+        Figure::Rectangle(r) => {
+            let (a, b) = (r.get_point_a(), r.get_point_b());
+            let corners = [
+                a,
+                Point::new(a.get_x(), b.get_y()),
+                b,
+                Point::new(b.get_x(), a.get_y()),
+                a,
+            ];
+            let d = get_polyline_distance(p, &corners);
+            if d > tolerance {
+                let inside = (a.get_x().min(b.get_x())..=a.get_x().max(b.get_x()))
+                    .contains(&p.get_x())
+                    && (a.get_y().min(b.get_y())..=a.get_y().max(b.get_y())).contains(&p.get_y());
+                if inside {
+                    return vec![String::from("the point lies inside the rectangle")];
+                }
+            }
+            d
+        }
+        _ => match figure.get_polylines(tolerance / 4.0, bounds) {
+            Some(pieces) => pieces
+                .iter()
+                .map(|piece| get_polyline_distance(p, piece))
+                .fold(f64::INFINITY, f64::min),
+            None => return vec![],
+        },
+    };
+    if distance <= tolerance {
+        vec![String::from("the point lies on the figure")]
+    } else {
+        vec![format!(
+            "the point is {} away from the figure",
+            format_number(distance, precision)
+        )]
+    }
+}
+
+fn get_line_relations(l1: Line, l2: Line, tolerance: Tolerance, precision: usize) -> Vec<String> {
+    if is_parallel(l1.get_slope(), l2.get_slope(), tolerance.get_angle()) {
+        let distance = get_line_distance(l2.get_point_a(), l1);
+        if distance <= tolerance.get_distance() {
+            return vec![String::from("the lines are the same")];
+        }
+        return vec![format!(
+            "the lines are parallel, {} apart",
+            format_number(distance, precision)
+        )];
+    }
+    let p = get_point_of_intersection(l1, l2);
+    if is_orthogonal(l1.get_slope(), l2.get_slope(), tolerance.get_angle()) {
+        return vec![format!(
+            "the lines are perpendicular, meeting at {}",
+            format_point(p, precision)
+        )];
+    }
+    let angle = get_angle_between_slopes(l1.get_slope(), l2.get_slope());
+    vec![format!(
+        "the lines intersect at {} at an angle of {}°",
+        format_point(p, precision),
+        format_number(angle.to_degrees(), precision)
+    )]
+}
+
+fn get_line_circle_relations(l: Line, c: Circle, tolerance: f64, precision: usize) -> Vec<String> {
+    let distance = get_line_distance(c.get_center(), l);
+    if (distance - c.get_rad()).abs() <= tolerance {
+        let foot = get_point_of_intersection(l, get_perpendicular_line(l, c.get_center()));
+        return vec![format!(
+            "the line is tangent to the circle at {}",
+            format_point(foot, precision)
+        )];
+    }
+    if distance <= tolerance {
+        return vec![
+            String::from("the line goes through the center of the circle"),
+            describe_intersections(&get_line_circle_intersection(l, c), precision),
+        ];
+    }
+    vec![describe_intersections(
+        &get_line_circle_intersection(l, c),
+        precision,
+    )]
+}
+
+fn get_circle_relations(c1: Circle, c2: Circle, tolerance: f64, precision: usize) -> Vec<String> {
+    let d = get_distance(c1.get_center(), c2.get_center());
+    let (r1, r2) = (c1.get_rad(), c2.get_rad());
+    if d <= tolerance {
+        if (r1 - r2).abs() <= tolerance {
+            return vec![String::from("the circles are the same")];
+        }
+        return vec![
+            String::from("the circles are concentric"),
+            containment(r1, r2),
+        ];
+    }
+    // the touching point lies on the line through both centers
+    let touching = |along: f64| {
+        Point::new(
+            c1.get_center().get_x() + (c2.get_center().get_x() - c1.get_center().get_x()) * along,
+            c1.get_center().get_y() + (c2.get_center().get_y() - c1.get_center().get_y()) * along,
+        )
+    };
+    if (d - (r1 + r2)).abs() <= tolerance {
+        return vec![format!(
+            "the circles touch from outside at {}",
+            format_point(touching(r1 / d), precision)
+        )];
+    }
+    if (d - (r1 - r2).abs()).abs() <= tolerance {
+        let along = if r1 > r2 { r1 / d } else { -r1 / d };
+        return vec![
+            format!(
+                "the circles touch from inside at {}",
+                format_point(touching(along), precision)
+            ),
+            containment(r1, r2),
+        ];
+    }
+    if d < (r1 - r2).abs() {
+        return vec![containment(r1, r2)];
+    }
+    if d > r1 + r2 {
+        return vec![String::from("the circles lie outside of each other")];
+    }
+    vec![describe_intersections(
+        &get_circles_intersection(c1, c2),
+        precision,
+    )]
+}
+
+fn containment(r1: f64, r2: f64) -> String {
+    if r1 > r2 {
+        String::from("the second circle lies inside the first")
+    } else {
+        String::from("the first circle lies inside the second")
+    }
+}
+
+fn get_polyline_distance(p: Point, points: &[Point]) -> f64 {
+    points
+        .windows(2)
+        .map(|s| get_segment_distance(p, s[0], s[1]))
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn describe_intersections(points: &[Point], precision: usize) -> String {
+    if points.is_empty() {
+        return String::from("the figures don't intersect");
+    }
+    let points: Vec<String> = points.iter().map(|p| format_point(*p, precision)).collect();
+    format!("the figures intersect at {}", points.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: (f64, f64, f64, f64) = (-1000.0, -1000.0, 1000.0, 1000.0);
+
+    fn relations(f1: Figure, f2: Figure) -> Vec<String> {
+        get_relations(&f1, &f2, Tolerance::new(1e-6, 1e-3), 2, BOUNDS)
+    }
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Figure {
+        Figure::Line(Line::new(Point::new(x1, y1), Point::new(x2, y2)))
+    }
+
+    fn circle(x: f64, y: f64, r: f64) -> Figure {
+        Figure::Circle(Circle::new(Point::new(x, y), Point::new(x + r, y)))
+    }
+
+    #[test]
+    fn nearly_parallel_lines() {
+        // 0.0005 rad is within the angle tolerance, 0.005 rad is not
+        let within = line(0.0, 2.0, 1.0, 2.0 + 0.0005f64.tan());
+        assert_eq!(
+            relations(line(0.0, 0.0, 1.0, 0.0), within),
+            ["the lines are parallel, 2.00 apart"]
+        );
+        let outside = line(0.0, 2.0, 1.0, 2.005);
+        assert_eq!(
+            relations(line(0.0, 0.0, 1.0, 0.0), outside),
+            ["the lines intersect at (-400.00, 0.00) at an angle of 0.29°"]
+        );
+        assert_eq!(
+            relations(line(0.0, 0.0, 1.0, 1.0), line(3.0, 3.0, -2.0, -2.0)),
+            ["the lines are the same"]
+        );
+        assert_eq!(
+            relations(line(0.0, 0.0, 1.0, 1.0), line(0.0, 2.0, 1.0, 1.0)),
+            ["the lines are perpendicular, meeting at (1.00, 1.00)"]
+        );
+    }
+
+    #[test]
+    fn lines_and_circles() {
+        assert_eq!(
+            relations(line(-10.0, 7.0, 10.0, 7.0), circle(5.0, 5.0, 2.0)),
+            ["the line is tangent to the circle at (5.00, 7.00)"]
+        );
+        assert_eq!(
+            relations(circle(5.0, 5.0, 2.0), line(3.0, -10.0, 3.0, 10.0)),
+            ["the line is tangent to the circle at (3.00, 5.00)"]
+        );
+        assert_eq!(
+            relations(line(-10.0, 5.0, 10.0, 5.0), circle(5.0, 5.0, 2.0)),
+            [
+                "the line goes through the center of the circle",
+                "the figures intersect at (7.00, 5.00), (3.00, 5.00)"
+            ]
+        );
+        assert_eq!(
+            relations(line(-10.0, 8.0, 10.0, 8.0), circle(5.0, 5.0, 2.0)),
+            ["the figures don't intersect"]
+        );
+    }
+
+    #[test]
+    fn touching_circles() {
+        assert_eq!(
+            relations(circle(0.0, 0.0, 2.0), circle(5.0, 0.0, 3.0)),
+            ["the circles touch from outside at (2.00, 0.00)"]
+        );
+        assert_eq!(
+            relations(circle(0.0, 0.0, 5.0), circle(0.0, 2.0, 3.0)),
+            [
+                "the circles touch from inside at (0.00, 5.00)",
+                "the second circle lies inside the first"
+            ]
+        );
+        assert_eq!(
+            relations(circle(0.0, 2.0, 3.0), circle(0.0, 0.0, 5.0)),
+            [
+                "the circles touch from inside at (0.00, 5.00)",
+                "the first circle lies inside the second"
+            ]
+        );
+        assert_eq!(
+            relations(circle(1.0, 1.0, 2.0), circle(1.0, 1.0, 3.0)),
+            [
+                "the circles are concentric",
+                "the first circle lies inside the second"
+            ]
+        );
+        assert_eq!(
+            relations(circle(0.0, 0.0, 1.0), circle(5.0, 0.0, 1.0)),
+            ["the circles lie outside of each other"]
+        );
+    }
+
+    #[test]
+    fn collinear_and_concyclic_points() {
+        let on_line: Vec<Point> = (0..4)
+            .map(|i| Point::new(3.0 + 2.0 * i as f64, -1.0 + 0.5 * i as f64))
+            .collect();
+        assert_eq!(
+            get_points_relations(&on_line, 1e-6),
+            ["the points are collinear", "the points are not concyclic"]
+        );
+        let on_circle: Vec<Point> = [0.3, 1.2, 2.5, 4.0]
+            .iter()
+            .map(|t: &f64| Point::new(40.0 + 7.0 * t.cos(), -20.0 + 7.0 * t.sin()))
+            .collect();
+        assert_eq!(
+            get_points_relations(&on_circle, 1e-6),
+            ["the points are not collinear", "the points are concyclic"]
+        );
+        let mut off = on_circle.clone();
+        off[3] = Point::new(40.0, -20.0);
+        assert_eq!(
+            get_points_relations(&off, 1e-6),
+            [
+                "the points are not collinear",
+                "the points are not concyclic"
+            ]
+        );
+        assert_eq!(
+            get_points_relations(&on_line[..3], 1e-6),
+            ["the points are collinear"]
+        );
+    }
+}
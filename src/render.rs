@@ -0,0 +1,203 @@
+#![allow(dead_code)]
+use std::f64;
+
+use crate::figures::{point::Point, Figure};
+use crate::style::{LinePattern, Marker, Style};
+use crate::utils::get_distance;
+use crate::view::View;
+
+// how far from the screen, in pixels, curves may go before they are cut off; backends
+// work with 16 bit coordinates underneath and a huge circle wraps around
+const REACH: f64 = 4000.0;
+// how far a flattened curve may be off, in pixels
+const FLATNESS: f64 = 0.25;
+
+// what the outlines are drawn with, the opacity goes for the fills as well
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pen {
+    color: (u8, u8, u8),
+    width: f64,
+    pattern: LinePattern,
+    opacity: f64,
+}
+
+impl Pen {
+    pub fn new(color: (u8, u8, u8), width: f64, pattern: LinePattern, opacity: f64) -> Pen {
+        Pen {
+            color,
+            width,
+            pattern,
+            opacity,
+        }
+    }
+
+    pub fn get_color(&self) -> (u8, u8, u8) {
+        self.color
+    }
+
+    pub fn get_width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn get_pattern(&self) -> LinePattern {
+        self.pattern
+    }
+
+    pub fn get_opacity(&self) -> f64 {
+        self.opacity
+    }
+}
+
+// something figures can be drawn on, in screen coordinates with y going down; angles
+// count counterclockwise like in the world, fills come first and the outline over them
+pub trait Renderer {
+    fn set_pen(&mut self, pen: Pen);
+    fn draw_polyline(&mut self, points: &[(f64, f64)]);
+    fn draw_polygon(&mut self, points: &[(f64, f64)], fill: Option<(u8, u8, u8)>);
+    fn draw_circle(&mut self, center: (f64, f64), rad: f64, fill: Option<(u8, u8, u8)>);
+    // a pie when it is filled
+    fn draw_arc(
+        &mut self,
+        center: (f64, f64),
+        rad: f64,
+        start: f64,
+        sweep: f64,
+        fill: Option<(u8, u8, u8)>,
+    );
+    // a point object in the color of the pen, size is the radius
+    fn draw_marker(&mut self, p: (f64, f64), marker: Marker, size: f64);
+}
+
+// draws a figure the same way on every backend
+pub fn render_figure(renderer: &mut dyn Renderer, figure: &Figure, style: &Style, view: &View) {
+    renderer.set_pen(Pen::new(
+        style.get_color(),
+        style.get_width() as f64,
+        style.get_dash(),
+        style.get_opacity(),
+    ));
+    let fill = style.get_fill();
+    let polyline = |renderer: &mut dyn Renderer, points: &[Point]| {
+        let points: Vec<(f64, f64)> = points.iter().map(|p| view.to_screen(*p)).collect();
+        renderer.draw_polyline(&points);
+    };
+    match figure {
+        Figure::Point(p) => renderer.draw_marker(
+            view.to_screen(*p),
+            style.get_marker(),
+            style.get_size() as f64,
+        ),
+        Figure::Line(l) => {
+            if let Some((a, b)) = get_visible_line(l.get_point_a(), l.get_point_b(), view) {
+                renderer.draw_polyline(&[a, b]);
+            }
+        }
+        Figure::Circle(c) => {
+            let center = view.to_screen(c.get_center());
+            let rad = view.to_pixels(c.get_rad());
+            if rad < REACH {
+                renderer.draw_circle(center, rad, fill);
+            } else {
+                // only the part around the screen, the cut edges are out of sight
+                let outline = flatten_circle(center, rad);
+                let bounds = (
+                    -REACH / 2.0,
+                    -REACH / 2.0,
+                    view.get_width() as f64 + REACH / 2.0,
+                    view.get_height() as f64 + REACH / 2.0,
+                );
+                let clipped = clip_polygon(&outline, bounds);
+                if clipped.len() > 2 {
+                    renderer.draw_polygon(&clipped, fill);
+                }
+            }
+        }
+        Figure::Rectangle(r) => {
+            let (x1, y1) = view.to_screen(r.get_point_a());
+            let (x2, y2) = view.to_screen(r.get_point_b());
+            renderer.draw_polygon(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)], fill);
+        }
+        Figure::Arc(a) => renderer.draw_arc(
+            view.to_screen(a.get_center()),
+            view.to_pixels(a.get_rad()),
+            a.get_start_angle(),
+            a.get_sweep(),
+            fill,
+        ),
+        Figure::Conic(c) => {
+            for piece in c.sample(FLATNESS / view.get_scale(), view.get_bounds()) {
+                polyline(renderer, &piece);
+            }
+        }
+        // the graphs are sampled over what is visible now, so they always reach across
+        _ => {
+            for piece in figure
+                .get_polylines(FLATNESS / view.get_scale(), view.get_bounds())
+                .unwrap_or_default()
+            {
+                polyline(renderer, &piece);
+            }
+        }
+    }
+}
+
+// the line through a and b from one side of the screen past the other
+fn get_visible_line(a: Point, b: Point, view: &View) -> Option<((f64, f64), (f64, f64))> {
+    let len = get_distance(a, b);
+    if len == 0.0 {
+        return None;
+    }
+    let center = view.to_world(
+        view.get_width() as f64 / 2.0,
+        view.get_height() as f64 / 2.0,
+    );
+    let reach =
+        get_distance(a, center) + (view.get_width() + view.get_height()) as f64 / view.get_scale();
+    let dx = (b.get_x() - a.get_x()) / len * reach;
+    let dy = (b.get_y() - a.get_y()) / len * reach;
+    Some((
+        view.to_screen(Point::new(a.get_x() - dx, a.get_y() - dy)),
+        view.to_screen(Point::new(a.get_x() + dx, a.get_y() + dy)),
+    ))
+}
+
+// a polygon no more than FLATNESS off the circle
+fn flatten_circle((x, y): (f64, f64), rad: f64) -> Vec<(f64, f64)> {
+    let step = 2.0 * (1.0 - FLATNESS / rad).acos();
+    let n = ((2.0 * f64::consts::PI / step).ceil() as usize).clamp(8, 1 << 16);
+    (0..n)
+        .map(|i| {
+            let angle = 2.0 * f64::consts::PI * i as f64 / n as f64;
+            (x + rad * angle.cos(), y - rad * angle.sin())
+        })
+        .collect()
+}
+
+// the part of a polygon inside the box (x_min, y_min, x_max, y_max), one side at a time
+pub fn clip_polygon(polygon: &[(f64, f64)], bounds: (f64, f64, f64, f64)) -> Vec<(f64, f64)> {
+    let (x_min, y_min, x_max, y_max) = bounds;
+    // a coordinate of the point, the limit and which side of it is inside
+    let sides: [(usize, f64, bool); 4] = [
+        (0, x_min, true),
+        (0, x_max, false),
+        (1, y_min, true),
+        (1, y_max, false),
+    ];
+    let mut result = polygon.to_vec();
+    for (axis, limit, above) in sides {
+        let get = |p: (f64, f64)| if axis == 0 { p.0 } else { p.1 };
+        let inside = |p: (f64, f64)| (get(p) >= limit) == above || get(p) == limit;
+        let input = std::mem::take(&mut result);
+        for (i, &p) in input.iter().enumerate() {
+            let q = input[(i + 1) % input.len()];
+            if inside(p) {
+                result.push(p);
+            }
+            if inside(p) != inside(q) {
+                let t = (limit - get(p)) / (get(q) - get(p));
+                result.push((p.0 + (q.0 - p.0) * t, p.1 + (q.1 - p.1) * t));
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+use std::fs;
+use std::path::Path;
+
+// a construction script (.gvz), one statement of the construction language per line;
+// blank lines and comments, from a '#' at the start or after a space, are left out
+#[derive(Debug, Clone)]
+pub struct Script {
+    steps: Vec<(usize, String)>, // line number counting from 1 and the statement
+    next: usize,
+}
+
+impl Script {
+    pub fn parse(source: &str) -> Script {
+        let steps = source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line).trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| (i, String::from(line)))
+            .collect();
+        Script { steps, next: 0 }
+    }
+
+    pub fn read(path: &Path) -> Result<Script, String> {
+        fs::read_to_string(path)
+            .map(|source| Script::parse(&source))
+            .map_err(|e| format!("can't read {}: {}", path.display(), e))
+    }
+
+    // the statement to run next with its line number, None at the end
+    pub fn peek(&self) -> Option<(usize, &str)> {
+        self.steps.get(self.next).map(|(i, s)| (*i, s.as_str()))
+    }
+
+    pub fn advance(&mut self) {
+        self.next = (self.next + 1).min(self.steps.len());
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+
+    // how many statements have run and how many there are
+    pub fn get_progress(&self) -> (usize, usize) {
+        (self.next, self.steps.len())
+    }
+}
+
+// a '#' at the start of a word starts a comment, unless it is a value like the color in
+// "color = #ff8000", which comes after an '='
+fn strip_comment(line: &str) -> &str {
+    let mut previous = ' ';
+    let mut last_word = ' '; // the last character which isn't whitespace
+    for (i, c) in line.char_indices() {
+        if c == '#' && previous.is_whitespace() && last_word != '=' {
+            return &line[..i];
+        }
+        previous = c;
+        if !c.is_whitespace() {
+            last_word = c;
+        }
+    }
+    line
+}
+
+pub fn is_script(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gvz"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments() {
+        assert_eq!(strip_comment("# a comment"), "");
+        assert_eq!(strip_comment("A = (1, 2) # a point"), "A = (1, 2) ");
+        assert_eq!(
+            strip_comment("Style(c, color=#ff0000)"),
+            "Style(c, color=#ff0000)"
+        );
+        assert_eq!(
+            strip_comment("Style(c, color = #ff0000) # red"),
+            "Style(c, color = #ff0000) "
+        );
+        assert_eq!(
+            strip_comment("Style(c, fill= #00ff00)"),
+            "Style(c, fill= #00ff00)"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use fltk::enums::{Align, FrameType};
+use fltk::prelude::*;
+use fltk::{frame::Frame, group::Flex};
+
+use crate::canvas::Canvas;
+use crate::figures::point::Point;
+use crate::tooltip::format_point;
+
+const COORDS_WIDTH: i32 = 180;
+const TOOL_WIDTH: i32 = 200;
+const COUNT_WIDTH: i32 = 100;
+
+// a line under the canvas with where the mouse is, the tool in use, how many objects
+// there are and what the next click of the tool is for
+#[derive(Clone)]
+pub struct StatusBar {
+    coords: Frame, // in world units, empty while the mouse is off the canvas
+    tool: Frame,
+    count: Frame,
+    hint: Frame,
+    canvas: Canvas,
+}
+
+impl StatusBar {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let mut row = Flex::new(x, y, w, h, None).row();
+        row.set_pad(2);
+        let mut cell = |width: Option<i32>| {
+            let mut frame = Frame::default();
+            frame.set_frame(FrameType::ThinDownBox);
+            frame.set_align(Align::Left | Align::Inside);
+            if let Some(width) = width {
+                row.set_size(&frame, width);
+            }
+            frame
+        };
+        let coords = cell(Some(COORDS_WIDTH));
+        let tool = cell(Some(TOOL_WIDTH));
+        let count = cell(Some(COUNT_WIDTH));
+        let hint = cell(None);
+        row.end();
+
+        let mut bar = StatusBar {
+            coords,
+            tool,
+            count,
+            hint,
+            canvas,
+        };
+        bar.canvas.add_on_change({
+            let mut bar = bar.clone();
+            move || bar.refresh()
+        });
+        bar.canvas.add_on_move({
+            let mut bar = bar.clone();
+            move |p| bar.show_cursor(p)
+        });
+        bar.refresh();
+        bar
+    }
+
+    // the tool, the number of objects and the hint the canvas has now
+    pub fn refresh(&mut self) {
+        self.tool
+            .set_label(&format!(" {}", self.canvas.get_tool().get_name()));
+        let count = self.canvas.get_objects().len();
+        let s = if count == 1 { "" } else { "s" };
+        self.count.set_label(&format!(" {} object{}", count, s));
+        self.hint.set_label(&format!(" {}", self.canvas.get_hint()));
+    }
+
+    fn show_cursor(&mut self, p: Option<Point>) {
+        let label = p.map_or(String::new(), |p| {
+            format!(" {}", format_point(p, self.canvas.get_precision()))
+        });
+        self.coords.set_label(&label);
+    }
+}
@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinePattern {
+    Solid,
+    Dashed,
+    Dotted,
+    DashDot,
+}
+
+// how a point object is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Dot, // a filled circle
+    Circle,
+    Cross,
+    Square,
+    Diamond,
+}
+
+const DASHES: [(LinePattern, &str); 4] = [
+    (LinePattern::Solid, "solid"),
+    (LinePattern::Dashed, "dashed"),
+    (LinePattern::Dotted, "dotted"),
+    (LinePattern::DashDot, "dashdot"),
+];
+const MARKERS: [(Marker, &str); 5] = [
+    (Marker::Dot, "dot"),
+    (Marker::Circle, "circle"),
+    (Marker::Cross, "cross"),
+    (Marker::Square, "square"),
+    (Marker::Diamond, "diamond"),
+];
+
+// the look of one object, written as "color=#ff0000 width=3 dash=dotted ..." in the
+// inspector and in Style statements of the construction language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    color: (u8, u8, u8),        // of the outline
+    fill: Option<(u8, u8, u8)>, // None for an outline only
+    width: i32,                 // in pixels
+    dash: LinePattern,
+    marker: Marker,
+    size: i32, // radius of the point marker in pixels
+    opacity: f64,
+}
+
+impl Style {
+    // what new objects get from the color and fill toggles
+    pub fn new(color: (u8, u8, u8), fill: bool) -> Style {
+        Style {
+            color,
+            fill: if fill { Some(color) } else { None },
+            width: 3,
+            dash: LinePattern::Solid,
+            marker: Marker::Dot,
+            size: 3,
+            opacity: 1.0,
+        }
+    }
+
+    // the same with lines of another width
+    pub fn with_width(self, width: i32) -> Style {
+        Style { width, ..self }
+    }
+
+    pub fn get_color(&self) -> (u8, u8, u8) {
+        self.color
+    }
+
+    pub fn get_fill(&self) -> Option<(u8, u8, u8)> {
+        self.fill
+    }
+
+    pub fn get_width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn get_dash(&self) -> LinePattern {
+        self.dash
+    }
+
+    pub fn get_marker(&self) -> Marker {
+        self.marker
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn get_opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    // the settings the way parse reads them
+    pub fn to_text(self) -> String {
+        format!(
+            "color={} fill={} width={} dash={} marker={} size={} opacity={}",
+            format_color(self.color),
+            self.fill.map_or(String::from("none"), format_color),
+            self.width,
+            get_word(&DASHES, self.dash),
+            get_word(&MARKERS, self.marker),
+            self.size,
+            self.opacity
+        )
+    }
+
+    // changes the settings in "width=2, dash=dotted" or "width=2 dash=dotted", spaces around
+    // the '=' don't matter; everything not mentioned stays like it is here
+    pub fn parse(&self, settings: &str) -> Result<Style, String> {
+        let mut style = *self;
+        let settings = settings
+            .split('=')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("=");
+        for setting in settings
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+        {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' should be written like width=2", setting))?;
+            let value = value.trim();
+            match key.trim() {
+                "color" => style.color = parse_color(value)?,
+                "fill" if value == "none" => style.fill = None,
+                "fill" => style.fill = Some(parse_color(value)?),
+                "width" => style.width = parse_number(key, value, 0.0, 20.0)? as i32,
+                "dash" => style.dash = parse_word(&DASHES, key, value)?,
+                "marker" => style.marker = parse_word(&MARKERS, key, value)?,
+                "size" => style.size = parse_number(key, value, 1.0, 20.0)? as i32,
+                "opacity" => style.opacity = parse_number(key, value, 0.0, 1.0)?,
+                key => {
+                    return Err(format!(
+                        "there is no style '{}', only color, fill, width, dash, marker, size and opacity",
+                        key
+                    ))
+                }
+            }
+        }
+        Ok(style)
+    }
+}
+
+pub fn format_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// "#ff8000" or "#f80"
+pub fn parse_color(value: &str) -> Result<(u8, u8, u8), String> {
+    let error = || format!("'{}' is not a color like #ff8000", value);
+    let hex = value.strip_prefix('#').ok_or_else(error)?;
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .ok_or_else(error)?;
+    match digits[..] {
+        [r, g, b] => Ok((r * 17, g * 17, b * 17)),
+        [r1, r2, g1, g2, b1, b2] => Ok((r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+        _ => Err(error()),
+    }
+}
+
+fn parse_number(key: &str, value: &str, min: f64, max: f64) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("{} takes a number from {} to {}", key, min, max)),
+    }
+}
+
+fn parse_word<T: Copy>(words: &[(T, &str)], key: &str, value: &str) -> Result<T, String> {
+    match words.iter().find(|(_, w)| w.eq_ignore_ascii_case(value)) {
+        Some((item, _)) => Ok(*item),
+        None => {
+            let names: Vec<&str> = words.iter().map(|(_, w)| *w).collect();
+            Err(format!("{} is one of {}", key, names.join(", ")))
+        }
+    }
+}
+
+fn get_word<T: Copy + PartialEq>(words: &[(T, &'static str)], item: T) -> &'static str {
+    words
+        .iter()
+        .find(|(i, _)| *i == item)
+        .map_or("", |(_, w)| *w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_read_back() {
+        let style = Style::new((255, 0, 0), false)
+            .parse("color = #0f8, fill=#102030, width=5 dash=DashDot marker=diamond size=4 opacity=0.5")
+            .unwrap();
+        assert_eq!(style.get_color(), (0, 255, 136));
+        assert_eq!(style.get_fill(), Some((16, 32, 48)));
+        assert_eq!(style.get_width(), 5);
+        assert_eq!(style.get_dash(), LinePattern::DashDot);
+        assert_eq!(style.get_marker(), Marker::Diamond);
+        assert_eq!(style.get_size(), 4);
+        assert_eq!(style.get_opacity(), 0.5);
+        assert_eq!(
+            Style::new((0, 0, 0), true).parse(&style.to_text()),
+            Ok(style)
+        );
+    }
+
+    #[test]
+    fn unmentioned_settings_stay() {
+        let style = Style::new((1, 2, 3), true).with_width(7);
+        let thinner = style.parse("width=1").unwrap();
+        assert_eq!(thinner, style.with_width(1));
+        assert_eq!(style.parse("fill=none").unwrap().get_fill(), None);
+        assert_eq!(style.parse("").unwrap(), style);
+    }
+
+    #[test]
+    fn bad_settings() {
+        let style = Style::new((0, 0, 0), false);
+        for settings in [
+            "width",
+            "width=30",
+            "opacity=-1",
+            "color=red",
+            "color=#12345",
+            "dash=wavy",
+            "thickness=2",
+        ] {
+            assert!(style.parse(settings).is_err(), "{}", settings);
+        }
+    }
+}
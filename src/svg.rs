@@ -0,0 +1,184 @@
+#![allow(dead_code)]
+use std::f64;
+use std::fmt::Write;
+
+use crate::figures::{point::Point, Figure};
+use crate::render::{render_figure, Pen, Renderer};
+use crate::style::{format_color, LinePattern, Marker, Style};
+use crate::view::View;
+
+const AXIS_COLOR: &str = "#d9d9d9";
+
+// the figures as an SVG image the size of the view, drawn like the canvas draws them
+// but without the handles, which are only there for editing
+pub fn to_svg(figures: &[(&Figure, &Style)], view: &View) -> String {
+    let (w, h) = (view.get_width(), view.get_height());
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n"
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        w, h
+    ));
+    let (x, y) = view.to_screen(Point::new(0.0, 0.0));
+    svg.push_str(&format!(
+        "<path d=\"M0 {y:.2}H{w}M{x:.2} 0V{h}\" stroke=\"{AXIS_COLOR}\" stroke-width=\"1\"/>\n"
+    ));
+    let mut renderer = SvgRenderer {
+        svg,
+        pen: Pen::new((0, 0, 0), 1.0, LinePattern::Solid, 1.0),
+    };
+    for (figure, style) in figures {
+        render_figure(&mut renderer, figure, style, view);
+    }
+    renderer.svg.push_str("</svg>\n");
+    renderer.svg
+}
+
+// writes one element for everything drawn
+struct SvgRenderer {
+    svg: String,
+    pen: Pen,
+}
+
+impl SvgRenderer {
+    // the attributes for the pen and the fill
+    fn get_paint(&self, fill: Option<(u8, u8, u8)>) -> String {
+        let width = self.pen.get_width();
+        let mut paint = format!(
+            "fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"",
+            fill.map_or(String::from("none"), format_color),
+            format_color(self.pen.get_color()),
+            width
+        );
+        let dashes = match self.pen.get_pattern() {
+            LinePattern::Solid => None,
+            LinePattern::Dashed => Some(vec![width * 4.0, width * 2.0]),
+            LinePattern::Dotted => Some(vec![width, width * 2.0]),
+            LinePattern::DashDot => Some(vec![width * 4.0, width * 2.0, width, width * 2.0]),
+        };
+        if let Some(dashes) = dashes {
+            let dashes: Vec<String> = dashes.iter().map(|d| d.to_string()).collect();
+            write!(paint, " stroke-dasharray=\"{}\"", dashes.join(" ")).ok();
+        }
+        if self.pen.get_opacity() < 1.0 {
+            write!(paint, " opacity=\"{}\"", self.pen.get_opacity()).ok();
+        }
+        paint
+    }
+
+    fn add_path(&mut self, d: &str, fill: Option<(u8, u8, u8)>) {
+        let paint = self.get_paint(fill);
+        writeln!(self.svg, "<path d=\"{}\" {}/>", d, paint).ok();
+    }
+}
+
+// "M1.00 2.00L3.00 4.00..."
+fn get_path(points: &[(f64, f64)]) -> String {
+    let mut d = String::new();
+    for (i, (x, y)) in points.iter().enumerate() {
+        write!(d, "{}{:.2} {:.2}", if i == 0 { "M" } else { "L" }, x, y).ok();
+    }
+    d
+}
+
+impl Renderer for SvgRenderer {
+    fn set_pen(&mut self, pen: Pen) {
+        self.pen = pen;
+    }
+
+    fn draw_polyline(&mut self, points: &[(f64, f64)]) {
+        if points.len() > 1 {
+            self.add_path(&get_path(points), None);
+        }
+    }
+
+    fn draw_polygon(&mut self, points: &[(f64, f64)], fill: Option<(u8, u8, u8)>) {
+        if points.len() > 2 {
+            self.add_path(&format!("{}Z", get_path(points)), fill);
+        }
+    }
+
+    fn draw_circle(&mut self, (x, y): (f64, f64), rad: f64, fill: Option<(u8, u8, u8)>) {
+        let paint = self.get_paint(fill);
+        writeln!(
+            self.svg,
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}/>",
+            x, y, rad, paint
+        )
+        .ok();
+    }
+
+    fn draw_arc(
+        &mut self,
+        (x, y): (f64, f64),
+        rad: f64,
+        start: f64,
+        sweep: f64,
+        fill: Option<(u8, u8, u8)>,
+    ) {
+        let point_at = |angle: f64| (x + rad * angle.cos(), y - rad * angle.sin());
+        let (x1, y1) = point_at(start);
+        let (x2, y2) = point_at(start + sweep);
+        let large = (sweep.abs() > f64::consts::PI) as i32;
+        // counterclockwise on the screen as well, which is sweep flag 0 in SVG
+        let mut d = format!(
+            "M{:.2} {:.2}A{:.2} {:.2} 0 {} 0 {:.2} {:.2}",
+            x1, y1, rad, rad, large, x2, y2
+        );
+        // a filled arc is a pie, closed through the center
+        if fill.is_some() {
+            write!(d, "L{:.2} {:.2}Z", x, y).ok();
+        }
+        self.add_path(&d, fill);
+    }
+
+    fn draw_marker(&mut self, (x, y): (f64, f64), marker: Marker, r: f64) {
+        let color = format_color(self.pen.get_color());
+        let opacity = if self.pen.get_opacity() < 1.0 {
+            format!(" opacity=\"{}\"", self.pen.get_opacity())
+        } else {
+            String::new()
+        };
+        let stroke = format!(
+            "fill=\"none\" stroke=\"{}\" stroke-width=\"2\"{}",
+            color, opacity
+        );
+        let element = match marker {
+            Marker::Dot => format!(
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"{}/>",
+                x, y, r, color, opacity
+            ),
+            Marker::Circle => format!(
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" {}/>",
+                x, y, r, stroke
+            ),
+            Marker::Cross => format!(
+                "<path d=\"M{:.2} {:.2}L{:.2} {:.2}M{:.2} {:.2}L{:.2} {:.2}\" {}/>",
+                x - r,
+                y - r,
+                x + r,
+                y + r,
+                x - r,
+                y + r,
+                x + r,
+                y - r,
+                stroke
+            ),
+            Marker::Square => format!(
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{}\" height=\"{}\" {}/>",
+                x - r,
+                y - r,
+                r * 2.0,
+                r * 2.0,
+                stroke
+            ),
+            Marker::Diamond => format!(
+                "<path d=\"{}Z\" {}/>",
+                get_path(&[(x, y - r), (x + r, y), (x, y + r), (x - r, y)]),
+                stroke
+            ),
+        };
+        writeln!(self.svg, "{}", element).ok();
+    }
+}
@@ -0,0 +1,366 @@
+use std::cell::RefCell;
+use std::mem::discriminant;
+use std::rc::Rc;
+
+use fltk::dialog::{color_chooser_with_default, ColorMode};
+use fltk::enums::{Align, Color, FrameType};
+use fltk::image::SvgImage;
+use fltk::prelude::*;
+use fltk::{
+    button::{Button, RadioButton, ToggleButton},
+    frame::Frame,
+    group::Flex,
+};
+
+use crate::canvas::{Canvas, Tool};
+use crate::keymap::Keymap;
+
+const BUTTON_WIDTH: i32 = 28;
+const SPACE: i32 = 8;
+const ICON_SIZE: i32 = 22;
+const INK: &str = "#303030";
+
+// every tool with its label, the menu it is in, the key that picks it by default and its
+// icon; an icon is drawn in a 24 x 24 box, "dot" is short for a point
+pub const TOOLS: [(Tool, &str, &str, &str, &str); 28] = [
+    (
+        Tool::Line,
+        "Line",
+        "Construct",
+        "l",
+        "<path d='M3 19L21 5'/><dot cx='6.9' cy='16'/><dot cx='17.1' cy='8'/>",
+    ),
+    (
+        Tool::Rectangle,
+        "Rectangle",
+        "Construct",
+        "r",
+        "<rect x='4' y='6' width='16' height='12'/><dot cx='4' cy='18'/><dot cx='20' cy='6'/>",
+    ),
+    (
+        Tool::Circle,
+        "Circle",
+        "Construct",
+        "c",
+        "<circle cx='12' cy='12' r='8'/><dot cx='12' cy='12'/><dot cx='20' cy='12'/>",
+    ),
+    (
+        Tool::Arc,
+        "Arc",
+        "Construct",
+        "a",
+        "<path d='M20 12A8 8 0 0 0 6.3 6.3'/><dot cx='12' cy='12'/><dot cx='20' cy='12'/>",
+    ),
+    (
+        Tool::ThreePointArc,
+        "Arc through three points",
+        "Construct",
+        "Shift+a",
+        "<path d='M4 16A9 9 0 0 1 20 16'/><dot cx='4' cy='16'/><dot cx='12' cy='11.1'/><dot cx='20' cy='16'/>",
+    ),
+    (
+        Tool::QuadraticBezier,
+        "Quadratic Bézier",
+        "Construct",
+        "q",
+        "<path d='M4 19Q12 3 20 19'/><path d='M4 19L12 3L20 19' stroke-dasharray='2 2' stroke-width='1'/><dot cx='12' cy='3'/>",
+    ),
+    (
+        Tool::CubicBezier,
+        "Cubic Bézier",
+        "Construct",
+        "b",
+        "<path d='M3 18C7 2 17 22 21 6'/><dot cx='3' cy='18'/><dot cx='21' cy='6'/>",
+    ),
+    (
+        Tool::Spline,
+        "Spline",
+        "Construct",
+        "s",
+        "<path d='M3 16C6 6 9 6 12 12S18 18 21 8'/><dot cx='3' cy='16'/><dot cx='12' cy='12'/><dot cx='21' cy='8'/>",
+    ),
+    (
+        Tool::Conic,
+        "Conic through five points",
+        "Construct",
+        "o",
+        "<ellipse cx='12' cy='12' rx='9' ry='5' transform='rotate(-30 12 12)'/>",
+    ),
+    (
+        Tool::TangentAtPoint,
+        "Tangent at a point",
+        "Construct",
+        "t",
+        "<circle cx='10' cy='14' r='6'/><path d='M2 8H22'/><dot cx='10' cy='8'/>",
+    ),
+    (
+        Tool::TangentFromPoint,
+        "Tangents from a point",
+        "Construct",
+        "Shift+t",
+        "<circle cx='14' cy='14' r='5'/><path d='M3 4L21.5 11.7M3 4L12.4 21.6'/><dot cx='3' cy='4'/>",
+    ),
+    (
+        Tool::CommonTangents,
+        "Common tangents",
+        "Construct",
+        "g",
+        "<circle cx='7' cy='13' r='4'/><circle cx='17' cy='13' r='4'/><path d='M2 9H22M2 17H22'/>",
+    ),
+    (
+        Tool::Parallel,
+        "Parallel",
+        "Construct",
+        "p",
+        "<path d='M3 16L15 4M9 20L21 8'/><dot cx='15' cy='14'/>",
+    ),
+    (
+        Tool::Perpendicular,
+        "Perpendicular",
+        "Construct",
+        "Shift+p",
+        "<path d='M3 18H21M12 18V3M12 14H16V18'/><dot cx='12' cy='6'/>",
+    ),
+    (
+        Tool::PerpendicularBisector,
+        "Perpendicular bisector",
+        "Construct",
+        "u",
+        "<path d='M4 14H20M12 3V21'/><dot cx='4' cy='14'/><dot cx='20' cy='14'/>",
+    ),
+    (
+        Tool::AngleBisector,
+        "Angle bisector",
+        "Construct",
+        "Shift+u",
+        "<path d='M3 20H21M3 20L17 6'/><path d='M3 20L21 12.5' stroke-dasharray='2 2'/>",
+    ),
+    (
+        Tool::Midpoint,
+        "Midpoint",
+        "Construct",
+        "m",
+        "<path d='M4 18L20 6'/><dot cx='4' cy='18'/><dot cx='20' cy='6'/><circle cx='12' cy='12' r='3'/>",
+    ),
+    (
+        Tool::Reflect,
+        "Reflection in a line",
+        "Construct",
+        "e",
+        "<path d='M12 3V21' stroke-dasharray='2 2'/><path d='M4 7L9 12L4 17ZM20 7L15 12L20 17Z'/>",
+    ),
+    (
+        Tool::PointReflect,
+        "Reflection in a point",
+        "Construct",
+        "Shift+e",
+        "<path d='M3 5H9L6 10ZM21 19H15L18 14Z'/><dot cx='12' cy='12'/>",
+    ),
+    (
+        Tool::Rotate(0.0),
+        "Rotation…",
+        "Construct",
+        "Shift+r",
+        "<path d='M19 12A7 7 0 1 1 12 5M12 2L15 5L12 8'/><dot cx='12' cy='12'/>",
+    ),
+    (
+        Tool::Dilate(0.0),
+        "Dilation…",
+        "Construct",
+        "d",
+        "<path d='M4 20V14H10V20ZM4 20V6H18V20Z'/><dot cx='4' cy='20'/>",
+    ),
+    (
+        Tool::Translate,
+        "Translation",
+        "Construct",
+        "v",
+        "<path d='M6 17L18 7M13 7H18V12'/><dot cx='6' cy='17'/>",
+    ),
+    (
+        Tool::Invert,
+        "Inversion in a circle",
+        "Construct",
+        "i",
+        "<circle cx='9' cy='12' r='6'/><dot cx='12' cy='12'/><dot cx='21' cy='12'/>",
+    ),
+    (
+        Tool::Relation,
+        "Relation",
+        "Measure",
+        "x",
+        "<path d='M5 10H19M5 16H19'/><dot cx='12' cy='4'/>",
+    ),
+    (
+        Tool::Distance,
+        "Distance",
+        "Measure",
+        "Shift+d",
+        "<path d='M4 12H20M4 8V16M20 8V16M7 10L4 12L7 14M17 10L20 12L17 14'/>",
+    ),
+    (
+        Tool::Angle,
+        "Angle between two lines",
+        "Measure",
+        "n",
+        "<path d='M3 20H21M3 20L16 5M11 20A8 8 0 0 0 8.2 14'/>",
+    ),
+    (
+        Tool::ThreePointAngle,
+        "Angle through three points",
+        "Measure",
+        "Shift+n",
+        "<path d='M3 20H21M3 20L16 5M11 20A8 8 0 0 0 8.2 14'/><dot cx='21' cy='20'/><dot cx='3' cy='20'/><dot cx='16' cy='5'/>",
+    ),
+    (
+        Tool::Area,
+        "Area",
+        "Measure",
+        "k",
+        "<path d='M4 18L9 5L20 9L17 19Z' fill='#a0c4ff'/>",
+    ),
+];
+
+// the toolbar and the menus leave some room after these
+pub fn ends_group(tool: &Tool) -> bool {
+    matches!(
+        tool,
+        Tool::Conic | Tool::CommonTangents | Tool::Midpoint | Tool::Invert
+    )
+}
+
+fn get_icon(body: &str) -> Option<SvgImage> {
+    let body = body.replace(
+        "<dot ",
+        &format!("<circle r='2' fill='{}' stroke='none' ", INK),
+    );
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' width='24' height='24' viewBox='0 0 24 24'>\
+         <g fill='none' stroke='{}' stroke-width='1.6' stroke-linecap='round' stroke-linejoin='round'>{}</g></svg>",
+        INK, body
+    );
+    let mut icon = SvgImage::from_data(&svg).ok()?;
+    icon.scale(ICON_SIZE, ICON_SIZE, true, true);
+    Some(icon)
+}
+
+type OnTool = Box<dyn FnMut(Tool)>;
+
+// a button for each tool, the pressed one is the tool in use, and what new objects look like
+#[derive(Clone)]
+pub struct Toolbar {
+    buttons: Vec<RadioButton>, // in the order of TOOLS
+    name: Frame,               // of the tool in use
+    fill: ToggleButton,
+    color: Button,
+    canvas: Canvas,
+    on_tool: Rc<RefCell<Option<OnTool>>>,
+}
+
+impl Toolbar {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, canvas: Canvas) -> Self {
+        let mut row = Flex::new(x, y, w, h, None).row();
+        row.set_margin(3);
+        row.set_pad(1);
+
+        let mut buttons = vec![];
+        for (tool, label, _, _, icon) in TOOLS.iter() {
+            let mut button = RadioButton::default();
+            button.set_image(get_icon(icon));
+            button.set_tooltip(label.trim_end_matches('…'));
+            button.clear_visible_focus();
+            row.set_size(&button, BUTTON_WIDTH);
+            buttons.push(button);
+            if ends_group(tool) {
+                let space = Frame::default();
+                row.set_size(&space, SPACE);
+            }
+        }
+        buttons[0].set_value(true);
+
+        let mut name = Frame::default();
+        name.set_align(Align::Left | Align::Inside);
+
+        let mut fill = ToggleButton::default().with_label("Fill");
+        fill.set_tooltip("Fill new circles, rectangles and arcs");
+        fill.clear_visible_focus();
+        row.set_size(&fill, 50);
+
+        let mut color = Button::default();
+        color.set_frame(FrameType::DownBox);
+        color.set_color(Color::Black);
+        color.set_tooltip("Color of new objects");
+        color.clear_visible_focus();
+        row.set_size(&color, BUTTON_WIDTH);
+        row.end();
+
+        let mut toolbar = Toolbar {
+            buttons,
+            name,
+            fill,
+            color,
+            canvas,
+            on_tool: Rc::new(RefCell::new(None)),
+        };
+
+        for (i, button) in toolbar.buttons.clone().iter_mut().enumerate() {
+            let toolbar = toolbar.clone();
+            button.set_callback(move |_| {
+                if let Some(on_tool) = toolbar.on_tool.borrow_mut().as_mut() {
+                    on_tool(TOOLS[i].0);
+                }
+            });
+        }
+        toolbar.fill.set_callback({
+            let mut canvas = toolbar.canvas.clone();
+            move |fill| canvas.set_fill(fill.value())
+        });
+        toolbar.color.clone().set_callback({
+            let mut canvas = toolbar.canvas.clone();
+            move |_| {
+                let color = color_chooser_with_default(
+                    "Color of new objects",
+                    ColorMode::Rgb,
+                    canvas.get_color(),
+                );
+                canvas.set_color(color);
+            }
+        });
+        toolbar.canvas.add_on_change({
+            let mut toolbar = toolbar.clone();
+            move || toolbar.refresh()
+        });
+        toolbar.refresh();
+        toolbar
+    }
+
+    // what a click on a tool does, the tool still needs its angle or factor if it has one
+    pub fn set_callback(&mut self, on_tool: impl FnMut(Tool) + 'static) {
+        *self.on_tool.borrow_mut() = Some(Box::new(on_tool));
+    }
+
+    // the tooltips tell the key of each tool
+    pub fn show_keys(&mut self, keymap: &Keymap) {
+        for ((_, label, menu, ..), button) in TOOLS.iter().zip(self.buttons.iter_mut()) {
+            let key = keymap.get_key(&format!("{}/{}", menu, label));
+            let label = label.trim_end_matches('…');
+            match key {
+                "none" => button.set_tooltip(label),
+                key => button.set_tooltip(&format!("{} ({})", label, key)),
+            }
+        }
+    }
+
+    // shows the tool, fill and color the canvas has now
+    pub fn refresh(&mut self) {
+        let tool = self.canvas.get_tool();
+        for ((t, ..), button) in TOOLS.iter().zip(self.buttons.iter_mut()) {
+            button.set_value(discriminant(t) == discriminant(&tool));
+        }
+        self.name.set_label(&format!(" {}", tool.get_name()));
+        self.fill.set_value(self.canvas.get_fill());
+        let (r, g, b) = self.canvas.get_color();
+        self.color.set_color(Color::from_rgb(r, g, b));
+        self.color.redraw();
+    }
+}
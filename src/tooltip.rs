@@ -0,0 +1,166 @@
+#![allow(dead_code)]
+use crate::figures::{conic::ConicKind, line::Line, point::Point, Figure};
+use crate::utils::round_to;
+
+// writes numbers with as many decimals as it takes to read back the same value
+pub const FULL_PRECISION: usize = usize::MAX;
+
+// what the tooltip shows for a figure, one property per line
+pub fn get_tooltip(figure: &Figure, precision: usize) -> String {
+    let n = |val: f64| format_number(val, precision);
+    match figure {
+        Figure::Point(p) => format!("Point {}", format_point(*p, precision)),
+        Figure::Line(l) => get_line_tooltip(*l, precision),
+        Figure::Circle(c) => format!(
+            "Circle\ncenter: {}\nradius: {}\narea: {}",
+            format_point(c.get_center(), precision),
+            n(c.get_rad()),
+            n(c.get_area())
+        ),
+        Figure::Rectangle(r) => format!(
+            "Rectangle\nwidth: {}\nheight: {}\narea: {}",
+            n(r.get_width().abs()),
+            n(r.get_height().abs()),
+            n(r.get_area().abs())
+        ),
+        Figure::Arc(a) => format!(
+            "Arc\ncenter: {}\nradius: {}\nangle: {}°\nlength: {}",
+            format_point(a.get_center(), precision),
+            n(a.get_rad()),
+            n(a.get_sweep().to_degrees()),
+            n(a.get_length())
+        ),
+        Figure::Bezier(b) => format!("Bezier curve of degree {}", b.get_controls().len() - 1),
+        Figure::Spline(s) => format!("Spline through {} points", s.get_points().len()),
+        Figure::Function(g) => format!("f(x) = {}", g.get_source()),
+        Figure::Parametric(c) => {
+            let (t0, t1) = c.get_range();
+            let name = if c.is_polar() { "Polar curve" } else { "Curve" };
+            format!("{} {}\nfrom {} to {}", name, c.get_source(), n(t0), n(t1))
+        }
+        Figure::Conic(c) => {
+            let kind = match c.get_kind() {
+                ConicKind::Ellipse => "Ellipse",
+                ConicKind::Parabola => "Parabola",
+                ConicKind::Hyperbola => "Hyperbola",
+                ConicKind::Degenerate => "Pair of lines",
+            };
+            let terms = ["x²", "x·y", "y²", "x", "y", ""];
+            // scaled so the largest coefficient is 1, which is easier to read than unit length
+            let coefficients = c.get_coefficients();
+            let largest = coefficients
+                .iter()
+                .fold(0.0, |m: f64, c| if c.abs() > m.abs() { *c } else { m });
+            format!(
+                "{}\n{} = 0",
+                kind,
+                format_sum(&coefficients.map(|c| c / largest), &terms, precision)
+            )
+        }
+    }
+}
+
+pub fn format_point(p: Point, precision: usize) -> String {
+    format!(
+        "({}, {})",
+        format_number(p.get_x(), precision),
+        format_number(p.get_y(), precision)
+    )
+}
+
+// rounded to the given number of decimals, without a "-0"
+pub fn format_number(val: f64, precision: usize) -> String {
+    if precision == FULL_PRECISION {
+        return format!("{}", val + 0.0);
+    }
+    format!("{:.*}", precision, round_to(val, precision) + 0.0)
+}
+
+fn get_line_tooltip(l: Line, precision: usize) -> String {
+    let slope = l.get_slope();
+    if slope.is_infinite() {
+        return format!(
+            "Line\nx = {}\nslope: undefined",
+            format_number(l.get_point_a().get_x(), precision)
+        );
+    }
+    let intercept = l.get_y_intercept();
+    format!(
+        "Line\ny = {}\nslope: {}\ny-intercept: {}",
+        format_sum(&[slope, intercept], &["x", ""], precision),
+        format_number(slope, precision),
+        format_number(intercept, precision)
+    )
+}
+
+// "2x² - 3xy + 1" from the coefficients and their terms, leaving out the ones that round to 0
+pub fn format_sum(coefficients: &[f64], terms: &[&str], precision: usize) -> String {
+    let mut sum = String::new();
+    for (c, term) in coefficients.iter().zip(terms) {
+        let c = round_to(*c, precision);
+        if c == 0.0 {
+            continue;
+        }
+        if sum.is_empty() {
+            if c < 0.0 {
+                sum.push('-');
+            }
+        } else if c < 0.0 {
+            sum.push_str(" - ");
+        } else {
+            sum.push_str(" + ");
+        }
+        // a 1 in front of a term goes without saying
+        if c.abs() != 1.0 || term.is_empty() {
+            sum.push_str(&format_number(c.abs(), precision));
+        }
+        sum.push_str(term);
+    }
+    if sum.is_empty() {
+        sum.push('0');
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(format_number(1.23456, 2), "1.23");
+        assert_eq!(format_number(2.5, 0), "3");
+        assert_eq!(format_number(-0.0004, 3), "0.000");
+        assert_eq!(
+            format_number(0.1 + 0.2, FULL_PRECISION),
+            "0.30000000000000004"
+        );
+        assert_eq!(format_number(-0.0, FULL_PRECISION), "0");
+        assert_eq!(format_point(Point::new(0.5, -1.0), 1), "(0.5, -1.0)");
+    }
+
+    #[test]
+    fn sums() {
+        let terms = ["x²", "x·y", "y²", ""];
+        assert_eq!(
+            format_sum(&[2.0, -3.0, 0.0, 1.0], &terms, 1),
+            "2.0x² - 3.0x·y + 1.0"
+        );
+        assert_eq!(
+            format_sum(&[-1.0, 1.0, 0.004, -1.0], &terms, 2),
+            "-x² + x·y - 1.00"
+        );
+        assert_eq!(format_sum(&[0.0001, 0.0, 0.0, 0.0], &terms, 2), "0");
+    }
+
+    #[test]
+    fn lines() {
+        let line = Figure::Line(Line::new(Point::new(0.0, 1.0), Point::new(2.0, 2.0)));
+        assert_eq!(
+            get_tooltip(&line, 2),
+            "Line\ny = 0.50x + 1.00\nslope: 0.50\ny-intercept: 1.00"
+        );
+        let vertical = Figure::Line(Line::new(Point::new(3.0, 0.0), Point::new(3.0, 1.0)));
+        assert_eq!(get_tooltip(&vertical, 1), "Line\nx = 3.0\nslope: undefined");
+    }
+}
@@ -0,0 +1,256 @@
+#![allow(dead_code)]
+use std::f64;
+
+use crate::figures::{
+    arc::Arc, bezier::Bezier, circle::Circle, conic::Conic, line::Line, point::Point,
+    rectangle::Rectangle, spline::Spline, Figure,
+};
+use crate::utils::{
+    get_angle, get_distance, get_midpoint, get_perpendicular_line, get_point_of_intersection,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Transformation {
+    Reflection(Line),
+    PointReflection(Point),
+    Rotation(Point, f64), // center and angle, counterclockwise
+    Dilation(Point, f64), // center and factor
+    Translation(f64, f64),
+    Inversion(Circle),
+}
+
+impl Transformation {
+    // None in the center of an inversion
+    pub fn apply_to_point(&self, p: Point) -> Option<Point> {
+        let (x, y) = (p.get_x(), p.get_y());
+        Some(match *self {
+            Transformation::Reflection(l) => {
+                let foot = get_point_of_intersection(l, get_perpendicular_line(l, p));
+                Point::new(2.0 * foot.get_x() - x, 2.0 * foot.get_y() - y)
+            }
+            Transformation::PointReflection(c) => {
+                Point::new(2.0 * c.get_x() - x, 2.0 * c.get_y() - y)
+            }
+            Transformation::Rotation(c, angle) => {
+                let (sin, cos) = angle.sin_cos();
+                let (dx, dy) = (x - c.get_x(), y - c.get_y());
+                Point::new(
+                    c.get_x() + dx * cos - dy * sin,
+                    c.get_y() + dx * sin + dy * cos,
+                )
+            }
+            Transformation::Dilation(c, factor) => Point::new(
+                c.get_x() + factor * (x - c.get_x()),
+                c.get_y() + factor * (y - c.get_y()),
+            ),
+            Transformation::Translation(dx, dy) => Point::new(x + dx, y + dy),
+            Transformation::Inversion(circle) => {
+                let c = circle.get_center();
+                let d2 = (x - c.get_x()).powi(2) + (y - c.get_y()).powi(2);
+                if d2 == 0.0 {
+                    return None;
+                }
+                let k = circle.get_rad().powi(2) / d2;
+                Point::new(
+                    c.get_x() + k * (x - c.get_x()),
+                    c.get_y() + k * (y - c.get_y()),
+                )
+            }
+        })
+    }
+
+    // why a figure never has an image, whatever the transformation is made of
+    pub fn get_refusal(&self, figure: &Figure) -> Option<&'static str> {
+        match (self, figure) {
+            (_, Figure::Function(_) | Figure::Parametric(_)) => {
+                Some("functions and curves can't be transformed")
+            }
+            // only the others keep the sides parallel to the axes
+            (
+                Transformation::Reflection(_) | Transformation::Rotation(..),
+                Figure::Rectangle(_),
+            ) => Some("rectangles can only be translated, dilated or reflected in a point"),
+            (
+                Transformation::Inversion(_),
+                Figure::Point(_) | Figure::Line(_) | Figure::Circle(_) | Figure::Arc(_),
+            ) => None,
+            (Transformation::Inversion(_), _) => {
+                Some("only points, lines, circles and arcs can be inverted")
+            }
+            _ => None,
+        }
+    }
+
+    // the image of a figure, None if it can't be shown as a figure of its own
+    pub fn apply(&self, figure: &Figure) -> Option<Figure> {
+        if self.get_refusal(figure).is_some() {
+            return None;
+        }
+        if let Transformation::Inversion(c) = self {
+            return invert(figure, *c);
+        }
+        let map = |p: Point| self.apply_to_point(p);
+        let map_all =
+            |points: &[Point]| -> Option<Vec<Point>> { points.iter().map(|p| map(*p)).collect() };
+        Some(match figure {
+            Figure::Point(p) => Figure::Point(map(*p)?),
+            Figure::Line(l) => {
+                Figure::Line(Line::new(map(l.get_point_a())?, map(l.get_point_b())?))
+            }
+            Figure::Circle(c) => {
+                Figure::Circle(Circle::new(map(c.get_center())?, map(c.get_sidepoint())?))
+            }
+            Figure::Rectangle(r) => {
+                Figure::Rectangle(Rectangle::new(map(r.get_point_a())?, map(r.get_point_b())?))
+            }
+            // the sweep stays, so a full circle does too; a reflection in a line turns the
+            // arc around, so it starts at the image of the end
+            Figure::Arc(a) => {
+                let center = map(a.get_center())?;
+                let start = match self {
+                    Transformation::Reflection(_) => map(a.get_endpoint())?,
+                    _ => map(a.get_startpoint())?,
+                };
+                Figure::Arc(Arc::from_angles(
+                    center,
+                    get_distance(center, start),
+                    get_angle(center, start),
+                    a.get_sweep(),
+                ))
+            }
+            Figure::Bezier(b) => Figure::Bezier(Bezier::new(map_all(b.get_controls())?)),
+            Figure::Spline(s) => Figure::Spline(Spline::new(map_all(s.get_points())?)),
+            Figure::Conic(c) => {
+                let points = map_all(c.get_points())?;
+                Figure::Conic(Conic::from_five_points(points.try_into().ok()?)?)
+            }
+            Figure::Function(_) | Figure::Parametric(_) => return None,
+        })
+    }
+}
+
+// lines and circles through the center of the inversion turn into each other
+fn invert(figure: &Figure, inversion: Circle) -> Option<Figure> {
+    let t = Transformation::Inversion(inversion);
+    let o = inversion.get_center();
+    let eps = 1e-9 * inversion.get_rad();
+    match figure {
+        Figure::Point(p) => Some(Figure::Point(t.apply_to_point(*p)?)),
+        Figure::Line(l) => {
+            let foot = get_point_of_intersection(*l, get_perpendicular_line(*l, o));
+            if get_distance(foot, o) <= eps {
+                return Some(Figure::Line(*l));
+            }
+            // the image of the closest point is the far end of a diameter through o
+            let far = t.apply_to_point(foot)?;
+            Some(Figure::Circle(Circle::new(get_midpoint(o, far), o)))
+        }
+        Figure::Circle(c) => {
+            let d = get_distance(o, c.get_center());
+            let (ux, uy) = if d == 0.0 {
+                (1.0, 0.0)
+            } else {
+                (
+                    (c.get_center().get_x() - o.get_x()) / d,
+                    (c.get_center().get_y() - o.get_y()) / d,
+                )
+            };
+            // the points of the circle closest to and farthest from o
+            let far = Point::new(
+                c.get_center().get_x() + c.get_rad() * ux,
+                c.get_center().get_y() + c.get_rad() * uy,
+            );
+            let near = Point::new(
+                c.get_center().get_x() - c.get_rad() * ux,
+                c.get_center().get_y() - c.get_rad() * uy,
+            );
+            let far = t.apply_to_point(far)?;
+            if (d - c.get_rad()).abs() <= eps {
+                let through = Line::new(o, far);
+                return Some(Figure::Line(get_perpendicular_line(through, far)));
+            }
+            let near = t.apply_to_point(near)?;
+            Some(Figure::Circle(Circle::new(get_midpoint(near, far), far)))
+        }
+        Figure::Arc(a) => {
+            if (get_distance(o, a.get_center()) - a.get_rad()).abs() <= eps {
+                return None;
+            }
+            // three points of a full circle are two, so it goes as a circle
+            if a.get_sweep() >= 2.0 * f64::consts::PI {
+                let c = match invert(&Figure::Circle(a.to_circle()), inversion)? {
+                    Figure::Circle(c) => c,
+                    _ => return None,
+                };
+                let start = t.apply_to_point(a.get_startpoint())?;
+                return Some(Figure::Arc(Arc::from_angles(
+                    c.get_center(),
+                    c.get_rad(),
+                    get_angle(c.get_center(), start),
+                    a.get_sweep(),
+                )));
+            }
+            let mid = Arc::from_angles(
+                a.get_center(),
+                a.get_rad(),
+                a.get_start_angle(),
+                a.get_sweep() / 2.0,
+            )
+            .get_endpoint();
+            Some(Figure::Arc(Arc::from_three_points(
+                t.apply_to_point(a.get_startpoint())?,
+                t.apply_to_point(mid)?,
+                t.apply_to_point(a.get_endpoint())?,
+            )?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arc(figure: Option<Figure>) -> Arc {
+        match figure {
+            Some(Figure::Arc(a)) => a,
+            other => panic!("expected an arc, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn full_circle_arcs() {
+        let full = Arc::from_angles(Point::new(1.0, 0.0), 2.0, 0.0, 2.0 * f64::consts::PI);
+        let rotated =
+            arc(Transformation::Rotation(Point::new(0.0, 0.0), 1.0).apply(&Figure::Arc(full)));
+        assert!((rotated.get_sweep() - 2.0 * f64::consts::PI).abs() < 1e-12);
+        assert!((rotated.get_rad() - 2.0).abs() < 1e-12);
+        let inverted = arc(Transformation::Inversion(Circle::new(
+            Point::new(-4.0, 0.0),
+            Point::new(-4.0, 2.0),
+        ))
+        .apply(&Figure::Arc(full)));
+        assert!((inverted.get_sweep() - 2.0 * f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reflected_arcs_turn_around() {
+        // the upper half of the unit circle reflected in the x axis is the lower half
+        let upper = Arc::from_angles(Point::new(0.0, 0.0), 1.0, 0.0, f64::consts::PI);
+        let x_axis = Line::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+        let lower = arc(Transformation::Reflection(x_axis).apply(&Figure::Arc(upper)));
+        assert!((lower.get_start_angle() - f64::consts::PI).abs() < 1e-12);
+        assert!((lower.get_sweep() - f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rectangles_keep_their_sides() {
+        let r = Figure::Rectangle(Rectangle::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0)));
+        let rotation = Transformation::Rotation(Point::new(0.0, 0.0), 1.0);
+        assert!(rotation.apply(&r).is_none());
+        assert!(rotation.get_refusal(&r).is_some());
+        let dilation = Transformation::Dilation(Point::new(0.0, 0.0), 2.0);
+        assert!(dilation.get_refusal(&r).is_none());
+        assert!(matches!(dilation.apply(&r), Some(Figure::Rectangle(_))));
+    }
+}
@@ -1,11 +1,38 @@
-use crate::figures::{circle::Circle, line::Line, point::Point};
+use std::f64;
+
+use crate::figures::{
+    arc::Arc,
+    bezier::Bezier,
+    circle::Circle,
+    conic::{self, Conic, ConicKind},
+    function::FunctionGraph,
+    line::Line,
+    parametric::Parametric,
+    point::Point,
+    Figure,
+};
+
+// how far a curve piece may bend away from its chord before it is split further
+const CURVE_TOLERANCE: f64 = 1e-3;
+const MAX_CURVE_DEPTH: u32 = 40;
+// lines closer to parallel than this have no point of intersection worth showing
+const PARALLEL_TOLERANCE: f64 = 1e-12;
 
 pub fn get_slope(x1: f64, x2: f64, y1: f64, y2: f64) -> f64 {
     (y2 - y1) / (x2 - x1)
 }
 
 pub fn round2(val: f64) -> f64 {
-    (val * 100.0).round() as f64 / 100.0
+    round_to(val, 2)
+}
+
+pub fn round_to(val: f64, digits: usize) -> f64 {
+    // an f64 doesn't have more decimals than that to round
+    if digits > 17 {
+        return val;
+    }
+    let factor = 10f64.powi(digits as i32);
+    (val * factor).round() / factor
 }
 
 pub fn get_y_intercept(x1: f64, x2: f64, y1: f64, y2: f64) -> f64 {
@@ -14,12 +41,77 @@ pub fn get_y_intercept(x1: f64, x2: f64, y1: f64, y2: f64) -> f64 {
     y1 - (get_slope(x1, x2, y1, y2) * x1)
 }
 
-pub fn is_parallel(slope1: f64, slope2: f64) -> bool {
-    slope1 == slope2
+// the angle between two lines in [0, pi/2], vertical lines have an infinite slope
+pub fn get_angle_between_slopes(slope1: f64, slope2: f64) -> f64 {
+    let angle = (slope1.atan() - slope2.atan()).abs();
+    angle.min(f64::consts::PI - angle)
+}
+
+// tolerance is the angle in radians the lines may be off by
+pub fn is_parallel(slope1: f64, slope2: f64, tolerance: f64) -> bool {
+    get_angle_between_slopes(slope1, slope2) <= tolerance
 }
 
-pub fn is_orthogonal(slope1: f64, slope2: f64) -> bool {
-    slope1 * slope2 == -1.0
+pub fn is_orthogonal(slope1: f64, slope2: f64, tolerance: f64) -> bool {
+    (get_angle_between_slopes(slope1, slope2) - f64::consts::FRAC_PI_2).abs() <= tolerance
+}
+
+// all points are within tolerance of the line through the two farthest apart
+pub fn are_collinear(points: &[Point], tolerance: f64) -> bool {
+    let mut farthest = (0, 0, 0.0);
+    for (i, a) in points.iter().enumerate() {
+        for (j, b) in points.iter().enumerate().skip(i + 1) {
+            let d = get_distance(*a, *b);
+            if d > farthest.2 {
+                farthest = (i, j, d);
+            }
+        }
+    }
+    if farthest.2 <= tolerance {
+        return true;
+    }
+    let line = Line::new(points[farthest.0], points[farthest.1]);
+    points
+        .iter()
+        .all(|p| get_line_distance(*p, line) <= tolerance)
+}
+
+// all points are within tolerance of one circle, collinear points are not
+pub fn are_concyclic(points: &[Point], tolerance: f64) -> bool {
+    if points.len() < 3 || are_collinear(points, tolerance) {
+        return false;
+    }
+    // the circle through the three points which are farthest from being collinear
+    let mut best: Option<Point> = None;
+    let mut best_area = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        for (j, b) in points.iter().enumerate().skip(i + 1) {
+            for c in points.iter().skip(j + 1) {
+                let area = cross(
+                    b.get_x() - a.get_x(),
+                    b.get_y() - a.get_y(),
+                    c.get_x() - a.get_x(),
+                    c.get_y() - a.get_y(),
+                )
+                .abs();
+                if area > best_area {
+                    if let Some(center) = get_circumcenter(*a, *b, *c) {
+                        best = Some(center);
+                        best_area = area;
+                    }
+                }
+            }
+        }
+    }
+    match best {
+        Some(center) => {
+            let rad = get_distance(center, points[0]);
+            points
+                .iter()
+                .all(|p| (get_distance(center, *p) - rad).abs() <= tolerance)
+        }
+        None => false,
+    }
 }
 
 pub fn get_point_of_intersection(l1: Line, l2: Line) -> Point {
@@ -43,6 +135,29 @@ pub fn get_distance(p1: Point, p2: Point) -> f64 {
     ((p1.get_x() - p2.get_x()).powi(2) + (p1.get_y() - p2.get_y()).powi(2)).sqrt()
 }
 
+// distance to the line through both points of l
+pub fn get_line_distance(p: Point, l: Line) -> f64 {
+    let (a, b) = (l.get_point_a(), l.get_point_b());
+    cross(
+        b.get_x() - a.get_x(),
+        b.get_y() - a.get_y(),
+        p.get_x() - a.get_x(),
+        p.get_y() - a.get_y(),
+    )
+    .abs()
+        / get_distance(a, b)
+}
+
+pub fn get_segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.get_x() - a.get_x(), b.get_y() - a.get_y());
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return get_distance(p, a);
+    }
+    let t = (((p.get_x() - a.get_x()) * dx + (p.get_y() - a.get_y()) * dy) / len2).clamp(0.0, 1.0);
+    get_distance(p, Point::new(a.get_x() + t * dx, a.get_y() + t * dy))
+}
+
 // mathworld.wolfram.com/Circle-LineIntersection.html
 pub fn get_line_circle_intersection(line: Line, c: Circle) -> Vec<Point> {
     let mut l = line.clone();
@@ -74,13 +189,136 @@ pub fn get_line_circle_intersection(line: Line, c: Circle) -> Vec<Point> {
     }
 }
 
+// the tangent touching the circle where the ray from the center through p meets it
+pub fn get_tangent_at_point(c: Circle, p: Point) -> Option<Line> {
+    let d = get_distance(c.get_center(), p);
+    if d == 0.0 {
+        return None;
+    }
+    let dx = (p.get_x() - c.get_center().get_x()) / d;
+    let dy = (p.get_y() - c.get_center().get_y()) / d;
+    let touch = Point::new(
+        c.get_center().get_x() + c.get_rad() * dx,
+        c.get_center().get_y() + c.get_rad() * dy,
+    );
+    Some(Line::new(
+        touch,
+        Point::new(touch.get_x() - dy, touch.get_y() + dx),
+    ))
+}
+
+// none from inside the circle, one from a point on it and two from outside
+pub fn get_tangents_from_point(c: Circle, p: Point) -> Vec<Line> {
+    let d = get_distance(c.get_center(), p);
+    if (d - c.get_rad()).abs() <= 1e-9 * c.get_rad().max(1.0) {
+        return get_tangent_at_point(c, p).into_iter().collect();
+    }
+    if d < c.get_rad() {
+        return vec![];
+    }
+    // the touching points are seen from the center at acos(r / d) to either side of p
+    let angle = get_angle(c.get_center(), p);
+    let offset = (c.get_rad() / d).acos();
+    [angle + offset, angle - offset]
+        .iter()
+        .map(|a| {
+            let touch = Point::new(
+                c.get_center().get_x() + c.get_rad() * a.cos(),
+                c.get_center().get_y() + c.get_rad() * a.sin(),
+            );
+            Line::new(p, touch)
+        })
+        .collect()
+}
+
+// cp-algorithms.com/geometry/tangents-to-two-circles.html
+// the outer tangents come first, then the inner ones which only exist if the circles don't overlap
+pub fn get_common_tangents(c1: Circle, c2: Circle) -> Vec<Line> {
+    let dx = c2.get_center().get_x() - c1.get_center().get_x();
+    let dy = c2.get_center().get_y() - c1.get_center().get_y();
+    let z = dx.powi(2) + dy.powi(2);
+    if z == 0.0 {
+        return vec![];
+    }
+    let mut found: Vec<[f64; 3]> = Vec::new();
+    for (r1, r2) in [(1.0, 1.0), (-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0)] {
+        let (r1, r2) = (r1 * c1.get_rad(), r2 * c2.get_rad());
+        let r = r2 - r1;
+        let d = z - r.powi(2);
+        if d < -1e-9 * z {
+            continue;
+        }
+        let d = d.abs().sqrt();
+        // a x + b y + c = 0 with a² + b² = 1, moved back from c1 in the origin
+        let a = (dx * r + dy * d) / z;
+        let b = (dy * r - dx * d) / z;
+        let c = r1 - a * c1.get_center().get_x() - b * c1.get_center().get_y();
+        // touching circles share a tangent between the outer and the inner ones
+        if !found.iter().any(|l| {
+            (l[0] - a).abs() + (l[1] - b).abs() + (l[2] - c).abs() < 1e-9
+                || (l[0] + a).abs() + (l[1] + b).abs() + (l[2] + c).abs() < 1e-9
+        }) {
+            found.push([a, b, c]);
+        }
+    }
+    found.into_iter().filter_map(conic::to_line).collect()
+}
+
+pub fn get_midpoint(a: Point, b: Point) -> Point {
+    Point::new((a.get_x() + b.get_x()) / 2.0, (a.get_y() + b.get_y()) / 2.0)
+}
+
+pub fn get_parallel_line(l: Line, p: Point) -> Line {
+    let dx = l.get_point_b().get_x() - l.get_point_a().get_x();
+    let dy = l.get_point_b().get_y() - l.get_point_a().get_y();
+    Line::new(p, Point::new(p.get_x() + dx, p.get_y() + dy))
+}
+
+pub fn get_perpendicular_line(l: Line, p: Point) -> Line {
+    let dx = l.get_point_b().get_x() - l.get_point_a().get_x();
+    let dy = l.get_point_b().get_y() - l.get_point_a().get_y();
+    Line::new(p, Point::new(p.get_x() - dy, p.get_y() + dx))
+}
+
+pub fn get_perpendicular_bisector(a: Point, b: Point) -> Option<Line> {
+    if get_distance(a, b) == 0.0 {
+        return None;
+    }
+    Some(get_perpendicular_line(Line::new(a, b), get_midpoint(a, b)))
+}
+
+// both bisectors of crossing lines, the one between the directions of the lines first;
+// parallel lines only have the one in the middle
+pub fn get_angle_bisectors(l1: Line, l2: Line) -> Vec<Line> {
+    let direction = |l: Line| {
+        let len = get_distance(l.get_point_a(), l.get_point_b());
+        (
+            (l.get_point_b().get_x() - l.get_point_a().get_x()) / len,
+            (l.get_point_b().get_y() - l.get_point_a().get_y()) / len,
+        )
+    };
+    let (u1, u2) = (direction(l1), direction(l2));
+    if is_parallel(l1.get_slope(), l2.get_slope(), PARALLEL_TOLERANCE) {
+        let a = l1.get_point_a();
+        // the foot of the perpendicular from a on l2
+        let b = get_point_of_intersection(l2, get_perpendicular_line(l2, a));
+        let m = get_midpoint(a, b);
+        return vec![Line::new(m, Point::new(m.get_x() + u1.0, m.get_y() + u1.1))];
+    }
+    let vertex = get_point_of_intersection(l1, l2);
+    [(u1.0 + u2.0, u1.1 + u2.1), (u1.0 - u2.0, u1.1 - u2.1)]
+        .iter()
+        .map(|(dx, dy)| Line::new(vertex, Point::new(vertex.get_x() + dx, vertex.get_y() + dy)))
+        .collect()
+}
+
+// the sign for the line and circle formula, where 0 counts as positive so horizontal lines
+// still get two points
 fn sgn(x: f64) -> f64 {
     if x < 0.0 {
         return -1.0;
-    } else if x > 0.0 {
-        return 1.0;
     } else {
-        return 0.0;
+        return 1.0;
     }
 }
 
@@ -122,3 +360,862 @@ pub fn get_circles_intersection(c1: Circle, c2: Circle) -> Vec<Point> {
 pub fn double_comparison(a: f64, b: f64) -> bool {
     (a - b).abs() < 0.0000000000000001
 }
+
+// angle of the direction from center to p, like atan2
+pub fn get_angle(center: Point, p: Point) -> f64 {
+    (p.get_y() - center.get_y()).atan2(p.get_x() - center.get_x())
+}
+
+// maps an angle into [0, 2pi)
+pub fn normalize_angle(angle: f64) -> f64 {
+    let a = angle.rem_euclid(2.0 * f64::consts::PI);
+    if a >= 2.0 * f64::consts::PI {
+        0.0
+    } else {
+        a
+    }
+}
+
+// center of the circle through three points, None if they are collinear
+pub fn get_circumcenter(a: Point, b: Point, c: Point) -> Option<Point> {
+    let d = 2.0
+        * (a.get_x() * (b.get_y() - c.get_y())
+            + b.get_x() * (c.get_y() - a.get_y())
+            + c.get_x() * (a.get_y() - b.get_y()));
+    if d.abs() < 1e-9 {
+        return None;
+    }
+    let a2 = a.get_x().powi(2) + a.get_y().powi(2);
+    let b2 = b.get_x().powi(2) + b.get_y().powi(2);
+    let c2 = c.get_x().powi(2) + c.get_y().powi(2);
+    let x = (a2 * (b.get_y() - c.get_y())
+        + b2 * (c.get_y() - a.get_y())
+        + c2 * (a.get_y() - b.get_y()))
+        / d;
+    let y = (a2 * (c.get_x() - b.get_x())
+        + b2 * (a.get_x() - c.get_x())
+        + c2 * (b.get_x() - a.get_x()))
+        / d;
+    Some(Point::new(x, y))
+}
+
+// the arc intersections are the ones of its full circle, filtered to the arc's angular range
+pub fn get_line_arc_intersection(line: Line, a: Arc) -> Vec<Point> {
+    get_line_circle_intersection(line, a.to_circle())
+        .into_iter()
+        .filter(|p| a.contains_point(*p))
+        .collect()
+}
+
+pub fn get_circle_arc_intersection(c: Circle, a: Arc) -> Vec<Point> {
+    get_circles_intersection(c, a.to_circle())
+        .into_iter()
+        .filter(|p| a.contains_point(*p))
+        .collect()
+}
+
+pub fn get_arcs_intersection(a1: Arc, a2: Arc) -> Vec<Point> {
+    get_circles_intersection(a1.to_circle(), a2.to_circle())
+        .into_iter()
+        .filter(|p| a1.contains_point(*p) && a2.contains_point(*p))
+        .collect()
+}
+
+// all intersection points of two figures, rectangles don't take part yet; the curves without
+// an end are only searched within bounds (min x, min y, max x, max y), normally the view
+pub fn get_intersections(f1: &Figure, f2: &Figure, bounds: (f64, f64, f64, f64)) -> Vec<Point> {
+    let x_range = (bounds.0, bounds.2);
+    match (f1, f2) {
+        (Figure::Point(_), _) | (_, Figure::Point(_)) => vec![],
+        (Figure::Line(l1), Figure::Line(l2)) => {
+            if is_parallel(l1.get_slope(), l2.get_slope(), PARALLEL_TOLERANCE) {
+                vec![]
+            } else {
+                vec![get_point_of_intersection(*l1, *l2)]
+            }
+        }
+        (Figure::Line(l), Figure::Circle(c)) | (Figure::Circle(c), Figure::Line(l)) => {
+            get_line_circle_intersection(*l, *c)
+        }
+        (Figure::Circle(c1), Figure::Circle(c2)) => get_circles_intersection(*c1, *c2),
+        (Figure::Line(l), Figure::Arc(a)) | (Figure::Arc(a), Figure::Line(l)) => {
+            get_line_arc_intersection(*l, *a)
+        }
+        (Figure::Circle(c), Figure::Arc(a)) | (Figure::Arc(a), Figure::Circle(c)) => {
+            get_circle_arc_intersection(*c, *a)
+        }
+        (Figure::Arc(a1), Figure::Arc(a2)) => get_arcs_intersection(*a1, *a2),
+        (Figure::Function(g), Figure::Line(l)) | (Figure::Line(l), Figure::Function(g)) => {
+            get_function_line_intersection(g, *l, x_range)
+        }
+        (Figure::Function(g), Figure::Circle(c)) | (Figure::Circle(c), Figure::Function(g)) => {
+            get_function_circle_intersection(g, *c, x_range)
+        }
+        (Figure::Function(g), Figure::Arc(a)) | (Figure::Arc(a), Figure::Function(g)) => {
+            get_function_arc_intersection(g, *a, x_range)
+        }
+        (Figure::Function(g1), Figure::Function(g2)) => get_functions_intersection(g1, g2, x_range),
+        (Figure::Parametric(c), Figure::Line(l)) | (Figure::Line(l), Figure::Parametric(c)) => {
+            get_parametric_line_intersection(c, *l)
+        }
+        (Figure::Parametric(c), Figure::Circle(ci))
+        | (Figure::Circle(ci), Figure::Parametric(c)) => get_parametric_circle_intersection(c, *ci),
+        (Figure::Parametric(c), Figure::Arc(a)) | (Figure::Arc(a), Figure::Parametric(c)) => {
+            get_parametric_arc_intersection(c, *a)
+        }
+        (Figure::Parametric(c), Figure::Function(g))
+        | (Figure::Function(g), Figure::Parametric(c)) => {
+            get_parametric_function_intersection(c, g, x_range)
+        }
+        (Figure::Conic(c), Figure::Line(l)) | (Figure::Line(l), Figure::Conic(c)) => {
+            get_line_conic_intersection(*l, c)
+        }
+        (Figure::Conic(c), Figure::Circle(ci)) | (Figure::Circle(ci), Figure::Conic(c)) => {
+            get_conics_intersection(c, &Conic::from_circle(*ci))
+        }
+        (Figure::Conic(c), Figure::Arc(a)) | (Figure::Arc(a), Figure::Conic(c)) => {
+            get_conics_intersection(c, &Conic::from_circle(a.to_circle()))
+                .into_iter()
+                .filter(|p| a.contains_point(*p))
+                .collect()
+        }
+        (Figure::Conic(c1), Figure::Conic(c2)) => get_conics_intersection(c1, c2),
+        (Figure::Rectangle(_), _) | (_, Figure::Rectangle(_)) => vec![],
+        (Figure::Conic(_), _) | (_, Figure::Conic(_)) => get_polyline_intersections(f1, f2, bounds),
+        (Figure::Parametric(_), _) | (_, Figure::Parametric(_)) => {
+            get_polyline_intersections(f1, f2, bounds)
+        }
+        _ => get_curve_intersections(f1, f2, bounds),
+    }
+}
+
+// at least one of the figures is a curve, so intersect piece by piece
+fn get_curve_intersections(f1: &Figure, f2: &Figure, bounds: (f64, f64, f64, f64)) -> Vec<Point> {
+    let mut points = Vec::new();
+    match (f1.get_bezier_segments(), f2.get_bezier_segments()) {
+        (Some(s1), Some(s2)) => {
+            for c1 in s1.iter() {
+                for c2 in s2.iter() {
+                    points.append(&mut get_curves_intersection(c1, c2));
+                }
+            }
+        }
+        (Some(segments), None) | (None, Some(segments)) => {
+            let other = if f1.get_bezier_segments().is_some() {
+                f2
+            } else {
+                f1
+            };
+            for curve in segments.iter() {
+                points.append(&mut match other {
+                    Figure::Line(l) => get_curve_line_intersection(curve, *l),
+                    Figure::Circle(c) => get_curve_circle_intersection(curve, *c),
+                    Figure::Arc(a) => get_curve_arc_intersection(curve, *a),
+                    _ => return get_polyline_intersections(f1, f2, bounds),
+                });
+            }
+        }
+        (None, None) => {}
+    }
+    dedup_points(points)
+}
+
+fn cross(ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+    ax * by - ay * bx
+}
+
+// intersection of the segments a-b and c-d
+pub fn get_segments_intersection(a: Point, b: Point, c: Point, d: Point) -> Option<Point> {
+    let (rx, ry) = (b.get_x() - a.get_x(), b.get_y() - a.get_y());
+    let (sx, sy) = (d.get_x() - c.get_x(), d.get_y() - c.get_y());
+    let (qx, qy) = (c.get_x() - a.get_x(), c.get_y() - a.get_y());
+    let denom = cross(rx, ry, sx, sy);
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = cross(qx, qy, sx, sy) / denom;
+    let u = cross(qx, qy, rx, ry) / denom;
+    if (-1e-9..=1.0 + 1e-9).contains(&t) && (-1e-9..=1.0 + 1e-9).contains(&u) {
+        Some(Point::new(a.get_x() + t * rx, a.get_y() + t * ry))
+    } else {
+        None
+    }
+}
+
+// intersection of the segment a-b with an infinite line
+pub fn get_segment_line_intersection(a: Point, b: Point, line: Line) -> Option<Point> {
+    let (rx, ry) = (b.get_x() - a.get_x(), b.get_y() - a.get_y());
+    let s = line.get_point_a();
+    let (sx, sy) = (
+        line.get_point_b().get_x() - s.get_x(),
+        line.get_point_b().get_y() - s.get_y(),
+    );
+    let (qx, qy) = (s.get_x() - a.get_x(), s.get_y() - a.get_y());
+    let denom = cross(rx, ry, sx, sy);
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = cross(qx, qy, sx, sy) / denom;
+    if (-1e-9..=1.0 + 1e-9).contains(&t) {
+        Some(Point::new(a.get_x() + t * rx, a.get_y() + t * ry))
+    } else {
+        None
+    }
+}
+
+pub fn get_segment_circle_intersection(a: Point, b: Point, c: Circle) -> Vec<Point> {
+    let len2 = (b.get_x() - a.get_x()).powi(2) + (b.get_y() - a.get_y()).powi(2);
+    if len2 < 1e-24 {
+        return vec![];
+    }
+    get_line_circle_intersection(Line::new(a, b), c)
+        .into_iter()
+        .filter(|p| {
+            let t = ((p.get_x() - a.get_x()) * (b.get_x() - a.get_x())
+                + (p.get_y() - a.get_y()) * (b.get_y() - a.get_y()))
+                / len2;
+            (-1e-9..=1.0 + 1e-9).contains(&t)
+        })
+        .collect()
+}
+
+// the curve lies in the convex hull of its control points, so a piece whose controls are
+// all on one side of the line can't cross it
+pub fn get_curve_line_intersection(curve: &Bezier, line: Line) -> Vec<Point> {
+    let mut points = Vec::new();
+    curve_line_intersection(curve, line, 0, &mut points);
+    dedup_points(points)
+}
+
+fn curve_line_intersection(curve: &Bezier, line: Line, depth: u32, points: &mut Vec<Point>) {
+    let a = line.get_point_a();
+    let b = line.get_point_b();
+    let sides: Vec<f64> = curve
+        .get_controls()
+        .iter()
+        .map(|p| {
+            cross(
+                b.get_x() - a.get_x(),
+                b.get_y() - a.get_y(),
+                p.get_x() - a.get_x(),
+                p.get_y() - a.get_y(),
+            )
+        })
+        .collect();
+    if sides.iter().all(|s| *s > 0.0) || sides.iter().all(|s| *s < 0.0) {
+        return;
+    }
+    if depth >= MAX_CURVE_DEPTH || curve.is_flat(CURVE_TOLERANCE) {
+        if let Some(p) = get_segment_line_intersection(curve.get_start(), curve.get_end(), line) {
+            points.push(p);
+        }
+        return;
+    }
+    let (left, right) = curve.split(0.5);
+    curve_line_intersection(&left, line, depth + 1, points);
+    curve_line_intersection(&right, line, depth + 1, points);
+}
+
+pub fn get_curve_circle_intersection(curve: &Bezier, c: Circle) -> Vec<Point> {
+    let mut points = Vec::new();
+    curve_circle_intersection(curve, c, 0, &mut points);
+    dedup_points(points)
+}
+
+fn curve_circle_intersection(curve: &Bezier, c: Circle, depth: u32, points: &mut Vec<Point>) {
+    let center = c.get_center();
+    let (x1, y1, x2, y2) = curve.get_bounds();
+    let closest = Point::new(center.get_x().clamp(x1, x2), center.get_y().clamp(y1, y2));
+    // the piece is too far away or completely inside the circle
+    if get_distance(closest, center) > c.get_rad()
+        || curve
+            .get_controls()
+            .iter()
+            .all(|p| get_distance(*p, center) < c.get_rad())
+    {
+        return;
+    }
+    if depth >= MAX_CURVE_DEPTH || curve.is_flat(CURVE_TOLERANCE) {
+        points.append(&mut get_segment_circle_intersection(
+            curve.get_start(),
+            curve.get_end(),
+            c,
+        ));
+        return;
+    }
+    let (left, right) = curve.split(0.5);
+    curve_circle_intersection(&left, c, depth + 1, points);
+    curve_circle_intersection(&right, c, depth + 1, points);
+}
+
+pub fn get_curve_arc_intersection(curve: &Bezier, a: Arc) -> Vec<Point> {
+    get_curve_circle_intersection(curve, a.to_circle())
+        .into_iter()
+        .filter(|p| a.contains_point(*p))
+        .collect()
+}
+
+pub fn get_curves_intersection(c1: &Bezier, c2: &Bezier) -> Vec<Point> {
+    let mut points = Vec::new();
+    curves_intersection(c1, c2, 0, &mut points);
+    dedup_points(points)
+}
+
+fn curves_intersection(c1: &Bezier, c2: &Bezier, depth: u32, points: &mut Vec<Point>) {
+    let (ax1, ay1, ax2, ay2) = c1.get_bounds();
+    let (bx1, by1, bx2, by2) = c2.get_bounds();
+    if ax2 < bx1 || bx2 < ax1 || ay2 < by1 || by2 < ay1 {
+        return;
+    }
+    let flat1 = c1.is_flat(CURVE_TOLERANCE);
+    let flat2 = c2.is_flat(CURVE_TOLERANCE);
+    if depth >= MAX_CURVE_DEPTH || (flat1 && flat2) {
+        if let Some(p) =
+            get_segments_intersection(c1.get_start(), c1.get_end(), c2.get_start(), c2.get_end())
+        {
+            points.push(p);
+        }
+        return;
+    }
+    // split the curve that is still bent, or the bigger one if both are
+    if flat2 || (!flat1 && (ax2 - ax1) + (ay2 - ay1) >= (bx2 - bx1) + (by2 - by1)) {
+        let (left, right) = c1.split(0.5);
+        curves_intersection(&left, c2, depth + 1, points);
+        curves_intersection(&right, c2, depth + 1, points);
+    } else {
+        let (left, right) = c2.split(0.5);
+        curves_intersection(c1, &left, depth + 1, points);
+        curves_intersection(c1, &right, depth + 1, points);
+    }
+}
+
+// roots of g between a and b, found from sign changes between samples and refined by bisection;
+// roots where g only touches zero without changing sign are missed
+fn find_roots(g: &dyn Fn(f64) -> Option<f64>, a: f64, b: f64) -> Vec<f64> {
+    let n = 1000;
+    let mut roots = Vec::new();
+    let mut x0 = a;
+    let mut v0 = g(a);
+    for i in 1..=n {
+        let x1 = a + (b - a) * i as f64 / n as f64;
+        let v1 = g(x1);
+        if let (Some(y0), Some(y1)) = (v0, v1) {
+            if y0 == 0.0 {
+                roots.push(x0);
+            } else if y0 * y1 < 0.0 {
+                if let Some(r) = bisect(g, x0, x1, y0) {
+                    roots.push(r);
+                }
+            }
+        }
+        x0 = x1;
+        v0 = v1;
+    }
+    if v0 == Some(0.0) {
+        roots.push(x0);
+    }
+    roots
+}
+
+fn bisect(g: &dyn Fn(f64) -> Option<f64>, mut lo: f64, mut hi: f64, mut y_lo: f64) -> Option<f64> {
+    for _ in 0..60 {
+        let mid = (lo + hi) / 2.0;
+        let y_mid = g(mid)?;
+        if y_mid == 0.0 {
+            return Some(mid);
+        }
+        if (y_mid < 0.0) == (y_lo < 0.0) {
+            lo = mid;
+            y_lo = y_mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let r = (lo + hi) / 2.0;
+    // a sign change across a pole isn't a root
+    if g(r)?.abs() < 1e-6 {
+        Some(r)
+    } else {
+        None
+    }
+}
+
+// the functions are searched from x = a to b
+pub fn get_function_line_intersection(
+    f: &FunctionGraph,
+    l: Line,
+    (a, b): (f64, f64),
+) -> Vec<Point> {
+    if l.get_slope().is_infinite() {
+        let x = l.get_point_a().get_x();
+        return match f.eval(x) {
+            Some(y) if a <= x && x <= b => vec![Point::new(x, y)],
+            _ => vec![],
+        };
+    }
+    find_roots(
+        &|x| Some(f.eval(x)? - (l.get_slope() * x + l.get_y_intercept())),
+        a,
+        b,
+    )
+    .into_iter()
+    .filter_map(|x| Some(Point::new(x, f.eval(x)?)))
+    .collect()
+}
+
+// the circle is split into its upper and lower half, each one is a function of x
+pub fn get_function_circle_intersection(
+    f: &FunctionGraph,
+    c: Circle,
+    (a, b): (f64, f64),
+) -> Vec<Point> {
+    let cx = c.get_center().get_x();
+    let cy = c.get_center().get_y();
+    let r = c.get_rad();
+    let lo = a.max(cx - r);
+    let hi = b.min(cx + r);
+    if lo >= hi {
+        return vec![];
+    }
+    let half = |x: f64| (r.powi(2) - (x - cx).powi(2)).max(0.0).sqrt();
+    let mut xs = find_roots(&|x| Some(f.eval(x)? - (cy + half(x))), lo, hi);
+    xs.append(&mut find_roots(
+        &|x| Some(f.eval(x)? - (cy - half(x))),
+        lo,
+        hi,
+    ));
+    dedup_points(
+        xs.into_iter()
+            .filter_map(|x| Some(Point::new(x, f.eval(x)?)))
+            .collect(),
+    )
+}
+
+pub fn get_function_arc_intersection(f: &FunctionGraph, a: Arc, x_range: (f64, f64)) -> Vec<Point> {
+    get_function_circle_intersection(f, a.to_circle(), x_range)
+        .into_iter()
+        .filter(|p| a.contains_point(*p))
+        .collect()
+}
+
+pub fn get_functions_intersection(
+    f: &FunctionGraph,
+    g: &FunctionGraph,
+    (lo, hi): (f64, f64),
+) -> Vec<Point> {
+    find_roots(&|x| Some(f.eval(x)? - g.eval(x)?), lo, hi)
+        .into_iter()
+        .filter_map(|x| Some(Point::new(x, f.eval(x)?)))
+        .collect()
+}
+
+// a parametric curve crosses another figure where g changes its sign along t
+fn get_parametric_roots(c: &Parametric, g: &dyn Fn(Point) -> Option<f64>) -> Vec<Point> {
+    let (a, b) = c.get_range();
+    dedup_points(
+        find_roots(&|t| g(c.eval(t)?), a, b)
+            .into_iter()
+            .filter_map(|t| c.eval(t))
+            .collect(),
+    )
+}
+
+pub fn get_parametric_line_intersection(c: &Parametric, l: Line) -> Vec<Point> {
+    let a = l.get_point_a();
+    let b = l.get_point_b();
+    let len = get_distance(a, b);
+    if len == 0.0 {
+        return vec![];
+    }
+    // signed distance to the line
+    get_parametric_roots(c, &|p| {
+        Some(
+            cross(
+                b.get_x() - a.get_x(),
+                b.get_y() - a.get_y(),
+                p.get_x() - a.get_x(),
+                p.get_y() - a.get_y(),
+            ) / len,
+        )
+    })
+}
+
+pub fn get_parametric_circle_intersection(c: &Parametric, circle: Circle) -> Vec<Point> {
+    get_parametric_roots(c, &|p| {
+        Some(get_distance(p, circle.get_center()) - circle.get_rad())
+    })
+}
+
+pub fn get_parametric_arc_intersection(c: &Parametric, a: Arc) -> Vec<Point> {
+    get_parametric_circle_intersection(c, a.to_circle())
+        .into_iter()
+        .filter(|p| a.contains_point(*p))
+        .collect()
+}
+
+pub fn get_parametric_function_intersection(
+    c: &Parametric,
+    f: &FunctionGraph,
+    (lo, hi): (f64, f64),
+) -> Vec<Point> {
+    get_parametric_roots(c, &|p| {
+        if p.get_x() < lo || p.get_x() > hi {
+            return None;
+        }
+        Some(p.get_y() - f.eval(p.get_x())?)
+    })
+}
+
+// fallback for two curves without a better method: intersect their polylines
+fn get_polyline_intersections(
+    f1: &Figure,
+    f2: &Figure,
+    bounds: (f64, f64, f64, f64),
+) -> Vec<Point> {
+    match (
+        f1.get_polylines(CURVE_TOLERANCE, bounds),
+        f2.get_polylines(CURVE_TOLERANCE, bounds),
+    ) {
+        (Some(p1), Some(p2)) => {
+            let mut points = Vec::new();
+            for a in p1.iter() {
+                for b in p2.iter() {
+                    points.append(&mut get_polylines_intersection(a, b));
+                }
+            }
+            dedup_points(points)
+        }
+        _ => vec![],
+    }
+}
+
+pub fn get_polylines_intersection(p1: &[Point], p2: &[Point]) -> Vec<Point> {
+    let mut points = Vec::new();
+    for a in p1.windows(2) {
+        let (ax1, ax2) = (
+            a[0].get_x().min(a[1].get_x()),
+            a[0].get_x().max(a[1].get_x()),
+        );
+        let (ay1, ay2) = (
+            a[0].get_y().min(a[1].get_y()),
+            a[0].get_y().max(a[1].get_y()),
+        );
+        for b in p2.windows(2) {
+            // skip the segments whose bounding boxes don't even touch
+            if b[0].get_x().max(b[1].get_x()) < ax1
+                || b[0].get_x().min(b[1].get_x()) > ax2
+                || b[0].get_y().max(b[1].get_y()) < ay1
+                || b[0].get_y().min(b[1].get_y()) > ay2
+            {
+                continue;
+            }
+            if let Some(p) = get_segments_intersection(a[0], a[1], b[0], b[1]) {
+                points.push(p);
+            }
+        }
+    }
+    dedup_points(points)
+}
+
+// real roots of ax² + bx + c, a tangent counts once
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs());
+    if scale == 0.0 {
+        return vec![];
+    }
+    if a.abs() <= 1e-12 * scale {
+        return if b == 0.0 { vec![] } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant.abs() <= 1e-12 * b.abs().max(4.0 * (a * c).abs()).max(f64::MIN_POSITIVE) {
+        return vec![-b / (2.0 * a)];
+    }
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // avoids subtracting two close numbers
+    let sign = if b < 0.0 { -1.0 } else { 1.0 };
+    let q = -0.5 * (b + sign * discriminant.sqrt());
+    vec![q / a, c / q]
+}
+
+// real roots of ax³ + bx² + cx + d
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs());
+    if a.abs() <= 1e-12 * scale {
+        return solve_quadratic(b, c, d);
+    }
+    // depressed cubic t³ + pt + q with x = t - b / 3a
+    let (b, c, d) = (b / a, c / a, d / a);
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b.powi(3) / 27.0 - b * c / 3.0 + d;
+    let shift = -b / 3.0;
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    let roots = if discriminant > 0.0 {
+        let s = discriminant.sqrt();
+        vec![(-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt()]
+    } else if p == 0.0 {
+        vec![0.0]
+    } else {
+        // three real roots, trigonometric form
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = (3.0 * q / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * f64::consts::PI * k as f64 / 3.0).cos())
+            .collect()
+    };
+    // a newton step cleans up the rounding
+    roots
+        .into_iter()
+        .map(|t| t + shift)
+        .map(|x| {
+            let f = ((x + b) * x + c) * x + d;
+            let df = (3.0 * x + 2.0 * b) * x + c;
+            if df.abs() > 1e-12 {
+                x - f / df
+            } else {
+                x
+            }
+        })
+        .collect()
+}
+
+// put y = mx + k (or x = const for a vertical line) into the conic and solve the quadratic
+pub fn get_line_conic_intersection(l: Line, conic: &Conic) -> Vec<Point> {
+    let [a, b, c, d, e, f] = conic.get_coefficients();
+    let m = l.get_slope();
+    if m.is_nan() {
+        return vec![];
+    }
+    if m.is_infinite() || m.abs() > 1e12 {
+        let x = l.get_point_a().get_x();
+        return solve_quadratic(c, b * x + e, a * x * x + d * x + f)
+            .into_iter()
+            .map(|y| Point::new(x, y))
+            .collect();
+    }
+    let k = l.get_y_intercept();
+    solve_quadratic(
+        a + b * m + c * m * m,
+        b * k + 2.0 * c * m * k + d + e * m,
+        c * k * k + e * k + f,
+    )
+    .into_iter()
+    .map(|x| Point::new(x, m * x + k))
+    .collect()
+}
+
+// a degenerate conic of the pencil c1 + λ c2 is a pair of lines through all the
+// intersection points, so they are where those lines meet c2
+pub fn get_conics_intersection(c1: &Conic, c2: &Conic) -> Vec<Point> {
+    let (m1, m2) = (c1.get_matrix(), c2.get_matrix());
+    let mut lines = vec![];
+    if c2.get_kind() == ConicKind::Degenerate {
+        lines.push((conic::split_degenerate(m2), c1));
+    } else {
+        // det(m1 + λ m2) is a cubic in λ, found from four of its values
+        let det = |lambda: f64| {
+            let mut m = m1;
+            for (r, row) in m.iter_mut().enumerate() {
+                for (k, v) in row.iter_mut().enumerate() {
+                    *v += lambda * m2[r][k];
+                }
+            }
+            (conic::get_determinant3(m), m)
+        };
+        let (p0, p1, pm1, p2) = (det(0.0).0, det(1.0).0, det(-1.0).0, det(2.0).0);
+        let c2_coefficient = (p1 + pm1) / 2.0 - p0;
+        let odd = (p1 - pm1) / 2.0;
+        let c3_coefficient = (p2 - 4.0 * c2_coefficient - p0 - 2.0 * odd) / 6.0;
+        for lambda in solve_cubic(c3_coefficient, c2_coefficient, odd - c3_coefficient, p0) {
+            lines.push((conic::split_degenerate(det(lambda).1), c2));
+        }
+    }
+    // the first real pair of lines already holds every real intersection
+    for (pair, other) in lines {
+        let pair: Vec<Line> = pair.into_iter().filter_map(conic::to_line).collect();
+        if pair.is_empty() {
+            continue;
+        }
+        let points = pair
+            .iter()
+            .flat_map(|l| get_line_conic_intersection(*l, other))
+            .collect();
+        return dedup_points(points);
+    }
+    vec![]
+}
+
+// subdivision finds a point twice when it lies where two pieces meet
+fn dedup_points(points: Vec<Point>) -> Vec<Point> {
+    let mut result: Vec<Point> = Vec::new();
+    for p in points {
+        if !result
+            .iter()
+            .any(|q| get_distance(*q, p) < CURVE_TOLERANCE * 10.0)
+        {
+            result.push(p);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has_point(points: &[Point], x: f64, y: f64) -> bool {
+        points
+            .iter()
+            .any(|p| get_distance(*p, Point::new(x, y)) < 1e-9)
+    }
+
+    #[test]
+    fn axis_parallel_lines_meet_circles_twice() {
+        let h = 0.75f64.sqrt();
+        let circle = Circle::new(Point::new(2.0, 3.0), Point::new(3.0, 3.0));
+        let horizontal = Line::new(Point::new(-5.0, 3.5), Point::new(5.0, 3.5));
+        let points = get_line_circle_intersection(horizontal, circle);
+        assert_eq!(points.len(), 2);
+        assert!(has_point(&points, 2.0 - h, 3.5) && has_point(&points, 2.0 + h, 3.5));
+        let vertical = Line::new(Point::new(2.5, 10.0), Point::new(2.5, -10.0));
+        let points = get_line_circle_intersection(vertical, circle);
+        assert_eq!(points.len(), 2);
+        assert!(has_point(&points, 2.5, 3.0 - h) && has_point(&points, 2.5, 3.0 + h));
+        // through the center
+        let horizontal = Line::new(Point::new(0.0, 3.0), Point::new(1.0, 3.0));
+        let points = get_line_circle_intersection(horizontal, circle);
+        assert!(has_point(&points, 1.0, 3.0) && has_point(&points, 3.0, 3.0));
+    }
+
+    // the curves are cut into flat pieces, so their points are only this close
+    fn near(points: &[Point], x: f64, y: f64) -> bool {
+        points
+            .iter()
+            .any(|p| get_distance(*p, Point::new(x, y)) < 2e-3)
+    }
+
+    #[test]
+    fn curves_against_lines_circles_and_curves() {
+        // x = 2t and y = 4t(1 - t), so y = 0.5 where x = 1 ± 1/√2
+        let curve = Bezier::quadratic(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 2.0),
+            Point::new(2.0, 0.0),
+        );
+        let s = f64::consts::FRAC_1_SQRT_2;
+        let line = Line::new(Point::new(-1.0, 0.5), Point::new(3.0, 0.5));
+        let points = get_curve_line_intersection(&curve, line);
+        assert_eq!(points.len(), 2);
+        assert!(near(&points, 1.0 - s, 0.5) && near(&points, 1.0 + s, 0.5));
+        let line = Line::new(Point::new(-1.0, 1.5), Point::new(3.0, 1.5));
+        assert!(get_curve_line_intersection(&curve, line).is_empty());
+
+        // with u = t(1 - t) the distance to (1, 0) is 0.9 where 16u² - 4u + 0.19 = 0
+        let circle = Circle::new(Point::new(1.0, 0.0), Point::new(1.9, 0.0));
+        let points = get_curve_circle_intersection(&curve, circle);
+        assert_eq!(points.len(), 4);
+        for u in [(4.0 + 3.84f64.sqrt()) / 32.0, (4.0 - 3.84f64.sqrt()) / 32.0] {
+            let dx = (1.0 - 4.0 * u).sqrt();
+            assert!(near(&points, 1.0 - dx, 4.0 * u) && near(&points, 1.0 + dx, 4.0 * u));
+        }
+
+        // the same curve upside down and moved up by 1 meets it at y = 0.5 again
+        let other = Bezier::quadratic(
+            Point::new(0.0, 1.0),
+            Point::new(1.0, -1.0),
+            Point::new(2.0, 1.0),
+        );
+        let points = get_curves_intersection(&curve, &other);
+        assert_eq!(points.len(), 2);
+        assert!(near(&points, 1.0 - s, 0.5) && near(&points, 1.0 + s, 0.5));
+        let mut apart = other.clone();
+        apart.move_bezier(0.0, 2.0);
+        assert!(get_curves_intersection(&curve, &apart).is_empty());
+    }
+
+    fn touches(l: Line, c: Circle) -> bool {
+        (get_line_distance(c.get_center(), l) - c.get_rad()).abs() < 1e-9
+    }
+
+    #[test]
+    fn common_tangents() {
+        let circle = |x: f64, y: f64, r: f64| Circle::new(Point::new(x, y), Point::new(x + r, y));
+        for (c1, c2, count) in [
+            // apart, touching from outside, touching from inside and one inside the other
+            (circle(10.0, 20.0, 2.0), circle(20.0, 25.0, 3.0), 4),
+            (circle(10.0, 20.0, 2.0), circle(13.0, 24.0, 3.0), 3),
+            (circle(10.0, 20.0, 5.0), circle(12.0, 20.0, 3.0), 1),
+            (circle(10.0, 20.0, 5.0), circle(11.0, 21.0, 2.0), 0),
+            // crossing and the same center
+            (circle(10.0, 20.0, 2.0), circle(12.0, 20.0, 1.0), 2),
+            (circle(10.0, 20.0, 2.0), circle(10.0, 20.0, 1.0), 0),
+        ] {
+            let tangents = get_common_tangents(c1, c2);
+            assert_eq!(tangents.len(), count, "{:?} {:?}", c1, c2);
+            for l in tangents {
+                assert!(touches(l, c1) && touches(l, c2), "{:?}", l);
+            }
+        }
+    }
+
+    #[test]
+    fn tangents_from_a_point() {
+        let c = Circle::new(Point::new(-30.0, 40.0), Point::new(-25.0, 40.0));
+        let p = Point::new(-20.0, 45.0);
+        let tangents = get_tangents_from_point(c, p);
+        assert_eq!(tangents.len(), 2);
+        for l in tangents {
+            assert!(touches(l, c) && get_line_distance(p, l) < 1e-9);
+        }
+        let on = Point::new(-30.0, 45.0);
+        let tangents = get_tangents_from_point(c, on);
+        assert_eq!(tangents.len(), 1);
+        assert!(touches(tangents[0], c) && get_line_distance(on, tangents[0]) < 1e-9);
+        assert!(get_tangents_from_point(c, Point::new(-28.0, 41.0)).is_empty());
+    }
+
+    #[test]
+    fn arcs_drop_what_is_outside_the_sweep() {
+        let pi = f64::consts::PI;
+        let h = 0.75f64.sqrt();
+        let upper = Arc::from_angles(Point::new(0.0, 0.0), 1.0, 0.0, pi);
+        let line = Line::new(Point::new(0.5, -2.0), Point::new(0.5, 2.0));
+        let points = get_line_arc_intersection(line, upper);
+        assert_eq!(points.len(), 1);
+        assert!(has_point(&points, 0.5, h));
+
+        let circle = Circle::new(Point::new(1.0, 0.0), Point::new(2.0, 0.0));
+        let points = get_circle_arc_intersection(circle, upper);
+        assert_eq!(points.len(), 1);
+        assert!(has_point(&points, 0.5, h));
+
+        let lower = Arc::from_angles(Point::new(1.0, 0.0), 1.0, pi, pi);
+        let points = get_arcs_intersection(upper, lower);
+        assert!(points.is_empty());
+        let left = Arc::from_angles(Point::new(1.0, 0.0), 1.0, pi / 2.0, pi);
+        let points = get_arcs_intersection(upper, left);
+        assert_eq!(points.len(), 1);
+        assert!(has_point(&points, 0.5, h));
+    }
+
+    #[test]
+    fn arcs_across_angle_zero() {
+        let pi = f64::consts::PI;
+        let right = Arc::from_angles(Point::new(0.0, 0.0), 1.0, -pi / 4.0, pi / 2.0);
+        let line = Line::new(Point::new(0.8, -2.0), Point::new(0.8, 2.0));
+        let points = get_line_arc_intersection(line, right);
+        assert_eq!(points.len(), 2);
+        assert!(has_point(&points, 0.8, 0.6) && has_point(&points, 0.8, -0.6));
+        let line = Line::new(Point::new(-0.8, -2.0), Point::new(-0.8, 2.0));
+        assert!(get_line_arc_intersection(line, right).is_empty());
+
+        let y = (1.0 - 0.875f64.powi(2)).sqrt();
+        let circle = Circle::new(Point::new(1.0, 0.0), Point::new(1.5, 0.0));
+        let points = get_circle_arc_intersection(circle, right);
+        assert_eq!(points.len(), 2);
+        assert!(has_point(&points, 0.875, y) && has_point(&points, 0.875, -y));
+        let circle = Circle::new(Point::new(-1.0, 0.0), Point::new(-1.5, 0.0));
+        assert!(get_circle_arc_intersection(circle, right).is_empty());
+    }
+}
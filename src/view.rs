@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+use crate::figures::point::Point;
+
+// maps world coordinates (y pointing up) to pixels on the canvas surface (y pointing down)
+#[derive(Debug, Clone, Copy)]
+pub struct View {
+    origin_x: f64, // where the world origin is on the surface
+    origin_y: f64,
+    scale: f64, // pixels per unit
+    width: i32,
+    height: i32,
+}
+
+impl View {
+    // the origin starts in the middle of the surface
+    pub fn new(width: i32, height: i32) -> View {
+        View {
+            origin_x: width as f64 / 2.0,
+            origin_y: height as f64 / 2.0,
+            scale: 50.0,
+            width,
+            height,
+        }
+    }
+
+    // the same view on a surface of another size, what was in the middle stays there
+    pub fn resize(self, width: i32, height: i32) -> View {
+        View {
+            origin_x: self.origin_x + (width - self.width) as f64 / 2.0,
+            origin_y: self.origin_y + (height - self.height) as f64 / 2.0,
+            width,
+            height,
+            ..self
+        }
+    }
+
+    // the same view magnified by factor, what is in the middle stays there
+    pub fn zoom(self, factor: f64) -> View {
+        let (x, y) = (self.width as f64 / 2.0, self.height as f64 / 2.0);
+        View {
+            origin_x: x + (self.origin_x - x) * factor,
+            origin_y: y + (self.origin_y - y) * factor,
+            scale: self.scale * factor,
+            ..self
+        }
+    }
+
+    pub fn to_screen(self, p: Point) -> (f64, f64) {
+        (
+            self.origin_x + p.get_x() * self.scale,
+            self.origin_y - p.get_y() * self.scale,
+        )
+    }
+
+    pub fn to_world(self, x: f64, y: f64) -> Point {
+        Point::new(
+            (x - self.origin_x) / self.scale,
+            (self.origin_y - y) / self.scale,
+        )
+    }
+
+    // a length in world units as pixels
+    pub fn to_pixels(self, len: f64) -> f64 {
+        len * self.scale
+    }
+
+    // (min x, max x) of what is visible
+    pub fn get_x_range(&self) -> (f64, f64) {
+        (
+            self.to_world(0.0, 0.0).get_x(),
+            self.to_world(self.width as f64, 0.0).get_x(),
+        )
+    }
+
+    // (min y, max y) of what is visible
+    pub fn get_y_range(&self) -> (f64, f64) {
+        (
+            self.to_world(0.0, self.height as f64).get_y(),
+            self.to_world(0.0, 0.0).get_y(),
+        )
+    }
+
+    // (min x, min y, max x, max y) of what is visible, where the curves without an end are
+    // drawn and intersected
+    pub fn get_bounds(&self) -> (f64, f64, f64, f64) {
+        let ((x_min, x_max), (y_min, y_max)) = (self.get_x_range(), self.get_y_range());
+        (x_min, y_min, x_max, y_max)
+    }
+
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn get_width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> i32 {
+        self.height
+    }
+}